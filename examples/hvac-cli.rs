@@ -35,12 +35,12 @@ fn main() {
 
     println!(">>> autodiscovering broadlink devices...");
     let discovered = Device::list(None).expect("Could not enumerate devices!");
-    for device in discovered {
+    for device in discovered.devices {
         println!(">>> device authentication ...");
         let addr = device.get_info().address;
         println!(">>> device at {} => {}", addr, device);
 
-        let hvac = match device {
+        let mut hvac = match device {
             Device::Hvac { hvac } => hvac,
             _ => {
                 return;
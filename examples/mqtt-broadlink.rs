@@ -1,3 +1,5 @@
+#![allow(clippy::needless_return)]
+
 use std::{collections::HashMap, net::Ipv4Addr, time::Duration};
 
 use clap::Parser;
@@ -62,15 +64,15 @@ async fn main() {
     info!("Starting broadlink-mqtt v{}...", env!("CARGO_PKG_VERSION"));
 
     // Get the devices
-    let devices = get_devices(&args).expect("Could not find devices!");
+    let devices = get_devices(&args).await.expect("Could not find devices!");
 
     // Start an update thread on all the devices
     let mut threads: Vec<tokio::task::JoinHandle<_>> = vec![];
-    for (_, device) in devices {
+    for (_, mut device) in devices {
         let args_copy = args.clone();
 
         threads.push(tokio::spawn(async move {
-            handle_device(&device, args_copy)
+            handle_device(&mut device, args_copy)
                 .await
                 .expect("Could not handle device!");
         }));
@@ -82,7 +84,7 @@ async fn main() {
     }
 }
 
-async fn handle_device(device: &Device, args: Args) -> Result<(), String> {
+async fn handle_device(device: &mut Device, args: Args) -> Result<(), String> {
     let info = device.get_info();
     let sanitized_name = info
         .friendly_model
@@ -154,11 +156,11 @@ async fn handle_device(device: &Device, args: Args) -> Result<(), String> {
 
                 info!("Parsing message => {:?}", data);
                 let parts: Vec<&str> = data.split(" ").collect();
-                let cmd = parts.get(0);
+                let cmd = parts.first();
                 let payload = parts.get(1);
 
                 // Make sure that we skip non-cmds.
-                if cmd == None || payload == None {
+                if cmd.is_none() || payload.is_none() {
                     warn!(
                         "Skipping incomplete command for {}: {}",
                         &sanitized_name, &data
@@ -175,12 +177,12 @@ async fn handle_device(device: &Device, args: Args) -> Result<(), String> {
                 );
                 match unwrapped_cmd {
                     "blast" => {
-                        handle_blast(&mqtt_client, &device, &sanitized_name, &unwrapped_payload)
+                        handle_blast(&mqtt_client, device, &sanitized_name, unwrapped_payload)
                             .await
                             .expect("Could not handle blast!")
                     }
                     "learn" => {
-                        handle_learn(&mqtt_client, &device, &sanitized_name, &unwrapped_payload)
+                        handle_learn(&mqtt_client, device, &sanitized_name, unwrapped_payload)
                             .await
                             .expect("Could not handle learn!")
                     }
@@ -198,7 +200,7 @@ async fn handle_device(device: &Device, args: Args) -> Result<(), String> {
 /// Handles a blast command
 async fn handle_blast(
     client: &Client,
-    device: &Device,
+    device: &mut Device,
     sanitized_name: &str,
     payload: &str,
 ) -> Result<(), String> {
@@ -213,10 +215,10 @@ async fn handle_blast(
     info!("Blasting payload {:?}", &hex);
 
     match device {
-        Device::Remote { remote } => match remote.send_code(&hex) {
+        Device::Remote { remote } => match remote.send_code_async(&hex).await {
             Err(e) => {
                 let err_msg = Publish::new(
-                    get_path(&sanitized_name, &["blast_error"]),
+                    get_path(sanitized_name, &["blast_error"]),
                     e.to_string().into(),
                 );
                 client
@@ -235,7 +237,7 @@ async fn handle_blast(
     };
 
     // Tell the MQTT broker that we successfully blasted
-    let ok_msg = Publish::new(get_path(&sanitized_name, &["blast_status"]), "ok".into());
+    let ok_msg = Publish::new(get_path(sanitized_name, &["blast_status"]), "ok".into());
     client
         .publish(&ok_msg)
         .await
@@ -247,7 +249,7 @@ async fn handle_blast(
 /// Handles a learn command
 async fn handle_learn(
     client: &Client,
-    device: &Device,
+    device: &mut Device,
     sanitized_name: &str,
     payload: &str,
 ) -> Result<(), String> {
@@ -262,8 +264,8 @@ async fn handle_learn(
 
     // Try to learn the code
     let code = match payload {
-        "ir" => remote.learn_ir(),
-        "rf" => remote.learn_rf(),
+        "ir" => remote.learn_ir_async().await,
+        "rf" => remote.learn_rf_async().await,
         _ => {
             warn!("Skipping invalid learn mode {}", payload);
             return Ok(());
@@ -304,19 +306,25 @@ fn get_path(sanitized_name: &str, paths: &[&str]) -> String {
 }
 
 /// Get devices through autodiscovery or manual clients
-fn get_devices(args: &Args) -> Result<DeviceMap, String> {
+async fn get_devices(args: &Args) -> Result<DeviceMap, String> {
     let mut result: DeviceMap = DeviceMap::new();
 
     // Auto discover devices, if enabled
     if args.auto_discover {
         info!("Autodiscovering devices...");
-        let discovered = Device::list(args.local_ip).expect("Could not enumerate devices!");
-        for device in discovered {
+        let discovered = Device::list_async(args.local_ip)
+            .await
+            .expect("Could not enumerate devices!");
+        for device in discovered.devices {
             let addr = device.get_info().address;
 
             info!("Discovered device at {} => {}", addr, device);
             result.insert(addr, device);
         }
+
+        for failed in discovered.failed {
+            warn!("Skipping unusable device {}", failed);
+        }
     }
 
     // Add all of the static clients, if present
@@ -324,13 +332,14 @@ fn get_devices(args: &Args) -> Result<DeviceMap, String> {
         info!("Adding client at {}", addr);
 
         // Skip clients that we know of already
-        if result.contains_key(&addr) {
+        if result.contains_key(addr) {
             warn!("Skipping duplicate client {}", addr);
             continue;
         }
 
-        let client = Device::from_ip(*addr, args.local_ip)
-            .expect(format!("Could not add client {}!", addr).as_str());
+        let client = Device::from_ip_async(*addr, args.local_ip)
+            .await
+            .unwrap_or_else(|e| panic!("Could not add client {}! {}", addr, e));
 
         info!("Client added => {}", client);
         result.insert(*addr, client);
@@ -1,3 +1,5 @@
+#![allow(clippy::needless_return)]
+
 use std::net::Ipv4Addr;
 
 use clap::{ArgEnum, Parser, Subcommand};
@@ -84,6 +86,7 @@ enum LearnCodeType {
 }
 
 #[derive(ArgEnum, Clone, Debug)]
+#[allow(clippy::upper_case_acronyms)]
 enum WirelessConnectionArg {
     None,
     WEP,
@@ -128,13 +131,13 @@ fn blast(local_ip: Option<Ipv4Addr>, device_ip: Ipv4Addr, code: String) -> Resul
     let hex_code = hex::decode(code).expect("Invalid code!");
 
     // Ensure that the device is a remote
-    let remote = match device {
+    let mut remote = match device {
         Device::Remote { remote } => remote,
         _ => return Err("Device specified is not a remote!".into()),
     };
 
     println!("Blasting IR/RF code: {:02X?}", hex_code);
-    return remote.send_code(&hex_code);
+    return remote.send_code(&hex_code).map_err(|e| e.to_string());
 }
 
 fn connect(
@@ -185,7 +188,7 @@ fn learn(
 
     // Ensure that the device is a remote
     let device = Device::from_ip(device_ip, local_ip).expect("Could not connect to device!");
-    let remote = match device {
+    let mut remote = match device {
         Device::Remote { remote } => remote,
         _ => return Err("Device specified is not a remote!".into()),
     };
@@ -209,12 +212,20 @@ fn list(local_ip: Option<Ipv4Addr>) -> Result<(), String> {
     // Get the devices
     let devs = Device::list(local_ip).expect("Could not list devices!");
 
-    if devs.len() == 0 {
+    if devs.devices.is_empty() {
         println!("No devices found.")
     } else {
         println!("Devices:");
 
-        for dev in devs {
+        for dev in devs.devices {
+            println!("  {}", dev);
+        }
+    }
+
+    if !devs.failed.is_empty() {
+        println!("Unsupported devices:");
+
+        for dev in devs.failed {
             println!("  {}", dev);
         }
     }
@@ -0,0 +1,108 @@
+use std::{net::Ipv4Addr, sync::Arc};
+
+use packed_struct::PackedStruct;
+use phf::phf_map;
+
+use crate::{
+    constants, lookup_model,
+    network::{
+        util::reverse_mac, AlarmDataCommand, AlarmDataMessage, AlarmSensor, DiscoveryResponse,
+        UdpTransport,
+    },
+    traits::Transport,
+    Device, DeviceInfo, Error, RetryPolicy,
+};
+
+/// A mapping of alarm hub device codes to their friendly model equivalent.
+pub const ALARM_CODES: phf::Map<u16, &'static str> = phf_map! {
+    0x2722u16 => "S2KIT",
+};
+
+/// A broadlink S1C alarm hub, with paired door / window sensors, motion sensors and key fobs.
+#[derive(Debug, Clone)]
+pub struct AlarmDevice {
+    /// Base information about the alarm hub.
+    pub info: DeviceInfo,
+
+    /// The transport used to communicate with the device.
+    pub transport: Arc<dyn Transport>,
+
+    /// The policy used to retry failed commands sent to the device.
+    pub retry_policy: RetryPolicy,
+}
+
+impl AlarmDevice {
+    /// Create a new AlarmDevice.
+    ///
+    /// Note: This should not be called directly. Please use [Device::from_ip] or
+    /// [Device::list] instead.
+    pub fn new(name: &str, addr: Ipv4Addr, response: DiscoveryResponse) -> AlarmDevice {
+        // Get the type of alarm hub
+        let friendly_model: String = lookup_model(response.model_code)
+            .map_or("Unknown".into(), |model| model.name)
+            .to_string();
+
+        return Self {
+            info: DeviceInfo {
+                address: addr,
+                mac: reverse_mac(response.mac),
+                model_code: response.model_code,
+                friendly_type: "Alarm".into(),
+                friendly_model,
+                name: name.into(),
+                auth_id: 0, // This will be populated when authenticated.
+                key: constants::INITIAL_KEY,
+                is_locked: response.is_locked,
+                interface: None,
+            },
+            transport: Arc::new(UdpTransport::default()),
+            retry_policy: RetryPolicy::default(),
+        };
+    }
+
+    /// Get the sensors paired with the hub, along with their current status.
+    pub fn get_sensors(&mut self) -> Result<Vec<AlarmSensor>, Error> {
+        let response = self.send_command(AlarmDataCommand::GetSensors)?;
+
+        return AlarmDataMessage::unpack_sensors(&response);
+    }
+
+    /// Sends a raw command to the alarm hub.
+    /// Note: Try to avoid using this method in favor of [AlarmDevice::get_sensors].
+    pub fn send_command(&mut self, command: AlarmDataCommand) -> Result<Vec<u8>, Error> {
+        let packed = AlarmDataMessage::new(command).pack()?;
+
+        // We cast this object to a generic device in order to make use of the shared
+        // helper utilities.
+        return Device::Alarm {
+            alarm: self.clone(),
+        }
+        .send_command_for::<AlarmDataMessage>(&mut self.info, &packed);
+    }
+}
+
+/// Async versions of the alarm hub operations, for use within a tokio runtime.
+#[cfg(feature = "tokio")]
+impl AlarmDevice {
+    /// Async version of [AlarmDevice::get_sensors].
+    pub async fn get_sensors_async(&mut self) -> Result<Vec<AlarmSensor>, Error> {
+        let response = self
+            .send_command_async(AlarmDataCommand::GetSensors)
+            .await?;
+
+        return AlarmDataMessage::unpack_sensors(&response);
+    }
+
+    /// Async version of [AlarmDevice::send_command].
+    pub async fn send_command_async(
+        &mut self,
+        command: AlarmDataCommand,
+    ) -> Result<Vec<u8>, Error> {
+        let packed = AlarmDataMessage::new(command).pack()?;
+        return Device::Alarm {
+            alarm: self.clone(),
+        }
+        .send_command_for_async::<AlarmDataMessage>(&mut self.info, &packed)
+        .await;
+    }
+}
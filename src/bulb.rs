@@ -0,0 +1,155 @@
+use std::{net::Ipv4Addr, sync::Arc};
+
+use phf::phf_map;
+
+use crate::{
+    constants, lookup_model,
+    network::{
+        util::reverse_mac, BulbState, DiscoveryResponse, JsonDataCommand, JsonDataMessage,
+        UdpTransport,
+    },
+    traits::Transport,
+    Device, DeviceFamily, DeviceInfo, DeviceModel, Error, RetryPolicy,
+};
+
+/// The generation of a bulb, which decides how its messages are framed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulbType {
+    /// LB1 bulbs, which prefix their messages with the length.
+    Lb1,
+
+    /// LB2 bulbs.
+    Lb2,
+}
+
+/// A mapping of bulb device codes to their type and friendly model equivalent.
+pub const BULB_CODES: phf::Map<u16, (BulbType, &'static str)> = phf_map! {
+    0x5043u16 => (BulbType::Lb1, "SB800TD"),
+    0x504Eu16 => (BulbType::Lb1, "LB1"),
+    0x606Eu16 => (BulbType::Lb1, "SB500TD"),
+    0x60C7u16 => (BulbType::Lb1, "LB1"),
+    0x60C8u16 => (BulbType::Lb1, "LB1"),
+    0x6112u16 => (BulbType::Lb1, "LB1"),
+    0x644Bu16 => (BulbType::Lb1, "LB1"),
+    0x644Cu16 => (BulbType::Lb1, "LB27 R1"),
+    0x644Eu16 => (BulbType::Lb1, "LB26 R1"),
+    0xA4F4u16 => (BulbType::Lb2, "LB27 R1"),
+    0xA5F7u16 => (BulbType::Lb2, "LB27 R1"),
+};
+
+/// A broadlink smart bulb.
+#[derive(Debug, Clone)]
+pub struct BulbDevice {
+    /// Base information about the bulb.
+    pub info: DeviceInfo,
+
+    /// The generation of the bulb.
+    pub bulb_type: BulbType,
+
+    /// The transport used to communicate with the device.
+    pub transport: Arc<dyn Transport>,
+
+    /// The policy used to retry failed commands sent to the device.
+    pub retry_policy: RetryPolicy,
+}
+
+impl BulbDevice {
+    /// Create a new BulbDevice.
+    ///
+    /// Note: This should not be called directly. Please use [Device::from_ip] or
+    /// [Device::list] instead.
+    pub fn new(name: &str, addr: Ipv4Addr, response: DiscoveryResponse) -> BulbDevice {
+        // Get the type of bulb
+        let (bulb_type, friendly_model) = match lookup_model(response.model_code) {
+            Some(DeviceModel {
+                family: DeviceFamily::Bulb(bulb_type),
+                name,
+            }) => (bulb_type, name),
+            _ => (BulbType::Lb1, "Unknown".into()),
+        };
+
+        return Self {
+            info: DeviceInfo {
+                address: addr,
+                mac: reverse_mac(response.mac),
+                model_code: response.model_code,
+                friendly_type: "Bulb".into(),
+                friendly_model: friendly_model.into(),
+                name: name.into(),
+                auth_id: 0, // This will be populated when authenticated.
+                key: constants::INITIAL_KEY,
+                is_locked: response.is_locked,
+                interface: None,
+            },
+            bulb_type,
+            transport: Arc::new(UdpTransport::default()),
+            retry_policy: RetryPolicy::default(),
+        };
+    }
+
+    /// Get the current state of the bulb.
+    pub fn get_state(&mut self) -> Result<BulbState, Error> {
+        return self.send_command(&BulbState::default(), JsonDataCommand::GetState);
+    }
+
+    /// Update the state of the bulb, returning the resulting state.
+    ///
+    /// Only the fields which are set are changed, e.g. setting only the brightness keeps the color as-is.
+    pub fn set_state(&mut self, state: &BulbState) -> Result<BulbState, Error> {
+        return self.send_command(state, JsonDataCommand::SetState);
+    }
+
+    /// Sends a raw command to the bulb.
+    /// Note: Try to avoid using this method in favor of [BulbDevice::get_state] and [BulbDevice::set_state].
+    pub fn send_command(
+        &mut self,
+        state: &BulbState,
+        command: JsonDataCommand,
+    ) -> Result<BulbState, Error> {
+        // Construct the data message
+        let length_prefix = self.bulb_type == BulbType::Lb1;
+        let packed = state.pack_message(command, length_prefix)?;
+
+        // We cast this object to a generic device in order to make use of the shared
+        // helper utilities.
+        let response = Device::Bulb { bulb: self.clone() }
+            .send_command_for::<JsonDataMessage>(&mut self.info, &packed)?;
+
+        return BulbState::unpack_message(&response, length_prefix);
+    }
+}
+
+/// Async versions of the bulb operations, for use within a tokio runtime.
+#[cfg(feature = "tokio")]
+impl BulbDevice {
+    /// Async version of [BulbDevice::get_state].
+    pub async fn get_state_async(&mut self) -> Result<BulbState, Error> {
+        return self
+            .send_command_async(&BulbState::default(), JsonDataCommand::GetState)
+            .await;
+    }
+
+    /// Async version of [BulbDevice::set_state].
+    pub async fn set_state_async(&mut self, state: &BulbState) -> Result<BulbState, Error> {
+        return self
+            .send_command_async(state, JsonDataCommand::SetState)
+            .await;
+    }
+
+    /// Async version of [BulbDevice::send_command].
+    pub async fn send_command_async(
+        &mut self,
+        state: &BulbState,
+        command: JsonDataCommand,
+    ) -> Result<BulbState, Error> {
+        // Construct the data message
+        let length_prefix = self.bulb_type == BulbType::Lb1;
+        let packed = state.pack_message(command, length_prefix)?;
+
+        let response = Device::Bulb { bulb: self.clone() }
+            .send_command_for_async::<JsonDataMessage>(&mut self.info, &packed)
+            .await?;
+
+        return BulbState::unpack_message(&response, length_prefix);
+    }
+}
@@ -0,0 +1,254 @@
+use std::{cmp::Ordering, net::Ipv4Addr, sync::Arc, time::Duration};
+
+use packed_struct::PackedStruct;
+use phf::phf_map;
+
+use crate::{
+    constants, lookup_model,
+    network::{
+        util::reverse_mac, CurtainCommand, CurtainDataMessage, DiscoveryResponse, UdpTransport,
+    },
+    traits::Transport,
+    Device, DeviceInfo, Error, RetryPolicy,
+};
+
+/// A mapping of curtain motor device codes to their friendly model equivalent.
+pub const CURTAIN_CODES: phf::Map<u16, &'static str> = phf_map! {
+    0x4E4Du16 => "DT360E-45/20",
+};
+
+/// How many times to poll the position while moving to a target position.
+const MOVING_ATTEMPTS: usize = 600;
+
+/// How long to wait between polls while moving to a target position.
+const MOVING_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A Dooya curtain motor.
+#[derive(Debug, Clone)]
+pub struct CurtainDevice {
+    /// Base information about the curtain motor.
+    pub info: DeviceInfo,
+
+    /// The transport used to communicate with the device.
+    pub transport: Arc<dyn Transport>,
+
+    /// The policy used to retry failed commands sent to the device.
+    pub retry_policy: RetryPolicy,
+}
+
+impl CurtainDevice {
+    /// Create a new CurtainDevice.
+    ///
+    /// Note: This should not be called directly. Please use [Device::from_ip] or
+    /// [Device::list] instead.
+    pub fn new(name: &str, addr: Ipv4Addr, response: DiscoveryResponse) -> CurtainDevice {
+        // Get the type of curtain motor
+        let friendly_model: String = lookup_model(response.model_code)
+            .map_or("Unknown".into(), |model| model.name)
+            .to_string();
+
+        return Self {
+            info: DeviceInfo {
+                address: addr,
+                mac: reverse_mac(response.mac),
+                model_code: response.model_code,
+                friendly_type: "Curtain".into(),
+                friendly_model,
+                name: name.into(),
+                auth_id: 0, // This will be populated when authenticated.
+                key: constants::INITIAL_KEY,
+                is_locked: response.is_locked,
+                interface: None,
+            },
+            transport: Arc::new(UdpTransport::default()),
+            retry_policy: RetryPolicy::default(),
+        };
+    }
+
+    /// Start opening the curtain.
+    pub fn open(&mut self) -> Result<(), Error> {
+        self.send_command(CurtainCommand::Open)?;
+
+        return Ok(());
+    }
+
+    /// Start closing the curtain.
+    pub fn close(&mut self) -> Result<(), Error> {
+        self.send_command(CurtainCommand::Close)?;
+
+        return Ok(());
+    }
+
+    /// Stop moving the curtain.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        self.send_command(CurtainCommand::Stop)?;
+
+        return Ok(());
+    }
+
+    /// Get the position of the curtain, in percent, where 0 is closed and 100 is open.
+    pub fn get_position(&mut self) -> Result<u8, Error> {
+        let response = self.send_command(CurtainCommand::GetPosition)?;
+
+        return CurtainDataMessage::unpack_position(&response);
+    }
+
+    /// Move the curtain to a position, in percent, blocking until it is reached.
+    ///
+    /// The position is polled while moving and passed to `progress`. Returns the final position.
+    pub fn set_position<F>(&mut self, position: u8, mut progress: F) -> Result<u8, Error>
+    where
+        F: FnMut(u8),
+    {
+        check_position(position)?;
+
+        // Start moving in the right direction, if needed
+        let mut current = self.get_position()?;
+        progress(current);
+
+        let command = match direction(current, position) {
+            Some(c) => c,
+            None => return Ok(current),
+        };
+        self.send_command(command)?;
+
+        // Block until we reach the position or timeout
+        for _ in 0..MOVING_ATTEMPTS {
+            // Sleep before polling again
+            std::thread::sleep(MOVING_INTERVAL);
+
+            current = self.get_position()?;
+            progress(current);
+
+            if direction(current, position) != Some(command) {
+                self.stop()?;
+                return Ok(current);
+            }
+        }
+
+        // If we haven't reached the position up until now, then we failed
+        self.stop()?;
+        return Err(Error::Timeout);
+    }
+
+    /// Sends a raw command to the curtain motor.
+    /// Note: Try to avoid using this method in favor of [CurtainDevice::open], [CurtainDevice::get_position], etc.
+    pub fn send_command(&mut self, command: CurtainCommand) -> Result<Vec<u8>, Error> {
+        // Construct the data message
+        let msg = CurtainDataMessage::new(command);
+        let packed = msg.pack()?;
+
+        // We cast this object to a generic device in order to make use of the shared
+        // helper utilities.
+        return Device::Curtain {
+            curtain: self.clone(),
+        }
+        .send_command_for::<CurtainDataMessage>(&mut self.info, &packed);
+    }
+}
+
+/// Async versions of the curtain operations, for use within a tokio runtime.
+#[cfg(feature = "tokio")]
+impl CurtainDevice {
+    /// Async version of [CurtainDevice::open].
+    pub async fn open_async(&mut self) -> Result<(), Error> {
+        self.send_command_async(CurtainCommand::Open).await?;
+
+        return Ok(());
+    }
+
+    /// Async version of [CurtainDevice::close].
+    pub async fn close_async(&mut self) -> Result<(), Error> {
+        self.send_command_async(CurtainCommand::Close).await?;
+
+        return Ok(());
+    }
+
+    /// Async version of [CurtainDevice::stop].
+    pub async fn stop_async(&mut self) -> Result<(), Error> {
+        self.send_command_async(CurtainCommand::Stop).await?;
+
+        return Ok(());
+    }
+
+    /// Async version of [CurtainDevice::get_position].
+    pub async fn get_position_async(&mut self) -> Result<u8, Error> {
+        let response = self.send_command_async(CurtainCommand::GetPosition).await?;
+
+        return CurtainDataMessage::unpack_position(&response);
+    }
+
+    /// Async version of [CurtainDevice::set_position].
+    pub async fn set_position_async<F>(
+        &mut self,
+        position: u8,
+        mut progress: F,
+    ) -> Result<u8, Error>
+    where
+        F: FnMut(u8),
+    {
+        check_position(position)?;
+
+        // Start moving in the right direction, if needed
+        let mut current = self.get_position_async().await?;
+        progress(current);
+
+        let command = match direction(current, position) {
+            Some(c) => c,
+            None => return Ok(current),
+        };
+        self.send_command_async(command).await?;
+
+        // Block until we reach the position or timeout
+        for _ in 0..MOVING_ATTEMPTS {
+            // Sleep before polling again
+            tokio::time::sleep(MOVING_INTERVAL).await;
+
+            current = self.get_position_async().await?;
+            progress(current);
+
+            if direction(current, position) != Some(command) {
+                self.stop_async().await?;
+                return Ok(current);
+            }
+        }
+
+        // If we haven't reached the position up until now, then we failed
+        self.stop_async().await?;
+        return Err(Error::Timeout);
+    }
+
+    /// Async version of [CurtainDevice::send_command].
+    pub async fn send_command_async(&mut self, command: CurtainCommand) -> Result<Vec<u8>, Error> {
+        // Construct the data message
+        let msg = CurtainDataMessage::new(command);
+        let packed = msg.pack()?;
+
+        return Device::Curtain {
+            curtain: self.clone(),
+        }
+        .send_command_for_async::<CurtainDataMessage>(&mut self.info, &packed)
+        .await;
+    }
+}
+
+/// Ensures that a position is a valid percentage.
+fn check_position(position: u8) -> Result<(), Error> {
+    if position > 100 {
+        return Err(Error::InvalidArgument(format!(
+            "Position must be between 0 and 100, got {}",
+            position
+        )));
+    }
+
+    return Ok(());
+}
+
+/// Get the command moving the curtain from its current position towards the target, if any.
+fn direction(current: u8, target: u8) -> Option<CurtainCommand> {
+    return match current.cmp(&target) {
+        Ordering::Less => Some(CurtainCommand::Open),
+        Ordering::Greater => Some(CurtainCommand::Close),
+        Ordering::Equal => None,
+    };
+}
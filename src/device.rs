@@ -1,19 +1,28 @@
 use std::{
+    collections::HashSet,
     fmt,
     net::{IpAddr, Ipv4Addr, SocketAddr},
     str::from_utf8,
+    sync::Arc,
 };
 
 use packed_struct::prelude::{PackedStruct, PackedStructSlice};
 
+#[cfg(feature = "tokio")]
+use crate::DiscoveryStream;
+
 use crate::{
+    constants, lookup_model,
     network::{
-        util::{local_ip_or, send_and_receive_many, send_and_receive_one},
+        util::{local_ip_or, reverse_mac, send_and_receive_one, unpack_zero_padded},
         AuthenticationMessage, AuthenticationResponse, CommandMessage, DiscoveryMessage,
-        DiscoveryResponse, WirelessConnection, WirelessConnectionMessage,
+        DiscoveryResponse, DiscoveryTarget, NetworkConfig, NetworkInterface, UdpTransport,
+        WirelessConnection, WirelessConnectionMessage,
     },
-    traits::{CommandTrait, DeviceTrait},
-    DeviceInfo, HvacDevice, RemoteDevice, HVAC_CODES, REMOTE_CODES,
+    traits::{CommandTrait, DeviceTrait, Transport},
+    AlarmDevice, BulbDevice, CurtainDevice, DeviceFamily, DeviceInfo, DeviceSession, DiscoveryIter,
+    DiscoveryResult, Error, FailedDevice, GenericDevice, HvacDevice, PlugDevice, PowerStripDevice,
+    RemoteDevice, RetryPolicy, SensorDevice, ThermostatDevice,
 };
 
 /// A generic broadlink device.
@@ -22,127 +31,613 @@ pub enum Device {
     Remote { remote: RemoteDevice },
     /// Air Conditioner/HVAC device.
     Hvac { hvac: HvacDevice },
+    /// A smart plug.
+    Plug { plug: PlugDevice },
+    /// A power strip with individually switchable sockets.
+    PowerStrip { strip: PowerStripDevice },
+    /// An environmental sensor.
+    Sensor { sensor: SensorDevice },
+    /// A smart bulb.
+    Bulb { bulb: BulbDevice },
+    /// A Hysen / Beok thermostat.
+    Thermostat { thermostat: ThermostatDevice },
+    /// A curtain motor.
+    Curtain { curtain: CurtainDevice },
+    /// An alarm hub with paired security sensors.
+    Alarm { alarm: AlarmDevice },
+    /// A device of an unknown family, which only supports raw commands.
+    Generic { generic: GenericDevice },
 }
 
 /// Represents a generic device. See the different implementations for more specific info.
 impl Device {
     /// Create a new device directly from an IP.
-    pub fn from_ip(addr: Ipv4Addr, local_ip: Option<Ipv4Addr>) -> Result<Device, String> {
-        // Grab the first non-loopback address
-        let selected_ip = local_ip_or(local_ip)?;
-
-        // Construct the discovery message
-        let port = 42424;
-        let discover = DiscoveryMessage::new(selected_ip, port, None)?;
-        let msg = discover
-            .pack()
-            .map_err(|e| format!("Could not pack DiscoveryMessage! {}", e))?;
-
-        return Ok(
-            send_and_receive_one(&msg, addr, Some(port), |bytes_received, bytes, addr| {
-                return create_device_from_packet(addr, bytes_received, bytes);
-            })
-            .map_err(|e| format!("Could not communicate with specified device! {}", e))?,
+    pub fn from_ip(addr: Ipv4Addr, local_ip: Option<Ipv4Addr>) -> Result<Device, Error> {
+        return Device::from_ip_with_transport(addr, local_ip, Arc::new(UdpTransport::default()));
+    }
+
+    /// Create a new device directly from an IP, using the specified network settings.
+    pub fn from_ip_with_config(
+        addr: Ipv4Addr,
+        local_ip: Option<Ipv4Addr>,
+        config: NetworkConfig,
+    ) -> Result<Device, Error> {
+        return Device::from_ip_with_transport(
+            addr,
+            local_ip,
+            Arc::new(UdpTransport::with_config(config)),
         );
     }
 
+    /// Create a new device directly from an IP, communicating through the specified transport.
+    pub fn from_ip_with_transport(
+        addr: Ipv4Addr,
+        local_ip: Option<Ipv4Addr>,
+        transport: Arc<dyn Transport>,
+    ) -> Result<Device, Error> {
+        // Discover the device, announcing the port that responses are received on
+        let (bytes, device_addr) =
+            transport.discover_one(&|port| create_discovery_packet(local_ip, port), addr)?;
+
+        return create_device_from_packet(device_addr, &bytes, transport);
+    }
+
     /// List all devices in the current network. Optionally specify the local IP if on different subnets.
-    pub fn list(ip: Option<Ipv4Addr>) -> Result<Vec<Device>, String> {
-        // Grab the first non-loopback address
-        let selected_ip = local_ip_or(ip)?;
-
-        // Construct the discovery message
-        let port = 42424;
-        let discover = DiscoveryMessage::new(selected_ip, port, None)?;
-        let msg = discover
-            .pack()
-            .map_err(|e| format!("Could not pack DiscoveryMessage! {}", e))?;
-
-        let results = send_and_receive_many(
-            &msg,
-            Ipv4Addr::BROADCAST,
-            Some(port),
-            |bytes_received, bytes, addr| {
-                return Ok(create_device_from_packet(addr, bytes_received, &bytes)
-                    .map_err(|e| format!("Could not create device from packet! {}", e))?);
-            },
-        )
-        .map_err(|e| format!("Could not send discovery message! {}", e))?;
+    ///
+    /// Devices are de-duplicated by their MAC address. Devices which answer but cannot be used
+    /// (e.g. unknown models) are returned separately instead of aborting the scan.
+    pub fn list(ip: Option<Ipv4Addr>) -> Result<DiscoveryResult, Error> {
+        return Device::list_with_transport(ip, Arc::new(UdpTransport::default()));
+    }
 
-        // Remove duplicates
-        // TODO
+    /// List all devices in the current network, using the specified network settings.
+    pub fn list_with_config(
+        ip: Option<Ipv4Addr>,
+        config: NetworkConfig,
+    ) -> Result<DiscoveryResult, Error> {
+        return Device::list_with_transport(ip, Arc::new(UdpTransport::with_config(config)));
+    }
 
-        return Ok(results);
+    /// List all devices in the current network, communicating through the specified transport.
+    pub fn list_with_transport(
+        ip: Option<Ipv4Addr>,
+        transport: Arc<dyn Transport>,
+    ) -> Result<DiscoveryResult, Error> {
+        // Discover the devices, announcing the port that responses are received on
+        let packets = transport
+            .discover_many(
+                &|port| create_discovery_packet(ip, port),
+                Ipv4Addr::BROADCAST,
+            )?
+            .into_iter()
+            .map(|(bytes, addr)| (bytes, addr, None))
+            .collect::<Vec<DiscoveryPacket>>();
+
+        let mut result = parse_devices_from_packets(&packets, transport);
+        authenticate_all(&mut result);
+
+        return Ok(result);
     }
 
-    /// Authenticate a device. This is needed before any commands can be sent.
+    /// Discover devices on all of the specified targets at once.
     ///
-    /// Note: This is automatically called when constructing a device.
-    pub fn authenticate(&mut self) -> Result<(), String> {
-        let info = self.get_info();
+    /// Interfaces are scanned using broadcasts directed at their subnet, while addresses and
+    /// ranges are sent to directly from the interface whose subnet contains them. Every device
+    /// is tagged with the interface it was found on, if any.
+    pub fn discover(
+        targets: &[DiscoveryTarget],
+        config: NetworkConfig,
+    ) -> Result<DiscoveryResult, Error> {
+        let mut result = DiscoveryResult::default();
+        for item in Device::discover_iter(targets, config)? {
+            match item {
+                Ok(device) => result.devices.push(device),
+                Err(failed) => result.failed.push(failed),
+            };
+        }
 
-        // Create the actual auth message
-        let msg = AuthenticationMessage::new(&info.name);
-        let packed = msg
-            .pack()
-            .map_err(|e| format!("Could not pack Authentication message! {}", e))?;
+        return Ok(result);
+    }
 
-        // Send the auth message command to the device
-        let response = self
-            .send_command::<AuthenticationMessage>(&packed)
-            .map_err(|e| format!("Could not send authentication command! {}", e))?;
+    /// Discover devices on all of the specified targets at once, yielding each device as soon
+    /// as it answers. Refer to [Device::discover] for how targets are scanned.
+    pub fn discover_iter(
+        targets: &[DiscoveryTarget],
+        config: NetworkConfig,
+    ) -> Result<DiscoveryIter, Error> {
+        return DiscoveryIter::start(targets, config);
+    }
 
-        // Unpack the response
-        let auth = AuthenticationResponse::unpack_from_slice(&response)
-            .map_err(|e| format!("Could not unpack auth response! {}", e))?;
+    /// Discover devices on all of the specified targets, communicating through the specified transport.
+    ///
+    /// Unlike [Device::discover_iter], the responses of each target are gathered by the transport
+    /// before any device is yielded.
+    pub fn discover_iter_with_transport(
+        targets: &[DiscoveryTarget],
+        transport: Arc<dyn Transport>,
+    ) -> Result<DiscoveryIter, Error> {
+        return DiscoveryIter::start_with_transport(targets, transport);
+    }
 
-        // Save the returned key and ID
-        self.save_auth_pair(auth.id, auth.key);
+    /// Authenticate a device. This is needed before any commands can be sent.
+    ///
+    /// Note: This is automatically called when constructing a device.
+    pub fn authenticate(&mut self) -> Result<(), Error> {
+        let packed = self.create_auth_packet()?;
 
-        return Ok(());
+        // Send the auth message command to the device. The current session is kept until the
+        // device answers, in case authenticating fails.
+        let response =
+            self.send_command_with_retries::<AuthenticationMessage>(&self.initial_info(), &packed);
+
+        return self.save_auth_response(response);
     }
 
     /// Connects any found device to a specified network. Requires the host machine
     /// to connect to the device directly. Refer to -> <https://github.com/mjg59/python-broadlink#setup>
     pub fn connect_to_network(
         network: &WirelessConnection,
-    ) -> Result<WirelessConnectionMessage, String> {
-        let msg = network
-            .to_message()
-            .map_err(|e| format!("Could not create wireless connection message! {}", e))?;
-        let packed = msg
-            .pack()
-            .map_err(|e| format!("Could not pack wireless connection message! {}", e))?;
+    ) -> Result<WirelessConnectionMessage, Error> {
+        let msg = network.to_message()?;
+        let packed = msg.pack()?;
 
         // We don't know the format of the response, so we just pass here.
-        send_and_receive_one(&packed, Ipv4Addr::BROADCAST, None, |_, _, _| {
-            return Ok(());
-        })
-        .map_err(|e| format!("Could not send connection message! {}", e))?;
+        let config = NetworkConfig::default();
+        let destination = SocketAddr::from((Ipv4Addr::BROADCAST, config.device_port));
+        let bind = SocketAddr::from((config.bind_addr, 0));
+        send_and_receive_one(
+            &packed,
+            destination,
+            bind,
+            config.command_timeout,
+            |_, _, _| {
+                return Ok(());
+            },
+        )?;
 
         return Ok(msg);
     }
 
-    /// Sends a raw command to a broadlink device.
+    /// Rebuild a previously authenticated device from its saved session, without any network traffic.
+    ///
+    /// If the device no longer accepts the saved key (e.g. after a reboot), it is transparently
+    /// re-authenticated when sending the next command.
+    pub fn from_session(session: &DeviceSession) -> Result<Device, Error> {
+        return Device::from_session_with_transport(session, Arc::new(UdpTransport::default()));
+    }
+
+    /// Rebuild a previously authenticated device from its saved session, communicating through
+    /// the specified transport.
+    pub fn from_session_with_transport(
+        session: &DeviceSession,
+        transport: Arc<dyn Transport>,
+    ) -> Result<Device, Error> {
+        // Reconstruct the response that the device would have sent during discovery
+        let mut name = [0u8; 62];
+        let name_len = session.name.len().min(name.len());
+        name[..name_len].copy_from_slice(&session.name.as_bytes()[..name_len]);
+
+        let response = DiscoveryResponse {
+            model_code: session.model_code,
+            mac: reverse_mac(session.mac),
+            name,
+            is_locked: false,
+        };
+
+        let mut device = create_device_from_response(session.address, response)?;
+        device.set_transport(transport);
+        device.save_auth_pair(session.auth_id, session.key);
+
+        return Ok(device);
+    }
+
+    /// Get the session of this device, which can be saved and later passed to [Device::from_session].
+    pub fn get_session(&self) -> DeviceSession {
+        let info = self.get_info();
+
+        return DeviceSession {
+            address: info.address,
+            mac: info.mac,
+            model_code: info.model_code,
+            name: info.name.trim_end_matches('\0').to_string(),
+            auth_id: info.auth_id,
+            key: info.key,
+        };
+    }
+
+    /// Sends a raw command to a broadlink device, according to its [RetryPolicy].
+    ///
+    /// If the device rejects the current session, it is re-authenticated and the command is sent again.
     /// Note: Try to avoid using this method in favor of more specific methods (e.g. [Device::authenticate], etc.)
-    pub fn send_command<T>(&self, payload: &[u8]) -> Result<Vec<u8>, String>
+    pub fn send_command<T>(&mut self, payload: &[u8]) -> Result<Vec<u8>, Error>
     where
         T: CommandTrait,
     {
-        let info = self.get_info();
+        let policy = self.get_retry_policy();
 
-        // Construct the command.
-        let cmd = CommandMessage::new::<T>(info.model_code, info.mac, info.auth_id);
+        return match self.send_command_with_retries::<T>(&self.get_info(), payload) {
+            Err(e) if policy.reauthenticate && e.is_auth_error() && !is_auth_command::<T>() => {
+                self.authenticate()?;
+                self.send_command_with_retries::<T>(&self.get_info(), payload)
+            }
+            result => result,
+        };
+    }
+
+    /// Sends a raw command on behalf of a specific device, which this device wraps, in order
+    /// to make use of the shared helper utilities.
+    ///
+    /// The session of the specific device is updated afterwards, in case it was re-authenticated.
+    pub(crate) fn send_command_for<T>(
+        mut self,
+        info: &mut DeviceInfo,
+        payload: &[u8],
+    ) -> Result<Vec<u8>, Error>
+    where
+        T: CommandTrait,
+    {
+        let response = self.send_command::<T>(payload);
+        *info = self.get_info();
+
+        return response;
+    }
+
+    /// Sends a raw command to a broadlink device using the session of the specified info,
+    /// retrying transient failures.
+    fn send_command_with_retries<T>(
+        &self,
+        info: &DeviceInfo,
+        payload: &[u8],
+    ) -> Result<Vec<u8>, Error>
+    where
+        T: CommandTrait,
+    {
+        let policy = self.get_retry_policy();
+
+        let mut attempt = 1;
+        loop {
+            match self.send_command_once::<T>(info, payload) {
+                Err(e) if attempt < policy.attempts && policy.should_retry(&e) => {
+                    std::thread::sleep(policy.backoff_for(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            };
+        }
+    }
 
-        // Pack the message with the payload
-        let packed = cmd
-            .pack_with_payload(&payload, &info.key)
-            .map_err(|e| format!("Could not pack command with payload! {}", e))?;
+    /// Sends a raw command to a broadlink device once, without handling expired sessions.
+    fn send_command_once<T>(&self, info: &DeviceInfo, payload: &[u8]) -> Result<Vec<u8>, Error>
+    where
+        T: CommandTrait,
+    {
+        let packed = create_command_packet::<T>(info, payload)?;
 
         // Send the message to the device
-        return send_and_receive_one(&packed, info.address, None, |_, bytes, _| {
-            return CommandMessage::unpack_with_payload(bytes.to_vec(), &info.key);
-        });
+        let (bytes, _) = self
+            .get_transport()
+            .send_and_receive_one(&packed, info.address, None)?;
+
+        return CommandMessage::unpack_with_payload(bytes, &info.key);
+    }
+
+    /// Get the transport used to communicate with this device.
+    pub fn get_transport(&self) -> Arc<dyn Transport> {
+        return match self {
+            Device::Remote { remote } => remote.transport.clone(),
+            Device::Hvac { hvac } => hvac.transport.clone(),
+            Device::Plug { plug } => plug.transport.clone(),
+            Device::PowerStrip { strip } => strip.transport.clone(),
+            Device::Sensor { sensor } => sensor.transport.clone(),
+            Device::Bulb { bulb } => bulb.transport.clone(),
+            Device::Thermostat { thermostat } => thermostat.transport.clone(),
+            Device::Curtain { curtain } => curtain.transport.clone(),
+            Device::Alarm { alarm } => alarm.transport.clone(),
+            Device::Generic { generic } => generic.transport.clone(),
+        };
+    }
+
+    /// Set the transport used to communicate with this device.
+    pub fn set_transport(&mut self, transport: Arc<dyn Transport>) {
+        match self {
+            Device::Remote { remote } => remote.transport = transport,
+            Device::Hvac { hvac } => hvac.transport = transport,
+            Device::Plug { plug } => plug.transport = transport,
+            Device::PowerStrip { strip } => strip.transport = transport,
+            Device::Sensor { sensor } => sensor.transport = transport,
+            Device::Bulb { bulb } => bulb.transport = transport,
+            Device::Thermostat { thermostat } => thermostat.transport = transport,
+            Device::Curtain { curtain } => curtain.transport = transport,
+            Device::Alarm { alarm } => alarm.transport = transport,
+            Device::Generic { generic } => generic.transport = transport,
+        };
+    }
+
+    /// Get the policy used to retry failed commands sent to this device.
+    pub fn get_retry_policy(&self) -> RetryPolicy {
+        return match self {
+            Device::Remote { remote } => remote.retry_policy,
+            Device::Hvac { hvac } => hvac.retry_policy,
+            Device::Plug { plug } => plug.retry_policy,
+            Device::PowerStrip { strip } => strip.retry_policy,
+            Device::Sensor { sensor } => sensor.retry_policy,
+            Device::Bulb { bulb } => bulb.retry_policy,
+            Device::Thermostat { thermostat } => thermostat.retry_policy,
+            Device::Curtain { curtain } => curtain.retry_policy,
+            Device::Alarm { alarm } => alarm.retry_policy,
+            Device::Generic { generic } => generic.retry_policy,
+        };
+    }
+
+    /// Set the policy used to retry failed commands sent to this device.
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        match self {
+            Device::Remote { remote } => remote.retry_policy = retry_policy,
+            Device::Hvac { hvac } => hvac.retry_policy = retry_policy,
+            Device::Plug { plug } => plug.retry_policy = retry_policy,
+            Device::PowerStrip { strip } => strip.retry_policy = retry_policy,
+            Device::Sensor { sensor } => sensor.retry_policy = retry_policy,
+            Device::Bulb { bulb } => bulb.retry_policy = retry_policy,
+            Device::Thermostat { thermostat } => thermostat.retry_policy = retry_policy,
+            Device::Curtain { curtain } => curtain.retry_policy = retry_policy,
+            Device::Alarm { alarm } => alarm.retry_policy = retry_policy,
+            Device::Generic { generic } => generic.retry_policy = retry_policy,
+        };
+    }
+
+    /// Get the information of this device with the session used to authenticate.
+    ///
+    /// Authentication is always encrypted using the initial key, even when re-authenticating.
+    fn initial_info(&self) -> DeviceInfo {
+        let mut info = self.get_info();
+        info.auth_id = 0;
+        info.key = constants::INITIAL_KEY;
+
+        return info;
+    }
+
+    /// Get mutable access to the core information about this device.
+    fn info_mut(&mut self) -> &mut DeviceInfo {
+        return match self {
+            Device::Remote { remote } => &mut remote.info,
+            Device::Hvac { hvac } => &mut hvac.info,
+            Device::Plug { plug } => &mut plug.info,
+            Device::PowerStrip { strip } => &mut strip.info,
+            Device::Sensor { sensor } => &mut sensor.info,
+            Device::Bulb { bulb } => &mut bulb.info,
+            Device::Thermostat { thermostat } => &mut thermostat.info,
+            Device::Curtain { curtain } => &mut curtain.info,
+            Device::Alarm { alarm } => &mut alarm.info,
+            Device::Generic { generic } => &mut generic.info,
+        };
+    }
+
+    /// Creates the packed authentication message for this device.
+    fn create_auth_packet(&self) -> Result<Vec<u8>, Error> {
+        let msg = AuthenticationMessage::new(&self.get_info().name);
+
+        return Ok(msg.pack()?.to_vec());
+    }
+
+    /// Saves the key and ID returned by the device in response to an authentication message.
+    fn save_auth_response(&mut self, response: Result<Vec<u8>, Error>) -> Result<(), Error> {
+        let response = response.map_err(|e| match e {
+            Error::Device { .. } => Error::AuthRejected,
+            e => e,
+        })?;
+
+        // Unpack the response
+        let auth: AuthenticationResponse = unpack_zero_padded(&response)?;
+
+        // Save the returned key and ID
+        self.save_auth_pair(auth.id, auth.key);
+
+        return Ok(());
+    }
+}
+
+/// Async versions of the network operations, for use within a tokio runtime.
+#[cfg(feature = "tokio")]
+impl Device {
+    /// Async version of [Device::from_ip].
+    pub async fn from_ip_async(
+        addr: Ipv4Addr,
+        local_ip: Option<Ipv4Addr>,
+    ) -> Result<Device, Error> {
+        return Device::from_ip_with_config_async(addr, local_ip, NetworkConfig::default()).await;
+    }
+
+    /// Async version of [Device::from_ip_with_config].
+    pub async fn from_ip_with_config_async(
+        addr: Ipv4Addr,
+        local_ip: Option<Ipv4Addr>,
+        config: NetworkConfig,
+    ) -> Result<Device, Error> {
+        return Device::from_ip_with_transport_async(
+            addr,
+            local_ip,
+            Arc::new(UdpTransport::with_config(config)),
+        )
+        .await;
+    }
+
+    /// Async version of [Device::from_ip_with_transport].
+    pub async fn from_ip_with_transport_async(
+        addr: Ipv4Addr,
+        local_ip: Option<Ipv4Addr>,
+        transport: Arc<dyn Transport>,
+    ) -> Result<Device, Error> {
+        // Discover the device, announcing the port that responses are received on
+        let (bytes, device_addr) = transport
+            .discover_one_async(&|port| create_discovery_packet(local_ip, port), addr)
+            .await?;
+        let mut device = parse_device_from_packet(device_addr, &bytes)?;
+        device.set_transport(transport);
+
+        // Get the auth key for this device
+        device.authenticate_async().await?;
+
+        return Ok(device);
+    }
+
+    /// Async version of [Device::list].
+    pub async fn list_async(ip: Option<Ipv4Addr>) -> Result<DiscoveryResult, Error> {
+        return Device::list_with_transport_async(ip, Arc::new(UdpTransport::default())).await;
+    }
+
+    /// Async version of [Device::list_with_config].
+    pub async fn list_with_config_async(
+        ip: Option<Ipv4Addr>,
+        config: NetworkConfig,
+    ) -> Result<DiscoveryResult, Error> {
+        return Device::list_with_transport_async(ip, Arc::new(UdpTransport::with_config(config)))
+            .await;
+    }
+
+    /// Async version of [Device::list_with_transport].
+    pub async fn list_with_transport_async(
+        ip: Option<Ipv4Addr>,
+        transport: Arc<dyn Transport>,
+    ) -> Result<DiscoveryResult, Error> {
+        // Discover the devices, announcing the port that responses are received on
+        let packets = transport
+            .discover_many_async(
+                &|port| create_discovery_packet(ip, port),
+                Ipv4Addr::BROADCAST,
+            )
+            .await?
+            .into_iter()
+            .map(|(bytes, addr)| (bytes, addr, None))
+            .collect::<Vec<DiscoveryPacket>>();
+
+        let mut result = parse_devices_from_packets(&packets, transport);
+        authenticate_all_async(&mut result).await;
+
+        return Ok(result);
+    }
+
+    /// Async version of [Device::discover].
+    pub async fn discover_async(
+        targets: &[DiscoveryTarget],
+        config: NetworkConfig,
+    ) -> Result<DiscoveryResult, Error> {
+        let mut result = DiscoveryResult::default();
+        let mut stream = Device::discover_stream(targets, config).await?;
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(device) => result.devices.push(device),
+                Err(failed) => result.failed.push(failed),
+            };
+        }
+
+        return Ok(result);
+    }
+
+    /// Async version of [Device::discover_iter].
+    pub async fn discover_stream(
+        targets: &[DiscoveryTarget],
+        config: NetworkConfig,
+    ) -> Result<DiscoveryStream, Error> {
+        return DiscoveryStream::start(targets, config).await;
+    }
+
+    /// Async version of [Device::discover_iter_with_transport].
+    pub async fn discover_stream_with_transport(
+        targets: &[DiscoveryTarget],
+        transport: Arc<dyn Transport>,
+    ) -> Result<DiscoveryStream, Error> {
+        return DiscoveryStream::start_with_transport(targets, transport).await;
+    }
+
+    /// Async version of [Device::authenticate].
+    pub async fn authenticate_async(&mut self) -> Result<(), Error> {
+        let packed = self.create_auth_packet()?;
+
+        // Send the auth message command to the device. The current session is kept until the
+        // device answers, in case authenticating fails.
+        let response = self
+            .send_command_with_retries_async::<AuthenticationMessage>(&self.initial_info(), &packed)
+            .await;
+
+        return self.save_auth_response(response);
+    }
+
+    /// Async version of [Device::send_command].
+    pub async fn send_command_async<T>(&mut self, payload: &[u8]) -> Result<Vec<u8>, Error>
+    where
+        T: CommandTrait,
+    {
+        let policy = self.get_retry_policy();
+
+        return match self
+            .send_command_with_retries_async::<T>(&self.get_info(), payload)
+            .await
+        {
+            Err(e) if policy.reauthenticate && e.is_auth_error() && !is_auth_command::<T>() => {
+                self.authenticate_async().await?;
+                self.send_command_with_retries_async::<T>(&self.get_info(), payload)
+                    .await
+            }
+            result => result,
+        };
+    }
+
+    /// Async version of [Device::send_command_for].
+    pub(crate) async fn send_command_for_async<T>(
+        mut self,
+        info: &mut DeviceInfo,
+        payload: &[u8],
+    ) -> Result<Vec<u8>, Error>
+    where
+        T: CommandTrait,
+    {
+        let response = self.send_command_async::<T>(payload).await;
+        *info = self.get_info();
+
+        return response;
+    }
+
+    /// Async version of [Device::send_command_with_retries].
+    async fn send_command_with_retries_async<T>(
+        &self,
+        info: &DeviceInfo,
+        payload: &[u8],
+    ) -> Result<Vec<u8>, Error>
+    where
+        T: CommandTrait,
+    {
+        let policy = self.get_retry_policy();
+
+        let mut attempt = 1;
+        loop {
+            match self.send_command_once_async::<T>(info, payload).await {
+                Err(e) if attempt < policy.attempts && policy.should_retry(&e) => {
+                    tokio::time::sleep(policy.backoff_for(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            };
+        }
+    }
+
+    /// Async version of [Device::send_command_once].
+    async fn send_command_once_async<T>(
+        &self,
+        info: &DeviceInfo,
+        payload: &[u8],
+    ) -> Result<Vec<u8>, Error>
+    where
+        T: CommandTrait,
+    {
+        let packed = create_command_packet::<T>(info, payload)?;
+
+        // Send the message to the device
+        let transport = self.get_transport();
+        let (bytes, _) = transport
+            .send_and_receive_one_async(&packed, info.address, None)
+            .await?;
+
+        return CommandMessage::unpack_with_payload(bytes, &info.key);
     }
 }
 
@@ -153,6 +648,14 @@ impl DeviceTrait for Device {
         return match self {
             Device::Remote { remote } => remote.info.clone(),
             Device::Hvac { hvac } => hvac.info.clone(),
+            Device::Plug { plug } => plug.info.clone(),
+            Device::PowerStrip { strip } => strip.info.clone(),
+            Device::Sensor { sensor } => sensor.info.clone(),
+            Device::Bulb { bulb } => bulb.info.clone(),
+            Device::Thermostat { thermostat } => thermostat.info.clone(),
+            Device::Curtain { curtain } => curtain.info.clone(),
+            Device::Alarm { alarm } => alarm.info.clone(),
+            Device::Generic { generic } => generic.info.clone(),
         };
     }
 
@@ -167,6 +670,38 @@ impl DeviceTrait for Device {
                 hvac.info.auth_id = id;
                 hvac.info.key = key;
             }
+            Device::Plug { plug } => {
+                plug.info.auth_id = id;
+                plug.info.key = key;
+            }
+            Device::PowerStrip { strip } => {
+                strip.info.auth_id = id;
+                strip.info.key = key;
+            }
+            Device::Sensor { sensor } => {
+                sensor.info.auth_id = id;
+                sensor.info.key = key;
+            }
+            Device::Bulb { bulb } => {
+                bulb.info.auth_id = id;
+                bulb.info.key = key;
+            }
+            Device::Thermostat { thermostat } => {
+                thermostat.info.auth_id = id;
+                thermostat.info.key = key;
+            }
+            Device::Curtain { curtain } => {
+                curtain.info.auth_id = id;
+                curtain.info.key = key;
+            }
+            Device::Alarm { alarm } => {
+                alarm.info.auth_id = id;
+                alarm.info.key = key;
+            }
+            Device::Generic { generic } => {
+                generic.info.auth_id = id;
+                generic.info.key = key;
+            }
         };
     }
 }
@@ -192,50 +727,240 @@ impl fmt::Display for Device {
     }
 }
 
-/// Creates a device from a received network packet.
+/// Creates the packed discovery message, announcing the local IP and the given port.
+pub(crate) fn create_discovery_packet(
+    local_ip: Option<Ipv4Addr>,
+    port: u16,
+) -> Result<Vec<u8>, Error> {
+    // Grab the first non-loopback address
+    let selected_ip = local_ip_or(local_ip)?;
+
+    let discover = DiscoveryMessage::new(selected_ip, port, None)?;
+
+    return Ok(discover.pack()?.to_vec());
+}
+
+/// Creates a packed command, wrapping the payload, for the specified device.
+fn create_command_packet<T>(info: &DeviceInfo, payload: &[u8]) -> Result<Vec<u8>, Error>
+where
+    T: CommandTrait,
+{
+    // Construct the command.
+    let cmd = CommandMessage::new::<T>(info.model_code, info.mac, info.auth_id);
+
+    // Pack the message with the payload
+    return cmd.pack_with_payload(payload, &info.key);
+}
+
+/// Returns true if the command is the one used to authenticate with the device.
+fn is_auth_command<T>() -> bool
+where
+    T: CommandTrait,
+{
+    return T::packet_type() == AuthenticationMessage::packet_type();
+}
+
+/// Creates an authenticated device from a received network packet.
 fn create_device_from_packet(
     addr: SocketAddr,
-    bytes_received: usize,
     bytes: &[u8],
-) -> Result<Device, String> {
+    transport: Arc<dyn Transport>,
+) -> Result<Device, Error> {
+    let mut device = parse_device_from_packet(addr, bytes)?;
+    device.set_transport(transport);
+
+    // Get the auth key for this device
+    device.authenticate()?;
+
+    return Ok(device);
+}
+
+/// A received discovery packet, along with its sender and the local interface it was received on.
+pub(crate) type DiscoveryPacket = (Vec<u8>, SocketAddr, Option<NetworkInterface>);
+
+/// Authenticates every discovered device, moving the ones which fail to the failed list.
+fn authenticate_all(result: &mut DiscoveryResult) {
+    let mut devices = vec![];
+    for mut device in result.devices.drain(..) {
+        match device.authenticate() {
+            Ok(()) => devices.push(device),
+            Err(e) => result.failed.push(failed_device(&device, e)),
+        };
+    }
+
+    result.devices = devices;
+}
+
+/// Async version of [authenticate_all].
+#[cfg(feature = "tokio")]
+async fn authenticate_all_async(result: &mut DiscoveryResult) {
+    let mut devices = vec![];
+    for mut device in result.devices.drain(..) {
+        match device.authenticate_async().await {
+            Ok(()) => devices.push(device),
+            Err(e) => result.failed.push(failed_device(&device, e)),
+        };
+    }
+
+    result.devices = devices;
+}
+
+/// Creates unauthenticated devices from the received discovery packets, skipping any
+/// duplicate MAC addresses.
+fn parse_devices_from_packets(
+    packets: &[DiscoveryPacket],
+    transport: Arc<dyn Transport>,
+) -> DiscoveryResult {
+    let mut result = DiscoveryResult::default();
+    let mut seen_macs = HashSet::new();
+
+    for packet in packets {
+        match parse_discovery_packet(packet, &transport, &mut seen_macs) {
+            Some(Ok(device)) => result.devices.push(device),
+            Some(Err(failed)) => result.failed.push(failed),
+            None => {}
+        };
+    }
+
+    return result;
+}
+
+/// Creates an unauthenticated device from a received discovery packet, or None if a device
+/// with the same MAC address was already seen.
+pub(crate) fn parse_discovery_packet(
+    packet: &DiscoveryPacket,
+    transport: &Arc<dyn Transport>,
+    seen_macs: &mut HashSet<[u8; 6]>,
+) -> Option<Result<Device, FailedDevice>> {
+    let (bytes, addr, interface) = packet;
+    let (addr_ip, response) = match unpack_discovery_packet(*addr, bytes) {
+        Ok(r) => r,
+        Err(error) => {
+            return Some(Err(FailedDevice {
+                address: *addr,
+                mac: None,
+                model_code: None,
+                interface: interface.clone(),
+                error,
+            }))
+        }
+    };
+
+    // Devices may answer more than once, so only keep the first response
+    let mac = reverse_mac(response.mac);
+    if !seen_macs.insert(mac) {
+        return None;
+    }
+
+    let model_code = response.model_code;
+    return Some(match create_device_from_response(addr_ip, response) {
+        Ok(mut device) => {
+            device.set_transport(transport.clone());
+            device.info_mut().interface = interface.clone();
+            Ok(device)
+        }
+        Err(error) => Err(FailedDevice {
+            address: *addr,
+            mac: Some(mac),
+            model_code: Some(model_code),
+            interface: interface.clone(),
+            error,
+        }),
+    });
+}
+
+/// Describes a discovered device which could not be used.
+pub(crate) fn failed_device(device: &Device, error: Error) -> FailedDevice {
+    let info = device.get_info();
+
+    return FailedDevice {
+        address: SocketAddr::from((info.address, device.get_transport().config().device_port)),
+        mac: Some(info.mac),
+        model_code: Some(info.model_code),
+        interface: info.interface,
+        error,
+    };
+}
+
+/// Creates an unauthenticated device from a received network packet.
+fn parse_device_from_packet(addr: SocketAddr, bytes: &[u8]) -> Result<Device, Error> {
+    let (addr_ip, response) = unpack_discovery_packet(addr, bytes)?;
+
+    return create_device_from_response(addr_ip, response);
+}
+
+/// Unpacks a received discovery packet, along with the IPv4 address of its sender.
+fn unpack_discovery_packet(
+    addr: SocketAddr,
+    bytes: &[u8],
+) -> Result<(Ipv4Addr, DiscoveryResponse), Error> {
     // Make sure that we have the required amount of bytes
-    if bytes_received != 128 {
-        return Err("Received invalid response! Not enough data.".into());
+    if bytes.len() != 128 {
+        return Err(Error::InvalidResponse(
+            "Received invalid response! Not enough data.".into(),
+        ));
     }
 
     // Short-circuit if the device is using an IPv6 address (should be impossible)
     let addr_ip = match addr.ip() {
         IpAddr::V4(a) => a,
-        _ => return Err("Device has an IPv6 Address! This should be impossible...".into()),
+        _ => {
+            return Err(Error::InvalidResponse(
+                "Device has an IPv6 Address! This should be impossible...".into(),
+            ))
+        }
     };
 
-    let response = DiscoveryResponse::unpack_from_slice(&bytes)
-        .map_err(|e| format!("Could not unpack response from device! {}", e))?;
+    let response = DiscoveryResponse::unpack_from_slice(bytes)?;
 
-    // Decode the name
-    let raw_name = response.name.clone();
-    let name = from_utf8(&raw_name).map_err(|e| format!("Could not decode device name! {}", e))?;
+    return Ok((addr_ip, response));
+}
 
-    // Create the device conditionally based on the model code.
-    let mut device = match &response.model_code {
-        _ if REMOTE_CODES.contains_key(&response.model_code) => Device::Remote {
+/// Creates an unauthenticated device from its discovery response.
+fn create_device_from_response(
+    addr_ip: Ipv4Addr,
+    response: DiscoveryResponse,
+) -> Result<Device, Error> {
+    // Decode the name
+    let raw_name = response.name;
+    let name = from_utf8(&raw_name)
+        .map_err(|e| Error::InvalidResponse(format!("Could not decode device name! {}", e)))?;
+
+    // Create the device conditionally based on the family of the model.
+    // Unknown models still get a generic device, so that raw commands can be sent to them.
+    let family = lookup_model(response.model_code).map(|model| model.family);
+    let device = match family {
+        Some(DeviceFamily::Remote { .. }) => Device::Remote {
             remote: RemoteDevice::new(name, addr_ip, response),
         },
-        _ if HVAC_CODES.contains_key(&response.model_code) => Device::Hvac {
+        Some(DeviceFamily::Hvac) => Device::Hvac {
             hvac: HvacDevice::new(name, addr_ip, response),
         },
-        _ => {
-            return Err(format!(
-                "Unknown device: {} ({:#06X})",
-                response.model_code, response.model_code
-            ))
-        }
+        Some(DeviceFamily::Plug(_)) => Device::Plug {
+            plug: PlugDevice::new(name, addr_ip, response),
+        },
+        Some(DeviceFamily::PowerStrip) => Device::PowerStrip {
+            strip: PowerStripDevice::new(name, addr_ip, response),
+        },
+        Some(DeviceFamily::Sensor) => Device::Sensor {
+            sensor: SensorDevice::new(name, addr_ip, response),
+        },
+        Some(DeviceFamily::Bulb(_)) => Device::Bulb {
+            bulb: BulbDevice::new(name, addr_ip, response),
+        },
+        Some(DeviceFamily::Thermostat) => Device::Thermostat {
+            thermostat: ThermostatDevice::new(name, addr_ip, response),
+        },
+        Some(DeviceFamily::Curtain) => Device::Curtain {
+            curtain: CurtainDevice::new(name, addr_ip, response),
+        },
+        Some(DeviceFamily::Alarm) => Device::Alarm {
+            alarm: AlarmDevice::new(name, addr_ip, response),
+        },
+        Some(DeviceFamily::Generic) | None => Device::Generic {
+            generic: GenericDevice::new(name, addr_ip, response),
+        },
     };
 
-    // Get the auth key for this device
-    device
-        .authenticate()
-        .map_err(|e| format!("Could not authenticate device! {}", e))?;
-
     return Ok(device);
 }
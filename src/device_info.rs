@@ -1,5 +1,7 @@
 use std::net::Ipv4Addr;
 
+use crate::network::NetworkInterface;
+
 /// Represents a broadlink device core information.
 #[derive(Debug, Clone)]
 pub struct DeviceInfo {
@@ -29,4 +31,7 @@ pub struct DeviceInfo {
 
     /// The key used for encrypted communication
     pub key: [u8; 16],
+
+    /// The local interface that this device was discovered on, if known.
+    pub interface: Option<NetworkInterface>,
 }
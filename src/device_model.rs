@@ -0,0 +1,120 @@
+use std::{borrow::Cow, collections::BTreeMap, sync::RwLock};
+
+use crate::{
+    BulbType, PlugType, RemoteGeneration, ALARM_CODES, BULB_CODES, CURTAIN_CODES, HVAC_CODES,
+    PLUG_CODES, POWER_STRIP_CODES, REMOTE_CODES, SENSOR_CODES, THERMOSTAT_CODES,
+};
+
+/// Models registered at runtime, which take precedence over the built-in tables.
+static REGISTERED_MODELS: RwLock<BTreeMap<u16, DeviceModel>> = RwLock::new(BTreeMap::new());
+
+/// The family of a device, which decides the type of [crate::Device] created for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceFamily {
+    /// A remote, see [crate::RemoteDevice].
+    Remote {
+        /// The generation of the remote.
+        generation: RemoteGeneration,
+
+        /// Whether the remote can report temperature and humidity.
+        has_sensors: bool,
+    },
+
+    /// An air conditioner, see [crate::HvacDevice].
+    Hvac,
+
+    /// A smart plug of the given type, see [crate::PlugDevice].
+    Plug(PlugType),
+
+    /// A power strip, see [crate::PowerStripDevice].
+    PowerStrip,
+
+    /// An environmental sensor, see [crate::SensorDevice].
+    Sensor,
+
+    /// A smart bulb of the given type, see [crate::BulbDevice].
+    Bulb(BulbType),
+
+    /// A thermostat, see [crate::ThermostatDevice].
+    Thermostat,
+
+    /// A curtain motor, see [crate::CurtainDevice].
+    Curtain,
+
+    /// An alarm hub, see [crate::AlarmDevice].
+    Alarm,
+
+    /// Any other device, which only supports raw commands. See [crate::GenericDevice].
+    Generic,
+}
+
+/// Describes a model of device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceModel {
+    /// The family of the model.
+    pub family: DeviceFamily,
+
+    /// The friendly name of the model.
+    pub name: Cow<'static, str>,
+}
+
+/// Register a model code as belonging to a device family, e.g. for a new hardware revision.
+///
+/// Registered models take precedence over the built-in ones, and apply to every device
+/// created afterwards. The name can be borrowed or owned, e.g. when read from a config file.
+pub fn register_model(model_code: u16, name: impl Into<Cow<'static, str>>, family: DeviceFamily) {
+    REGISTERED_MODELS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(
+            model_code,
+            DeviceModel {
+                family,
+                name: name.into(),
+            },
+        );
+}
+
+/// Look up the model of a device from its model code, if known.
+pub fn lookup_model(model_code: u16) -> Option<DeviceModel> {
+    let registered = REGISTERED_MODELS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&model_code)
+        .cloned();
+    if registered.is_some() {
+        return registered;
+    }
+
+    let model = |family, name: &'static str| {
+        return Some(DeviceModel {
+            family,
+            name: name.into(),
+        });
+    };
+    return if let Some(remote) = REMOTE_CODES.get(&model_code) {
+        let family = DeviceFamily::Remote {
+            generation: remote.generation,
+            has_sensors: remote.has_sensors,
+        };
+        model(family, remote.name)
+    } else if let Some(name) = HVAC_CODES.get(&model_code) {
+        model(DeviceFamily::Hvac, name)
+    } else if let Some((plug_type, name)) = PLUG_CODES.get(&model_code) {
+        model(DeviceFamily::Plug(*plug_type), name)
+    } else if let Some(name) = POWER_STRIP_CODES.get(&model_code) {
+        model(DeviceFamily::PowerStrip, name)
+    } else if let Some(name) = SENSOR_CODES.get(&model_code) {
+        model(DeviceFamily::Sensor, name)
+    } else if let Some((bulb_type, name)) = BULB_CODES.get(&model_code) {
+        model(DeviceFamily::Bulb(*bulb_type), name)
+    } else if let Some(name) = THERMOSTAT_CODES.get(&model_code) {
+        model(DeviceFamily::Thermostat, name)
+    } else if let Some(name) = CURTAIN_CODES.get(&model_code) {
+        model(DeviceFamily::Curtain, name)
+    } else if let Some(name) = ALARM_CODES.get(&model_code) {
+        model(DeviceFamily::Alarm, name)
+    } else {
+        None
+    };
+}
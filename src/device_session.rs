@@ -0,0 +1,110 @@
+use std::{fmt, net::Ipv4Addr, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// The identity and session of an authenticated device.
+///
+/// A session can be saved (using serde, in any format) and later used to rebuild the device
+/// without any network traffic. Refer to [crate::Device::from_session].
+///
+/// For convenience, a session can also be converted to and parsed from a single line of text.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeviceSession {
+    /// The IP address of the device.
+    pub address: Ipv4Addr,
+
+    /// The MAC address of the device.
+    pub mac: [u8; 6],
+
+    /// The model code of the device.
+    pub model_code: u16,
+
+    /// The name of the device.
+    pub name: String,
+
+    /// The authentication ID used for encrypted communication.
+    pub auth_id: u32,
+
+    /// The key used for encrypted communication.
+    pub key: [u8; 16],
+}
+
+/// Formats the session as a single line, e.g.
+/// `10.0.0.2 AA:BB:CC:DD:EE:FF 0x649B 00000001 00112233445566778899AABBCCDDEEFF Living Room`
+impl fmt::Display for DeviceSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {:#06X} {:08X} {} {}",
+            self.address,
+            self.mac
+                .iter()
+                .map(|x| format!("{:02X}", x))
+                .collect::<Vec<String>>()
+                .join(":"),
+            self.model_code,
+            self.auth_id,
+            self.key
+                .iter()
+                .map(|x| format!("{:02X}", x))
+                .collect::<String>(),
+            self.name,
+        )
+    }
+}
+
+impl FromStr for DeviceSession {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |field: &str| {
+            return Error::InvalidArgument(format!("Could not parse session! Invalid {}", field));
+        };
+
+        let mut parts = s.trim().splitn(6, ' ');
+        let mut next = |field: &str| parts.next().ok_or_else(|| invalid(field));
+
+        let address = next("address")?
+            .parse::<Ipv4Addr>()
+            .map_err(|_| invalid("address"))?;
+
+        let mut mac = [0u8; 6];
+        let mac_parts = next("MAC address")?
+            .split(':')
+            .map(|x| u8::from_str_radix(x, 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| invalid("MAC address"))?;
+        if mac_parts.len() != mac.len() {
+            return Err(invalid("MAC address"));
+        }
+        mac.copy_from_slice(&mac_parts);
+
+        let model_code = u16::from_str_radix(next("model code")?.trim_start_matches("0x"), 16)
+            .map_err(|_| invalid("model code"))?;
+        let auth_id = u32::from_str_radix(next("authentication ID")?, 16)
+            .map_err(|_| invalid("authentication ID"))?;
+
+        let mut key = [0u8; 16];
+        let key_str = next("key")?;
+        if !key_str.is_ascii() || key_str.len() != key.len() * 2 {
+            return Err(invalid("key"));
+        }
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte =
+                u8::from_str_radix(&key_str[i * 2..i * 2 + 2], 16).map_err(|_| invalid("key"))?;
+        }
+
+        let name = parts.next().unwrap_or("").to_string();
+
+        return Ok(DeviceSession {
+            address,
+            mac,
+            model_code,
+            name,
+            auth_id,
+            key,
+        });
+    }
+}
@@ -0,0 +1,459 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    device::{create_discovery_packet, failed_device, parse_discovery_packet, DiscoveryPacket},
+    network::{
+        util::bind_broadcast_socket, DiscoveryTarget, NetworkConfig, NetworkInterface, UdpTransport,
+    },
+    traits::Transport,
+    Device, Error, FailedDevice,
+};
+
+#[cfg(feature = "tokio")]
+use crate::network::util::bind_broadcast_socket_async;
+
+/// How long to wait between checking the sockets for new responses.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Yields discovered devices as soon as they answer. Refer to [Device::discover_iter].
+///
+/// Devices are authenticated before being yielded, while devices which answer but cannot be
+/// used are yielded as errors. Iteration ends once the discovery timeout expires, the device
+/// limit is reached or the discovery is cancelled. Dropping the iterator stops the discovery.
+pub struct DiscoveryIter {
+    /// The sockets that discovery messages were sent from, along with their interface.
+    sockets: Vec<(UdpSocket, Option<NetworkInterface>)>,
+
+    /// The responses already gathered by a transport, handled before the sockets.
+    pending: VecDeque<DiscoveryPacket>,
+
+    /// When to stop waiting for responses.
+    deadline: Instant,
+
+    /// The transport given to every device found.
+    transport: Arc<dyn Transport>,
+
+    /// The MAC addresses of the devices seen so far.
+    seen_macs: HashSet<[u8; 6]>,
+
+    /// How many more devices to yield, if limited.
+    remaining: Option<usize>,
+
+    /// Set to true to stop the discovery.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl DiscoveryIter {
+    /// Sends the discovery messages to every target.
+    pub(crate) fn start(
+        targets: &[DiscoveryTarget],
+        config: NetworkConfig,
+    ) -> Result<DiscoveryIter, Error> {
+        let mut sockets = vec![];
+        for scan in plan_discovery(targets)? {
+            let (bind, local_ip) = scan_bind_addr(&scan, &config);
+            let socket = bind_broadcast_socket(bind)?;
+
+            // Announce the port that was actually bound
+            let port = socket.local_addr().map_err(Error::Io)?.port();
+            let msg = create_discovery_packet(local_ip, port)?;
+            for destination in &scan.destinations {
+                socket
+                    .send_to(&msg, (*destination, config.device_port))
+                    .map_err(Error::Io)?;
+            }
+
+            socket.set_nonblocking(true).map_err(Error::Io)?;
+            sockets.push((socket, scan.interface));
+        }
+
+        return Ok(DiscoveryIter {
+            sockets,
+            pending: VecDeque::new(),
+            deadline: Instant::now() + config.discovery_timeout,
+            transport: Arc::new(UdpTransport::with_config(config)),
+            seen_macs: HashSet::new(),
+            remaining: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        });
+    }
+
+    /// Sends the discovery messages to every target through the specified transport, gathering
+    /// every response before the first device is yielded.
+    pub(crate) fn start_with_transport(
+        targets: &[DiscoveryTarget],
+        transport: Arc<dyn Transport>,
+    ) -> Result<DiscoveryIter, Error> {
+        let mut pending = VecDeque::new();
+        for scan in plan_discovery(targets)? {
+            let local_ip = scan.interface.as_ref().map(|x| x.address);
+            let build = |port| create_discovery_packet(local_ip, port);
+            for destination in &scan.destinations {
+                for (bytes, addr) in transport.discover_many(&build, *destination)? {
+                    pending.push_back((bytes, addr, scan.interface.clone()));
+                }
+            }
+        }
+
+        return Ok(DiscoveryIter {
+            sockets: vec![],
+            pending,
+            deadline: Instant::now(),
+            transport,
+            seen_macs: HashSet::new(),
+            remaining: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        });
+    }
+
+    /// Stop the discovery once the specified amount of devices have been found.
+    pub fn stop_after(mut self, devices: usize) -> DiscoveryIter {
+        self.remaining = Some(devices);
+
+        return self;
+    }
+
+    /// Get a flag which stops the discovery when set, e.g. from another thread.
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        return self.cancelled.clone();
+    }
+
+    /// Handles a received packet, returning the item to yield, if any.
+    fn accept(&mut self, packet: &DiscoveryPacket) -> Option<Result<Device, FailedDevice>> {
+        let item = match parse_discovery_packet(packet, &self.transport, &mut self.seen_macs)? {
+            Ok(mut device) => match device.authenticate() {
+                Ok(()) => Ok(device),
+                Err(e) => Err(failed_device(&device, e)),
+            },
+            Err(failed) => Err(failed),
+        };
+
+        if let (Ok(_), Some(remaining)) = (&item, self.remaining.as_mut()) {
+            *remaining -= 1;
+        }
+
+        return Some(item);
+    }
+}
+
+impl Iterator for DiscoveryIter {
+    type Item = Result<Device, FailedDevice>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut recv_buffer = [0u8; 8092];
+        loop {
+            if self.remaining == Some(0) || self.cancelled.load(Ordering::SeqCst) {
+                return None;
+            }
+
+            // Handle the responses gathered by the transport first
+            if let Some(packet) = self.pending.pop_front() {
+                if let Some(item) = self.accept(&packet) {
+                    return Some(item);
+                }
+
+                continue;
+            }
+
+            if self.sockets.is_empty() {
+                return None;
+            }
+
+            // Check every socket for a response
+            let mut received = false;
+            for i in 0..self.sockets.len() {
+                let (socket, interface) = &self.sockets[i];
+                let (bytes_received, addr) = match socket.recv_from(&mut recv_buffer) {
+                    Ok(r) => r,
+                    Err(_) => continue,
+                };
+
+                received = true;
+                let packet = (
+                    recv_buffer[0..bytes_received].to_vec(),
+                    addr,
+                    interface.clone(),
+                );
+                if let Some(item) = self.accept(&packet) {
+                    return Some(item);
+                }
+            }
+
+            // Only give up once every response received in time has been handled
+            if !received {
+                if Instant::now() >= self.deadline {
+                    return None;
+                }
+
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Async version of [DiscoveryIter]. Refer to [Device::discover_stream].
+///
+/// Dropping the stream or setting its cancel flag stops the discovery.
+#[cfg(feature = "tokio")]
+pub struct DiscoveryStream {
+    /// The sockets that discovery messages were sent from, along with their interface.
+    sockets: Vec<(tokio::net::UdpSocket, Option<NetworkInterface>)>,
+
+    /// The responses already gathered by a transport, handled before the sockets.
+    pending: VecDeque<DiscoveryPacket>,
+
+    /// When to stop waiting for responses.
+    deadline: tokio::time::Instant,
+
+    /// The transport given to every device found.
+    transport: Arc<dyn Transport>,
+
+    /// The MAC addresses of the devices seen so far.
+    seen_macs: HashSet<[u8; 6]>,
+
+    /// How many more devices to yield, if limited.
+    remaining: Option<usize>,
+
+    /// Set to true to stop the discovery.
+    cancelled: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "tokio")]
+impl DiscoveryStream {
+    /// Sends the discovery messages to every target.
+    pub(crate) async fn start(
+        targets: &[DiscoveryTarget],
+        config: NetworkConfig,
+    ) -> Result<DiscoveryStream, Error> {
+        let mut sockets = vec![];
+        for scan in plan_discovery(targets)? {
+            let (bind, local_ip) = scan_bind_addr(&scan, &config);
+            let socket = bind_broadcast_socket_async(bind).await?;
+
+            // Announce the port that was actually bound
+            let port = socket.local_addr().map_err(Error::Io)?.port();
+            let msg = create_discovery_packet(local_ip, port)?;
+            for destination in &scan.destinations {
+                socket
+                    .send_to(&msg, (*destination, config.device_port))
+                    .await
+                    .map_err(Error::Io)?;
+            }
+
+            sockets.push((socket, scan.interface));
+        }
+
+        return Ok(DiscoveryStream {
+            sockets,
+            pending: VecDeque::new(),
+            deadline: tokio::time::Instant::now() + config.discovery_timeout,
+            transport: Arc::new(UdpTransport::with_config(config)),
+            seen_macs: HashSet::new(),
+            remaining: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        });
+    }
+
+    /// Async version of [DiscoveryIter::start_with_transport].
+    pub(crate) async fn start_with_transport(
+        targets: &[DiscoveryTarget],
+        transport: Arc<dyn Transport>,
+    ) -> Result<DiscoveryStream, Error> {
+        let mut pending = VecDeque::new();
+        for scan in plan_discovery(targets)? {
+            let local_ip = scan.interface.as_ref().map(|x| x.address);
+            let build = |port| create_discovery_packet(local_ip, port);
+            for destination in &scan.destinations {
+                for (bytes, addr) in transport.discover_many_async(&build, *destination).await? {
+                    pending.push_back((bytes, addr, scan.interface.clone()));
+                }
+            }
+        }
+
+        return Ok(DiscoveryStream {
+            sockets: vec![],
+            pending,
+            deadline: tokio::time::Instant::now(),
+            transport,
+            seen_macs: HashSet::new(),
+            remaining: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        });
+    }
+
+    /// Stop the discovery once the specified amount of devices have been found.
+    pub fn stop_after(mut self, devices: usize) -> DiscoveryStream {
+        self.remaining = Some(devices);
+
+        return self;
+    }
+
+    /// Get a flag which stops the discovery when set, e.g. from another task.
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        return self.cancelled.clone();
+    }
+
+    /// Wait for the next device to answer. Returns None once the discovery is over.
+    pub async fn next(&mut self) -> Option<Result<Device, FailedDevice>> {
+        let mut recv_buffer = [0u8; 8092];
+        loop {
+            if self.remaining == Some(0) || self.cancelled.load(Ordering::SeqCst) {
+                return None;
+            }
+
+            // Handle the responses gathered by the transport first, then wait on the sockets
+            let packet = match self.pending.pop_front() {
+                Some(packet) => packet,
+                None => self.receive(&mut recv_buffer).await?,
+            };
+
+            let item = match parse_discovery_packet(&packet, &self.transport, &mut self.seen_macs) {
+                Some(Ok(mut device)) => match device.authenticate_async().await {
+                    Ok(()) => Ok(device),
+                    Err(e) => Err(failed_device(&device, e)),
+                },
+                Some(Err(failed)) => Err(failed),
+                None => continue,
+            };
+
+            if let (Ok(_), Some(remaining)) = (&item, self.remaining.as_mut()) {
+                *remaining -= 1;
+            }
+
+            return Some(item);
+        }
+    }
+
+    /// Wait for a response on any of the sockets, returning None once the deadline is reached
+    /// or the discovery is cancelled.
+    async fn receive(&self, recv_buffer: &mut [u8]) -> Option<DiscoveryPacket> {
+        if self.sockets.is_empty() {
+            return None;
+        }
+
+        loop {
+            if self.cancelled.load(Ordering::SeqCst) {
+                return None;
+            }
+
+            // Wake up regularly to check whether the discovery was cancelled
+            let wake_up = self
+                .deadline
+                .min(tokio::time::Instant::now() + POLL_INTERVAL);
+            let sockets = &self.sockets;
+            let received = tokio::time::timeout_at(
+                wake_up,
+                std::future::poll_fn(|cx| {
+                    for (i, (socket, _)) in sockets.iter().enumerate() {
+                        let mut buf = tokio::io::ReadBuf::new(recv_buffer);
+                        if let std::task::Poll::Ready(result) = socket.poll_recv_from(cx, &mut buf)
+                        {
+                            let bytes_received = buf.filled().len();
+                            return std::task::Poll::Ready(
+                                result.map(|addr| (i, bytes_received, addr)),
+                            );
+                        }
+                    }
+
+                    return std::task::Poll::Pending;
+                }),
+            )
+            .await;
+
+            let (i, bytes_received, addr) = match received {
+                Ok(Ok(r)) => r,
+                Ok(Err(_)) => continue,
+                Err(_) if wake_up < self.deadline => continue,
+                Err(_) => return None,
+            };
+
+            return Some((
+                recv_buffer[0..bytes_received].to_vec(),
+                addr,
+                self.sockets[i].1.clone(),
+            ));
+        }
+    }
+}
+
+/// The destinations to send a discovery message to from a single local interface.
+#[derive(Debug, Clone)]
+struct InterfaceScan {
+    /// The interface to send from, or None to let the system decide.
+    interface: Option<NetworkInterface>,
+
+    /// The addresses to send the discovery message to.
+    destinations: Vec<Ipv4Addr>,
+}
+
+/// Groups the destinations of every target by the local interface to send from.
+fn plan_discovery(targets: &[DiscoveryTarget]) -> Result<Vec<InterfaceScan>, Error> {
+    let interfaces = NetworkInterface::list()?;
+
+    let mut scans: Vec<InterfaceScan> = vec![];
+    let mut add = |interface: Option<&NetworkInterface>, destination: Ipv4Addr| {
+        match scans.iter_mut().find(|x| x.interface.as_ref() == interface) {
+            Some(scan) => scan.destinations.push(destination),
+            None => scans.push(InterfaceScan {
+                interface: interface.cloned(),
+                destinations: vec![destination],
+            }),
+        };
+    };
+
+    for target in targets {
+        match target {
+            DiscoveryTarget::AllInterfaces => {
+                for interface in interfaces.iter().filter(|x| !x.is_loopback()) {
+                    add(Some(interface), interface.broadcast());
+                }
+            }
+            DiscoveryTarget::Interface(name) => {
+                let matching = interfaces
+                    .iter()
+                    .filter(|x| &x.name == name)
+                    .collect::<Vec<&NetworkInterface>>();
+                if matching.is_empty() {
+                    return Err(Error::InvalidArgument(format!(
+                        "No IPv4 interface named {}!",
+                        name
+                    )));
+                }
+
+                for interface in matching {
+                    add(Some(interface), interface.broadcast());
+                }
+            }
+            _ => {
+                for addr in target.addresses()? {
+                    add(interfaces.iter().find(|x| x.contains(addr)), addr);
+                }
+            }
+        };
+    }
+
+    return Ok(scans);
+}
+
+/// Get the local address to bind to and the IP to announce when scanning from an interface.
+fn scan_bind_addr(scan: &InterfaceScan, config: &NetworkConfig) -> (SocketAddr, Option<Ipv4Addr>) {
+    return match &scan.interface {
+        Some(interface) => (
+            SocketAddr::from((interface.address, config.discovery_port)),
+            Some(interface.address),
+        ),
+        // Use any free port, so as not to collide with the sockets bound to each interface
+        None => (
+            SocketAddr::from((config.bind_addr, 0)),
+            Some(config.bind_addr).filter(|x| !x.is_unspecified()),
+        ),
+    };
+}
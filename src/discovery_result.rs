@@ -0,0 +1,50 @@
+use std::{fmt, net::SocketAddr};
+
+use crate::{network::NetworkInterface, Device, Error};
+
+/// The devices found when scanning the network.
+#[derive(Default)]
+pub struct DiscoveryResult {
+    /// Devices which were found and authenticated, at most one per MAC address.
+    pub devices: Vec<Device>,
+
+    /// Devices which answered, but could not be used (e.g. unknown model or failed authentication).
+    pub failed: Vec<FailedDevice>,
+}
+
+/// A device which answered a discovery request, but could not be used.
+#[derive(Debug)]
+pub struct FailedDevice {
+    /// The address that the response was received from.
+    pub address: SocketAddr,
+
+    /// The MAC address of the device, if its response could be decoded.
+    pub mac: Option<[u8; 6]>,
+
+    /// The model code of the device, if its response could be decoded.
+    pub model_code: Option<u16>,
+
+    /// The local interface that the response was received on, if known.
+    pub interface: Option<NetworkInterface>,
+
+    /// The reason why the device could not be used.
+    pub error: Error,
+}
+
+impl fmt::Display for FailedDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.address)?;
+        if let Some(mac) = self.mac {
+            write!(
+                f,
+                " (mac = {})",
+                mac.iter()
+                    .map(|x| format!("{:02X}", x))
+                    .collect::<Vec<String>>()
+                    .join(":")
+            )?;
+        }
+
+        write!(f, ": {}", self.error)
+    }
+}
@@ -0,0 +1,148 @@
+use std::{error, fmt, io};
+
+use block_modes::{BlockModeError, InvalidKeyIvLength};
+use packed_struct::PackingError;
+
+/// Errors returned when communicating with a broadlink device.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// An I/O error occurred on the underlying socket.
+    Io(io::Error),
+
+    /// The device did not respond within the allotted time.
+    Timeout,
+
+    /// A message could not be packed into, or unpacked from, its wire format.
+    Packing(PackingError),
+
+    /// A received message did not match its advertised checksum.
+    ChecksumMismatch {
+        /// The checksum advertised by the message.
+        expected: u16,
+
+        /// The checksum computed from the received data.
+        actual: u16,
+    },
+
+    /// A payload could not be encrypted or decrypted with the session key.
+    Decryption(Box<dyn error::Error + Send + Sync>),
+
+    /// The device reported a model code that this library does not support.
+    UnknownModel(u16),
+
+    /// The device refused to authenticate.
+    AuthRejected,
+
+    /// The device responded with an error code in the command header.
+    Device {
+        /// The raw error code, as reported by the device.
+        code: i16,
+    },
+
+    /// The device sent back a response that could not be understood.
+    InvalidResponse(String),
+
+    /// A value supplied by the caller is not valid for the requested operation.
+    InvalidArgument(String),
+
+    /// The device does not support the requested operation.
+    Unsupported(String),
+}
+
+impl Error {
+    /// Returns true if this error indicates that the session with the device is no longer
+    /// valid, in which case re-authenticating may resolve it.
+    pub fn is_auth_error(&self) -> bool {
+        return match self {
+            Error::AuthRejected | Error::Decryption(_) | Error::ChecksumMismatch { .. } => true,
+            Error::Device { code } => matches!(code, -1 | -2 | -7),
+            _ => false,
+        };
+    }
+
+    /// Returns a human readable description of a device error code.
+    ///
+    /// Refer to -> <https://github.com/mjg59/python-broadlink/blob/master/broadlink/exceptions.py>
+    pub fn describe_device_code(code: i16) -> &'static str {
+        return match code {
+            -1 => "Authentication failed",
+            -2 => "You have been logged out",
+            -3 => "The device is offline",
+            -4 => "Command not supported",
+            -5 => "The device storage is full",
+            -6 => "Structure is abnormal",
+            -7 => "Control key is expired",
+            -8 => "Send error",
+            -9 => "Write error",
+            -10 => "Read error",
+            -11 => "SSID could not be found in AP configuration",
+            _ => "Unknown error",
+        };
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Timeout => write!(f, "No response within timeout!"),
+            Error::Packing(e) => write!(f, "Could not pack message! {}", e),
+            Error::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "Checksum does not match! Expected {:#06X} got {:#06X}",
+                expected, actual
+            ),
+            Error::Decryption(e) => write!(f, "Could not decrypt payload! {}", e),
+            Error::UnknownModel(code) => write!(f, "Unknown device: {} ({:#06X})", code, code),
+            Error::AuthRejected => write!(f, "Device rejected the authentication request!"),
+            Error::Device { code } => write!(
+                f,
+                "Device reported error {}: {}",
+                code,
+                Error::describe_device_code(*code)
+            ),
+            Error::InvalidResponse(msg) => write!(f, "Invalid response from device! {}", msg),
+            Error::InvalidArgument(msg) => write!(f, "Invalid argument! {}", msg),
+            Error::Unsupported(msg) => write!(f, "Not supported by the device! {}", msg),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        return match self {
+            Error::Io(e) => Some(e),
+            Error::Packing(e) => Some(e),
+            Error::Decryption(e) => Some(e.as_ref()),
+            _ => None,
+        };
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        return match e.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Error::Timeout,
+            _ => Error::Io(e),
+        };
+    }
+}
+
+impl From<PackingError> for Error {
+    fn from(e: PackingError) -> Self {
+        return Error::Packing(e);
+    }
+}
+
+impl From<BlockModeError> for Error {
+    fn from(e: BlockModeError) -> Self {
+        return Error::Decryption(Box::new(e));
+    }
+}
+
+impl From<InvalidKeyIvLength> for Error {
+    fn from(e: InvalidKeyIvLength) -> Self {
+        return Error::Decryption(Box::new(e));
+    }
+}
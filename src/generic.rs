@@ -0,0 +1,84 @@
+use std::{net::Ipv4Addr, sync::Arc};
+
+use crate::{
+    constants, lookup_model,
+    network::{util::reverse_mac, DiscoveryResponse, UdpTransport},
+    traits::{CommandTrait, Transport},
+    Device, DeviceInfo, Error, RetryPolicy,
+};
+
+/// A broadlink device of an unknown family, which only supports raw commands.
+///
+/// Use [crate::register_model] to have a model code handled as a known family instead.
+#[derive(Debug, Clone)]
+pub struct GenericDevice {
+    /// Base information about the device.
+    pub info: DeviceInfo,
+
+    /// The transport used to communicate with the device.
+    pub transport: Arc<dyn Transport>,
+
+    /// The policy used to retry failed commands sent to the device.
+    pub retry_policy: RetryPolicy,
+}
+
+impl GenericDevice {
+    /// Create a new GenericDevice.
+    ///
+    /// Note: This should not be called directly. Please use [Device::from_ip] or
+    /// [Device::list] instead.
+    pub fn new(name: &str, addr: Ipv4Addr, response: DiscoveryResponse) -> GenericDevice {
+        // Unknown models may still have been given a name
+        let friendly_model: String = lookup_model(response.model_code)
+            .map_or("Unknown".into(), |model| model.name)
+            .to_string();
+
+        return Self {
+            info: DeviceInfo {
+                address: addr,
+                mac: reverse_mac(response.mac),
+                model_code: response.model_code,
+                friendly_type: "Generic".into(),
+                friendly_model,
+                name: name.into(),
+                auth_id: 0, // This will be populated when authenticated.
+                key: constants::INITIAL_KEY,
+                is_locked: response.is_locked,
+                interface: None,
+            },
+            transport: Arc::new(UdpTransport::default()),
+            retry_policy: RetryPolicy::default(),
+        };
+    }
+
+    /// Sends a raw payload to the device, returning the decrypted response.
+    ///
+    /// The packet type is taken from `T`, e.g. [crate::network::RemoteDataMessage] for data packets.
+    pub fn send_command<T>(&mut self, payload: &[u8]) -> Result<Vec<u8>, Error>
+    where
+        T: CommandTrait,
+    {
+        // We cast this object to a generic device in order to make use of the shared
+        // helper utilities.
+        return Device::Generic {
+            generic: self.clone(),
+        }
+        .send_command_for::<T>(&mut self.info, payload);
+    }
+}
+
+/// Async versions of the generic device operations, for use within a tokio runtime.
+#[cfg(feature = "tokio")]
+impl GenericDevice {
+    /// Async version of [GenericDevice::send_command].
+    pub async fn send_command_async<T>(&mut self, payload: &[u8]) -> Result<Vec<u8>, Error>
+    where
+        T: CommandTrait,
+    {
+        return Device::Generic {
+            generic: self.clone(),
+        }
+        .send_command_for_async::<T>(&mut self.info, payload)
+        .await;
+    }
+}
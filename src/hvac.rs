@@ -1,15 +1,16 @@
-use std::net::Ipv4Addr;
+use std::{net::Ipv4Addr, sync::Arc};
 
 use packed_struct::PackedStructSlice;
 use phf::phf_map;
 
 use crate::{
-    constants,
+    constants, lookup_model,
     network::{
         util::reverse_mac, AirCondInfo, AirCondState, DiscoveryResponse, HvacDataCommand,
-        HvacDataMessage,
+        HvacDataMessage, UdpTransport,
     },
-    Device, DeviceInfo,
+    traits::Transport,
+    Device, DeviceInfo, Error, RetryPolicy,
 };
 
 /// A mapping of hvac device codes to their friendly model equivalent.
@@ -22,6 +23,12 @@ pub const HVAC_CODES: phf::Map<u16, &'static str> = phf_map! {
 pub struct HvacDevice {
     /// Base information about the device.
     pub info: DeviceInfo,
+
+    /// The transport used to communicate with the device.
+    pub transport: Arc<dyn Transport>,
+
+    /// The policy used to retry failed commands sent to the device.
+    pub retry_policy: RetryPolicy,
 }
 
 impl HvacDevice {
@@ -31,9 +38,8 @@ impl HvacDevice {
     /// [Device::list] instead.
     pub fn new(name: &str, addr: Ipv4Addr, response: DiscoveryResponse) -> HvacDevice {
         // Get the name of air conditioner
-        let friendly_model: String = HVAC_CODES
-            .get(&response.model_code)
-            .unwrap_or(&"Unknown")
+        let friendly_model: String = lookup_model(response.model_code)
+            .map_or("Unknown".into(), |model| model.name)
             .to_string();
 
         return Self {
@@ -42,42 +48,37 @@ impl HvacDevice {
                 mac: reverse_mac(response.mac),
                 model_code: response.model_code,
                 friendly_type: "HVAC".into(),
-                friendly_model: friendly_model,
+                friendly_model,
                 name: name.into(),
                 auth_id: 0, // This will be populated when authenticated.
                 key: constants::INITIAL_KEY,
                 is_locked: response.is_locked,
+                interface: None,
             },
+            transport: Arc::new(UdpTransport::default()),
+            retry_policy: RetryPolicy::default(),
         };
     }
 
     /// Get basic information from the air conditioner.
-    pub fn get_info(&self) -> Result<AirCondInfo, String> {
-        let data = self
-            .send_command(&[], HvacDataCommand::GetAcInfo)
-            .map_err(|e| format!("Could not obtain AC info from device! {}", e))?;
-        let info = AirCondInfo::unpack_from_slice(&data)
-            .map_err(|e| format!("Could not unpack command from bytes! {}", e))?;
+    pub fn get_info(&mut self) -> Result<AirCondInfo, Error> {
+        let data = self.send_command(&[], HvacDataCommand::GetAcInfo)?;
+        let info = AirCondInfo::unpack_from_slice(&data)?;
 
         return Ok(info);
     }
 
     /// Get current air conditioner state into AirCondState structure.
-    pub fn get_state(&self) -> Result<AirCondState, String> {
-        let data = self
-            .send_command(&[], HvacDataCommand::GetState)
-            .map_err(|e| format!("Could not obtain AC state from device! {}", e))?;
-        let state = AirCondState::unpack_from_slice(&data)
-            .map_err(|e| format!("Could not unpack command from bytes! {}", e))?;
+    pub fn get_state(&mut self) -> Result<AirCondState, Error> {
+        let data = self.send_command(&[], HvacDataCommand::GetState)?;
+        let state = AirCondState::unpack_from_slice(&data)?;
 
         return Ok(state);
     }
 
     /// Set new air conditioner state based on passed structure.
-    pub fn set_state(&self, state: &mut AirCondState) -> Result<Vec<u8>, String> {
-        let payload = state
-            .prepare_and_pack()
-            .map_err(|e| format!("Could not pack message! {}", e))?;
+    pub fn set_state(&mut self, state: &mut AirCondState) -> Result<Vec<u8>, Error> {
+        let payload = state.prepare_and_pack()?;
         let response = self.send_command(&payload, HvacDataCommand::SetState)?;
 
         return Ok(response);
@@ -86,23 +87,18 @@ impl HvacDevice {
     /// Sends a raw command to the device.
     /// Note: Try to avoid using this method in favor of [HvacDevice::get_info], [HvacDevice::set_state], etc.
     pub fn send_command(
-        &self,
+        &mut self,
         payload: &[u8],
         command: HvacDataCommand,
-    ) -> Result<Vec<u8>, String> {
-        // We cast this object to a generic device in order to make use of the shared
-        // helper utilities.
-        let generic_device = Device::Hvac { hvac: self.clone() };
-
+    ) -> Result<Vec<u8>, Error> {
         // Construct the data message
         let msg = HvacDataMessage::new(command);
-        let packed = msg
-            .pack_with_payload(&payload)
-            .map_err(|e| format!("Could not pack HVAC data message! {}", e))?;
+        let packed = msg.pack_with_payload(payload)?;
 
-        let response = generic_device
-            .send_command::<HvacDataMessage>(&packed)
-            .map_err(|e| format!("Could not send command! {}", e))?;
+        // We cast this object to a generic device in order to make use of the shared
+        // helper utilities.
+        let response = Device::Hvac { hvac: self.clone() }
+            .send_command_for::<HvacDataMessage>(&mut self.info, &packed)?;
 
         // TODO: check if there is some relation between
         // msg.command and the same return field from the response
@@ -110,3 +106,54 @@ impl HvacDevice {
         return HvacDataMessage::unpack_with_payload(&response);
     }
 }
+
+/// Async versions of the HVAC operations, for use within a tokio runtime.
+#[cfg(feature = "tokio")]
+impl HvacDevice {
+    /// Async version of [HvacDevice::get_info].
+    pub async fn get_info_async(&mut self) -> Result<AirCondInfo, Error> {
+        let data = self
+            .send_command_async(&[], HvacDataCommand::GetAcInfo)
+            .await?;
+        let info = AirCondInfo::unpack_from_slice(&data)?;
+
+        return Ok(info);
+    }
+
+    /// Async version of [HvacDevice::get_state].
+    pub async fn get_state_async(&mut self) -> Result<AirCondState, Error> {
+        let data = self
+            .send_command_async(&[], HvacDataCommand::GetState)
+            .await?;
+        let state = AirCondState::unpack_from_slice(&data)?;
+
+        return Ok(state);
+    }
+
+    /// Async version of [HvacDevice::set_state].
+    pub async fn set_state_async(&mut self, state: &mut AirCondState) -> Result<Vec<u8>, Error> {
+        let payload = state.prepare_and_pack()?;
+        let response = self
+            .send_command_async(&payload, HvacDataCommand::SetState)
+            .await?;
+
+        return Ok(response);
+    }
+
+    /// Async version of [HvacDevice::send_command].
+    pub async fn send_command_async(
+        &mut self,
+        payload: &[u8],
+        command: HvacDataCommand,
+    ) -> Result<Vec<u8>, Error> {
+        // Construct the data message
+        let msg = HvacDataMessage::new(command);
+        let packed = msg.pack_with_payload(payload)?;
+
+        let response = Device::Hvac { hvac: self.clone() }
+            .send_command_for_async::<HvacDataMessage>(&mut self.info, &packed)
+            .await?;
+
+        return HvacDataMessage::unpack_with_payload(&response);
+    }
+}
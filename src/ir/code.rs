@@ -0,0 +1,217 @@
+use crate::Error;
+
+/// The duration of a single tick of a code, in µs.
+pub const TICK_US: f64 = 32.84;
+
+/// The gap appended to a code ending with a pulse, in ticks.
+pub const TERMINATOR_TICKS: u16 = 0x0D05;
+
+/// The size of the header preceding the durations of a code.
+const HEADER_SIZE: usize = 0x04;
+
+/// The type byte of IR codes.
+const IR_TYPE: u8 = 0x26;
+
+/// The frequency band of an RF code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RfBand {
+    /// 433 MHz
+    Rf433,
+
+    /// 315 MHz
+    Rf315,
+}
+
+impl RfBand {
+    /// Get the type byte of codes in this band.
+    fn type_byte(&self) -> u8 {
+        return match self {
+            RfBand::Rf433 => 0xB2,
+            RfBand::Rf315 => 0xD7,
+        };
+    }
+
+    /// Get the band of codes with the given type byte, if any.
+    fn from_type_byte(value: u8) -> Option<RfBand> {
+        return match value {
+            0xB2 => Some(RfBand::Rf433),
+            0xD7 => Some(RfBand::Rf315),
+            _ => None,
+        };
+    }
+}
+
+/// An IR code, as alternating pulse / space durations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IrCode {
+    /// How many times to repeat the code after sending it once
+    pub repeat: u8,
+
+    /// The durations of the code in µs, starting with a pulse and alternating with spaces
+    pub durations: Vec<u32>,
+}
+
+impl IrCode {
+    /// Create a new IrCode, sent once.
+    pub fn new(durations: Vec<u32>) -> IrCode {
+        return IrCode {
+            repeat: 0,
+            durations,
+        };
+    }
+
+    /// Parse an IR code from the bytes learned by, or sent to, a remote.
+    pub fn from_bytes(bytes: &[u8]) -> Result<IrCode, Error> {
+        let (code_type, repeat, durations) = decode(bytes)?;
+        if code_type != IR_TYPE {
+            return Err(Error::InvalidArgument(format!(
+                "Not an IR code! Expected type {:#04X}, got {:#04X}",
+                IR_TYPE, code_type
+            )));
+        }
+
+        return Ok(IrCode { repeat, durations });
+    }
+
+    /// Encode the IR code into bytes, as accepted by [crate::RemoteDevice::send_code].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        return encode(IR_TYPE, self.repeat, &self.durations);
+    }
+}
+
+/// An RF code, as alternating pulse / space durations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RfCode {
+    /// The frequency band of the code
+    pub band: RfBand,
+
+    /// How many times to repeat the code after sending it once
+    pub repeat: u8,
+
+    /// The durations of the code in µs, starting with a pulse and alternating with spaces
+    pub durations: Vec<u32>,
+}
+
+impl RfCode {
+    /// Create a new RfCode, sent once.
+    pub fn new(band: RfBand, durations: Vec<u32>) -> RfCode {
+        return RfCode {
+            band,
+            repeat: 0,
+            durations,
+        };
+    }
+
+    /// Parse an RF code from the bytes learned by, or sent to, a remote.
+    pub fn from_bytes(bytes: &[u8]) -> Result<RfCode, Error> {
+        let (code_type, repeat, durations) = decode(bytes)?;
+        let band = RfBand::from_type_byte(code_type).ok_or_else(|| {
+            Error::InvalidArgument(format!("Not an RF code! Got type {:#04X}", code_type))
+        })?;
+
+        return Ok(RfCode {
+            band,
+            repeat,
+            durations,
+        });
+    }
+
+    /// Encode the RF code into bytes, as accepted by [crate::RemoteDevice::send_code].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        return encode(self.band.type_byte(), self.repeat, &self.durations);
+    }
+}
+
+/// Decodes the type, repeat count and durations (in µs) of a code.
+fn decode(bytes: &[u8]) -> Result<(u8, u8, Vec<u32>), Error> {
+    if bytes.len() < HEADER_SIZE {
+        return Err(Error::InvalidArgument(format!(
+            "Code is too short! Expected at least {} bytes, got {}",
+            HEADER_SIZE,
+            bytes.len()
+        )));
+    }
+
+    // Learned codes are padded, so only read up to the advertised length
+    let length = usize::from(u16::from_le_bytes([bytes[2], bytes[3]]));
+    let end = bytes.len().min(HEADER_SIZE + length);
+
+    let mut durations = vec![];
+    let mut index = HEADER_SIZE;
+    while index < end {
+        // Long durations are prefixed with a zero and stored big endian
+        let ticks = match bytes[index] {
+            0 => {
+                let long = bytes.get(index + 1..index + 3).ok_or_else(|| {
+                    Error::InvalidArgument("Code ends within a long duration!".into())
+                })?;
+                index += 3;
+
+                u16::from_be_bytes([long[0], long[1]])
+            }
+            short => {
+                index += 1;
+
+                u16::from(short)
+            }
+        };
+
+        durations.push(ticks_to_us(ticks));
+    }
+
+    return Ok((bytes[0], bytes[1], durations));
+}
+
+/// Encodes durations (in µs) into a code of the given type.
+fn encode(code_type: u8, repeat: u8, durations: &[u32]) -> Result<Vec<u8>, Error> {
+    let mut data = vec![];
+    for &duration in durations {
+        push_ticks(&mut data, us_to_ticks(duration)?);
+    }
+
+    // Codes must end with a space, so that repeats are told apart
+    if durations.len() % 2 == 1 {
+        push_ticks(&mut data, TERMINATOR_TICKS);
+    }
+
+    let length: u16 = data
+        .len()
+        .try_into()
+        .map_err(|e| Error::InvalidArgument(format!("Code is too long! {}", e)))?;
+
+    let mut result = vec![code_type, repeat];
+    result.extend(length.to_le_bytes());
+    result.extend(data);
+
+    return Ok(result);
+}
+
+/// Appends a duration in ticks, using the long form when it does not fit in a single byte.
+fn push_ticks(data: &mut Vec<u8>, ticks: u16) {
+    match u8::try_from(ticks) {
+        Ok(short) if short != 0 => data.push(short),
+        _ => {
+            data.push(0);
+            data.extend(ticks.to_be_bytes());
+        }
+    };
+}
+
+/// Converts a duration in ticks to µs.
+fn ticks_to_us(ticks: u16) -> u32 {
+    return (f64::from(ticks) * TICK_US).round() as u32;
+}
+
+/// Converts a duration in µs to ticks.
+fn us_to_ticks(duration: u32) -> Result<u16, Error> {
+    let ticks = (f64::from(duration) / TICK_US).round();
+    if ticks > f64::from(u16::MAX) {
+        return Err(Error::InvalidArgument(format!(
+            "Duration is too long! Expected at most {}µs, got {}µs",
+            ticks_to_us(u16::MAX),
+            duration
+        )));
+    }
+
+    return Ok(ticks as u16);
+}
@@ -0,0 +1,410 @@
+use std::fmt;
+
+use crate::{
+    ir::{
+        IrCode, IrProtocol, ProtocolCode, NECX_HEADER, NECX_UNIT, NEC_HEADER, NEC_REPEAT, NEC_UNIT,
+        PANASONIC_HEADER, PANASONIC_UNIT, PANASONIC_VENDOR, RC5_UNIT, RC6_HEADER, RC6_UNIT,
+        SONY_HEADER, SONY_UNIT,
+    },
+    Error,
+};
+
+/// The relative tolerance when matching a duration against the one expected.
+const TOLERANCE: f64 = 0.3;
+
+/// The smallest tolerance when matching a duration, in µs, to allow for the resolution of remotes.
+const MIN_TOLERANCE_US: f64 = 150.0;
+
+/// The result of decoding an IR code.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedCode {
+    /// A code matching a known protocol.
+    Protocol {
+        /// The parameters of the code, which generate an equivalent code
+        code: ProtocolCode,
+
+        /// How closely the durations match the protocol, from 0 to 1
+        confidence: f64,
+    },
+
+    /// A code not matching any known protocol.
+    Unknown {
+        /// The durations of the code in µs, starting with a pulse and alternating with spaces
+        durations: Vec<u32>,
+    },
+}
+
+impl DecodedCode {
+    /// Decode the bytes of an IR code, as learned by a remote.
+    pub fn from_bytes(bytes: &[u8]) -> Result<DecodedCode, Error> {
+        return Ok(DecodedCode::from_ir_code(&IrCode::from_bytes(bytes)?));
+    }
+
+    /// Decode an IR code, picking the protocol matching its durations the closest.
+    pub fn from_ir_code(code: &IrCode) -> DecodedCode {
+        let decoders: [fn(&mut Matcher) -> Option<ProtocolCode>; 6] = [
+            decode_nec,
+            decode_necx,
+            decode_rc5,
+            decode_rc6,
+            decode_sony,
+            decode_panasonic,
+        ];
+
+        let best = decoders
+            .iter()
+            .filter_map(|decoder| {
+                let mut matcher = Matcher::new(&code.durations);
+                let frame = decoder(&mut matcher)?;
+                let mut decoded = frame.clone();
+
+                // Count the frames repeated within the code, as when a button is held
+                if decoded.protocol == IrProtocol::Nec {
+                    while decode_nec_repeat(&mut matcher) {
+                        decoded.repeats = decoded.repeats.saturating_add(1);
+                    }
+                } else {
+                    decoded.repeats = code.repeat;
+                    loop {
+                        let mut next = matcher.clone();
+                        if decoder(&mut next).as_ref() != Some(&frame) {
+                            break;
+                        }
+
+                        decoded.repeats = decoded.repeats.saturating_add(1);
+                        matcher = next;
+                    }
+                }
+
+                return Some((decoded, matcher.confidence()));
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        return match best {
+            Some((code, confidence)) => DecodedCode::Protocol { code, confidence },
+            None => DecodedCode::Unknown {
+                durations: code.durations.clone(),
+            },
+        };
+    }
+}
+
+impl fmt::Display for DecodedCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodedCode::Protocol { code, .. } => write!(
+                f,
+                "{} addr {:#04x} cmd {:#04x}",
+                code.protocol, code.address, code.command
+            ),
+            DecodedCode::Unknown { durations } => {
+                write!(f, "Unknown ({} durations)", durations.len())
+            }
+        }
+    }
+}
+
+/// Matches durations against the ones expected by a protocol, keeping track of the error.
+#[derive(Debug, Clone)]
+struct Matcher<'a> {
+    /// The durations being matched, starting with a pulse
+    durations: &'a [u32],
+
+    /// The index of the next duration to match
+    index: usize,
+
+    /// The sum of the relative errors of the matched durations
+    error: f64,
+
+    /// The amount of matched durations, which count towards the error
+    matched: usize,
+}
+
+impl<'a> Matcher<'a> {
+    /// Create a new Matcher, starting at the first duration.
+    fn new(durations: &'a [u32]) -> Matcher<'a> {
+        return Matcher {
+            durations,
+            index: 0,
+            error: 0.0,
+            matched: 0,
+        };
+    }
+
+    /// Whether the next duration is a pulse.
+    fn is_pulse(&self) -> bool {
+        return self.index % 2 != 1;
+    }
+
+    /// Get the relative error of the next duration against the one expected, if within tolerance.
+    fn peek(&self, expected: u32) -> Option<f64> {
+        let actual = f64::from(*self.durations.get(self.index)?);
+        let expected = f64::from(expected);
+
+        let difference = (actual - expected).abs();
+        if difference > (expected * TOLERANCE).max(MIN_TOLERANCE_US) {
+            return None;
+        }
+
+        return Some(difference / expected);
+    }
+
+    /// Match the next duration, moving past it if within tolerance.
+    fn expect(&mut self, expected: u32) -> Option<()> {
+        let error = self.peek(expected)?;
+        self.index += 1;
+        self.error += error;
+        self.matched += 1;
+
+        return Some(());
+    }
+
+    /// Match the next duration against a multiple of a unit, returning the multiple.
+    fn units(&mut self, unit: u32, max: u32) -> Option<u32> {
+        let actual = *self.durations.get(self.index)?;
+        let units = (f64::from(actual) / f64::from(unit)).round() as u32;
+        if !(1..=max).contains(&units) {
+            return None;
+        }
+
+        self.expect(units * unit)?;
+        return Some(units);
+    }
+
+    /// Match the gap ending a frame, which is either a space or the end of the code.
+    fn gap(&mut self) {
+        if !self.is_pulse() && self.index < self.durations.len() {
+            self.index += 1;
+        }
+    }
+
+    /// Get how closely the durations matched, from 0 to 1.
+    ///
+    /// Durations left unmatched lower the confidence, as they may be noise.
+    fn confidence(&self) -> f64 {
+        if self.matched == 0 {
+            return 0.0;
+        }
+
+        let accuracy = 1.0 - self.error / self.matched as f64;
+        let coverage = self.index as f64 / self.durations.len() as f64;
+
+        return (accuracy * coverage).clamp(0.0, 1.0);
+    }
+
+    /// Match a pulse distance encoded frame, returning its data with the least significant bit first.
+    fn pulse_distance(&mut self, header: (u32, u32), bits: u8, unit: u32) -> Option<u64> {
+        self.expect(header.0)?;
+        self.expect(header.1)?;
+
+        let mut data = 0u64;
+        for bit in 0..bits {
+            self.expect(unit)?;
+            if self.peek(3 * unit).is_some() {
+                self.expect(3 * unit)?;
+                data |= 1 << bit;
+            } else {
+                self.expect(unit)?;
+            }
+        }
+
+        // The stop bit
+        self.expect(unit)?;
+        self.gap();
+
+        return Some(data);
+    }
+
+    /// Match the half bits of a Manchester encoded frame, as levels which are true for pulses.
+    ///
+    /// The end of the frame is padded with spaces, as the last one merges with the gap.
+    fn half_bits(
+        &mut self,
+        mut levels: Vec<bool>,
+        count: usize,
+        unit: u32,
+        max: u32,
+    ) -> Option<Vec<bool>> {
+        while levels.len() < count {
+            let is_pulse = self.is_pulse();
+            match self.units(unit, max) {
+                Some(units) => levels.extend((0..units).map(|_| is_pulse)),
+                None if is_pulse && self.index < self.durations.len() => return None,
+                None => break,
+            };
+        }
+
+        if levels.len() > count {
+            return None;
+        }
+
+        levels.resize(count, false);
+        self.gap();
+
+        return Some(levels);
+    }
+}
+
+/// Read the bits of Manchester encoded half bits, with the most significant bit first.
+fn manchester_bits(levels: &[bool], one_is_pulse: bool) -> Option<u64> {
+    let mut data = 0u64;
+    for pair in levels.chunks(2) {
+        if pair[0] == pair[1] {
+            return None;
+        }
+
+        data = (data << 1) | u64::from(pair[0] == one_is_pulse);
+    }
+
+    return Some(data);
+}
+
+/// Decode a NEC frame.
+fn decode_nec(matcher: &mut Matcher) -> Option<ProtocolCode> {
+    let data = matcher.pulse_distance(NEC_HEADER, 32, NEC_UNIT)?;
+    let (address, command) = split_nec(data)?;
+
+    // Short addresses are followed by their inverse, extended ones are not
+    let address = if address & 0xFF == !(address >> 8) & 0xFF {
+        address & 0xFF
+    } else {
+        address
+    };
+
+    return Some(ProtocolCode::new(IrProtocol::Nec, address, command));
+}
+
+/// Decode a NEC repeat frame.
+fn decode_nec_repeat(matcher: &mut Matcher) -> bool {
+    let mut next = matcher.clone();
+    if next.expect(NEC_REPEAT.0).is_none()
+        || next.expect(NEC_REPEAT.1).is_none()
+        || next.expect(NEC_UNIT).is_none()
+    {
+        return false;
+    }
+
+    next.gap();
+    *matcher = next;
+
+    return true;
+}
+
+/// Decode a NECx or Samsung frame, told apart by Samsung repeating its address.
+fn decode_necx(matcher: &mut Matcher) -> Option<ProtocolCode> {
+    let data = matcher.pulse_distance(NECX_HEADER, 32, NECX_UNIT)?;
+    let (address, command) = split_nec(data)?;
+
+    return Some(if address & 0xFF == address >> 8 {
+        ProtocolCode::new(IrProtocol::Samsung, address & 0xFF, command)
+    } else {
+        ProtocolCode::new(IrProtocol::NecExtended, address, command)
+    });
+}
+
+/// Split the data of a NEC-like frame into its address and command, checking the inverted command.
+fn split_nec(data: u64) -> Option<(u16, u16)> {
+    let address = (data & 0xFFFF) as u16;
+    let command = ((data >> 16) & 0xFF) as u16;
+    if (data >> 24) as u16 != !command & 0xFF {
+        return None;
+    }
+
+    return Some((address, command));
+}
+
+/// Decode an RC5 frame.
+fn decode_rc5(matcher: &mut Matcher) -> Option<ProtocolCode> {
+    // The leading space of the first start bit cannot be learned
+    let levels = matcher.half_bits(vec![false], 28, RC5_UNIT, 2)?;
+    let data = manchester_bits(&levels, false)?;
+
+    // The first start bit is always set, while the second one holds the inverted 7th bit of the command
+    if data >> 13 != 1 {
+        return None;
+    }
+
+    let field = (data >> 12) & 1;
+    let command = (data & 0x3F) | ((field ^ 1) << 6);
+    let mut code = ProtocolCode::new(IrProtocol::Rc5, ((data >> 6) & 0x1F) as u16, command as u16);
+    code.toggle = (data >> 11) & 1 == 1;
+
+    return Some(code);
+}
+
+/// Decode an RC6 mode 0 frame.
+fn decode_rc6(matcher: &mut Matcher) -> Option<ProtocolCode> {
+    matcher.expect(RC6_HEADER.0)?;
+    matcher.expect(RC6_HEADER.1)?;
+
+    // The double width toggle bit can merge with the half bits around it
+    let levels = matcher.half_bits(vec![], 44, RC6_UNIT, 3)?;
+
+    // The start bit, followed by mode 0
+    if manchester_bits(&levels[0..8], true)? != 0b1000 {
+        return None;
+    }
+
+    let toggle = match levels[8..12] {
+        [true, true, false, false] => true,
+        [false, false, true, true] => false,
+        _ => return None,
+    };
+
+    let data = manchester_bits(&levels[12..], true)?;
+    let mut code = ProtocolCode::new(IrProtocol::Rc6, (data >> 8) as u16, (data & 0xFF) as u16);
+    code.toggle = toggle;
+
+    return Some(code);
+}
+
+/// Decode a Sony SIRC frame, of any supported length.
+fn decode_sony(matcher: &mut Matcher) -> Option<ProtocolCode> {
+    matcher.expect(SONY_HEADER.0)?;
+    matcher.expect(SONY_HEADER.1)?;
+
+    // Sony codes are pulse width encoded, and the last space merges with the gap
+    let mut data = 0u64;
+    let mut bits = 0u8;
+    loop {
+        if matcher.units(SONY_UNIT, 2)? == 2 {
+            data |= 1 << bits;
+        }
+        bits += 1;
+
+        if bits > 20 || matcher.peek(SONY_UNIT).is_none() {
+            break;
+        }
+        matcher.expect(SONY_UNIT)?;
+    }
+    matcher.gap();
+
+    if !IrProtocol::Sony.bit_counts().contains(&bits) {
+        return None;
+    }
+
+    let mut code = ProtocolCode::new(IrProtocol::Sony, (data >> 7) as u16, (data & 0x7F) as u16);
+    code.bits = bits;
+
+    return Some(code);
+}
+
+/// Decode a Panasonic frame, checking its vendor and checksum.
+fn decode_panasonic(matcher: &mut Matcher) -> Option<ProtocolCode> {
+    let data = matcher.pulse_distance(PANASONIC_HEADER, 48, PANASONIC_UNIT)?;
+    let bytes = data.to_le_bytes();
+    if bytes[0..2] != PANASONIC_VENDOR || bytes[2] & 0x0F != 0 {
+        return None;
+    }
+    if bytes[2] ^ bytes[3] ^ bytes[4] != bytes[5] {
+        return None;
+    }
+
+    let address = u16::from(bytes[2] >> 4) | (u16::from(bytes[3]) << 4);
+
+    return Some(ProtocolCode::new(
+        IrProtocol::Panasonic,
+        address,
+        u16::from(bytes[4]),
+    ));
+}
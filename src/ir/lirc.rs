@@ -0,0 +1,527 @@
+use crate::{
+    ir::{IrCode, Pulses, TERMINATOR_TICKS, TICK_US},
+    Error,
+};
+
+/// The amount of durations written per line of exported raw codes.
+const DURATIONS_PER_LINE: usize = 6;
+
+/// The maximum amount of bits of a code, its pre data or its post data.
+const MAX_BITS: u32 = 64;
+
+/// A remote read from, or written to, a LIRC config file (lircd.conf).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LircRemote {
+    /// The name of the remote
+    pub name: String,
+
+    /// The buttons of the remote, in the order of the config
+    pub buttons: Vec<LircButton>,
+}
+
+/// A named button of a [LircRemote].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LircButton {
+    /// The name of the button, e.g. `KEY_POWER`
+    pub name: String,
+
+    /// The code sent by the button
+    pub code: IrCode,
+}
+
+impl LircRemote {
+    /// Create a new LircRemote, without any buttons.
+    pub fn new(name: &str) -> LircRemote {
+        return LircRemote {
+            name: name.into(),
+            buttons: vec![],
+        };
+    }
+
+    /// Get the code of a button by its name, if any.
+    pub fn get_code(&self, name: &str) -> Option<&IrCode> {
+        return self
+            .buttons
+            .iter()
+            .find(|button| button.name == name)
+            .map(|button| &button.code);
+    }
+
+    /// Parse the remotes of a LIRC config, using either raw codes or protocol parameters.
+    ///
+    /// Note: Only space encoded (e.g. NEC), RC5 and RC6 remotes are supported for protocol parameters.
+    pub fn from_config(config: &str) -> Result<Vec<LircRemote>, Error> {
+        let mut remotes = vec![];
+        let mut section = Section::Outside;
+        let mut remote = LircRemote::new("");
+        let mut parameters = Parameters::default();
+
+        for (index, line) in config.lines().enumerate() {
+            let error = |message: &str| {
+                return Error::InvalidArgument(format!(
+                    "Invalid LIRC config at line {}! {}",
+                    index + 1,
+                    message
+                ));
+            };
+
+            // Report the errors of the helpers along with the line
+            let at_line = |e: Error| {
+                return match e {
+                    Error::InvalidArgument(message) => error(&message),
+                    e => e,
+                };
+            };
+
+            // Comments run until the end of the line
+            let line = line.split('#').next().unwrap_or_default();
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let Some(&key) = tokens.first() else {
+                continue;
+            };
+            let values = &tokens[1..];
+
+            section = match (section, key, values) {
+                (Section::Outside, "begin", ["remote"]) => {
+                    remote = LircRemote::new("");
+                    parameters = Parameters::default();
+
+                    Section::Remote
+                }
+                (Section::Outside, _, _) => return Err(error("Expected the start of a remote")),
+                (Section::Remote, "end", ["remote"]) => {
+                    remotes.push(parameters.finish(remote.clone()).map_err(at_line)?);
+
+                    Section::Outside
+                }
+                (Section::Remote, "begin", ["codes"]) => Section::Codes,
+                (Section::Remote, "begin", ["raw_codes"]) => Section::RawCodes,
+                (Section::Remote, "name", [name]) => {
+                    remote.name = name.to_string();
+
+                    Section::Remote
+                }
+                (Section::Remote, _, _) => {
+                    parameters.set(key, values).map_err(at_line)?;
+
+                    Section::Remote
+                }
+                (Section::Codes, "end", ["codes"]) => Section::Remote,
+                (Section::Codes, name, _) => {
+                    let data = values
+                        .iter()
+                        .map(|value| parse_number(value))
+                        .collect::<Result<Vec<u64>, Error>>()
+                        .map_err(at_line)?;
+                    if data.is_empty() {
+                        return Err(error(&format!("Button {} has no code", name)));
+                    }
+
+                    parameters.codes.push((name.to_string(), data));
+
+                    Section::Codes
+                }
+                (Section::RawCodes, "end", ["raw_codes"]) => Section::Remote,
+                (Section::RawCodes, "name", [name]) => {
+                    parameters.raw_codes.push((name.to_string(), vec![]));
+
+                    Section::RawCodes
+                }
+                (Section::RawCodes, _, _) => {
+                    let (_, durations) = parameters
+                        .raw_codes
+                        .last_mut()
+                        .ok_or_else(|| error("Raw code without a button name"))?;
+                    for token in &tokens {
+                        let duration = parse_number(token).map_err(at_line)?;
+                        durations.push(u32::try_from(duration).map_err(|e| error(&e.to_string()))?);
+                    }
+
+                    Section::RawCodes
+                }
+            };
+        }
+
+        if section != Section::Outside {
+            return Err(Error::InvalidArgument(
+                "Invalid LIRC config! The last remote is not terminated".into(),
+            ));
+        }
+
+        return Ok(remotes);
+    }
+
+    /// Export the remote as a LIRC config, using raw codes.
+    ///
+    /// Fails if the name of the remote or of a button is not a single word, since it could not
+    /// be read back.
+    pub fn to_config(&self) -> Result<String, Error> {
+        check_name(&self.name)?;
+
+        let mut codes = vec![];
+        let mut gap = 0;
+        for button in &self.buttons {
+            check_name(&button.name)?;
+
+            // Raw codes end with a pulse, followed by the gap of the remote
+            let mut durations = vec![];
+            for _ in 0..=button.code.repeat {
+                durations.extend(&button.code.durations);
+                if durations.len() % 2 == 1 {
+                    durations.push((f64::from(TERMINATOR_TICKS) * TICK_US).round() as u32);
+                }
+            }
+            gap = gap.max(durations.pop().unwrap_or_default());
+
+            codes.push((&button.name, durations));
+        }
+
+        let mut config = String::new();
+        config += "begin remote\n\n";
+        config += &format!("  name  {}\n", self.name);
+        config += "  flags RAW_CODES\n";
+        config += "  eps            30\n";
+        config += "  aeps          100\n\n";
+        config += &format!("  gap          {}\n\n", gap);
+        config += "      begin raw_codes\n\n";
+        for (name, durations) in codes {
+            config += &format!("          name {}\n", name);
+            for line in durations.chunks(DURATIONS_PER_LINE) {
+                let line: Vec<String> = line.iter().map(|d| format!("{:>8}", d)).collect();
+                config += &format!("          {}\n", line.join(""));
+            }
+            config += "\n";
+        }
+        config += "      end raw_codes\n\n";
+        config += "end remote\n";
+
+        return Ok(config);
+    }
+}
+
+/// The section of a LIRC config being parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    /// Between remotes
+    Outside,
+
+    /// Within the parameters of a remote
+    Remote,
+
+    /// Within the protocol codes of a remote
+    Codes,
+
+    /// Within the raw codes of a remote
+    RawCodes,
+}
+
+/// How the bits of protocol codes are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    /// Each bit is a pulse followed by a space, told apart by their durations
+    Space,
+
+    /// Bi-phase, sending ones as a space followed by a pulse (RC5)
+    Shift,
+
+    /// Bi-phase, sending ones as a pulse followed by a space (RC6)
+    Rc6,
+}
+
+/// The parameters of a remote, along with its codes waiting to be encoded.
+#[derive(Debug, Default)]
+struct Parameters {
+    /// The flags of the remote, e.g. `SPACE_ENC`
+    flags: Vec<String>,
+
+    /// The amount of bits of each code
+    bits: u32,
+
+    /// The pulse / space sent before each code, if non zero
+    header: (u32, u32),
+
+    /// The pulse / space of a one
+    one: (u32, u32),
+
+    /// The pulse / space of a zero
+    zero: (u32, u32),
+
+    /// The pulse sent before the data, if non zero
+    plead: u32,
+
+    /// The pulse sent after the data, if non zero
+    ptrail: u32,
+
+    /// The data sent before each code, and its amount of bits
+    pre_data: (u64, u32),
+
+    /// The pulse / space sent after the pre data, if non zero
+    pre: (u32, u32),
+
+    /// The data sent after each code, and its amount of bits
+    post_data: (u64, u32),
+
+    /// The pulse / space sent before the post data, if non zero
+    post: (u32, u32),
+
+    /// The pulse / space sent after each code, if non zero
+    foot: (u32, u32),
+
+    /// The space following each code, or the total length of a code for `CONST_LENGTH` remotes
+    gap: u32,
+
+    /// The bits lasting twice as long, for RC6 remotes
+    rc6_mask: u64,
+
+    /// The named protocol codes of the remote
+    codes: Vec<(String, Vec<u64>)>,
+
+    /// The named raw codes of the remote, in µs
+    raw_codes: Vec<(String, Vec<u32>)>,
+}
+
+impl Parameters {
+    /// Set a parameter from its values, ignoring the ones which do not affect the codes.
+    fn set(&mut self, key: &str, values: &[&str]) -> Result<(), Error> {
+        let numbers = || {
+            return values
+                .iter()
+                .map(|value| parse_number(value))
+                .collect::<Result<Vec<u64>, Error>>();
+        };
+        let to_u32 = |value: u64| {
+            return u32::try_from(value)
+                .map_err(|e| Error::InvalidArgument(format!("Invalid value for {}! {}", key, e)));
+        };
+        let single = || -> Result<u32, Error> {
+            return match numbers()?[..] {
+                [value] => to_u32(value),
+                _ => Err(Error::InvalidArgument(format!(
+                    "Expected a single value for {}",
+                    key
+                ))),
+            };
+        };
+        let pair = || -> Result<(u32, u32), Error> {
+            return match numbers()?[..] {
+                [pulse, space] => Ok((to_u32(pulse)?, to_u32(space)?)),
+                _ => Err(Error::InvalidArgument(format!(
+                    "Expected a pulse and a space for {}",
+                    key
+                ))),
+            };
+        };
+        let width = || -> Result<u32, Error> {
+            let bits = single()?;
+            if bits > MAX_BITS {
+                return Err(Error::InvalidArgument(format!(
+                    "Expected at most {} bits for {}, got {}",
+                    MAX_BITS, key, bits
+                )));
+            }
+
+            return Ok(bits);
+        };
+
+        match key {
+            "flags" => {
+                self.flags = values
+                    .join("")
+                    .split('|')
+                    .map(|flag| flag.trim().to_string())
+                    .collect();
+            }
+            "bits" => self.bits = width()?,
+            "header" => self.header = pair()?,
+            "one" => self.one = pair()?,
+            "zero" => self.zero = pair()?,
+            "plead" => self.plead = single()?,
+            "ptrail" => self.ptrail = single()?,
+            "pre_data_bits" => self.pre_data.1 = width()?,
+            "pre_data" => self.pre_data.0 = numbers()?.first().copied().unwrap_or_default(),
+            "pre" => self.pre = pair()?,
+            "post_data_bits" => self.post_data.1 = width()?,
+            "post_data" => self.post_data.0 = numbers()?.first().copied().unwrap_or_default(),
+            "post" => self.post = pair()?,
+            "foot" => self.foot = pair()?,
+            "gap" => self.gap = numbers()?.first().copied().unwrap_or_default() as u32,
+            "rc6_mask" => self.rc6_mask = numbers()?.first().copied().unwrap_or_default(),
+            _ => (),
+        };
+
+        return Ok(());
+    }
+
+    /// Whether the remote has the given flag.
+    fn has_flag(&self, flag: &str) -> bool {
+        return self.flags.iter().any(|f| f == flag);
+    }
+
+    /// Add the buttons of the remote, encoding its codes.
+    fn finish(&self, mut remote: LircRemote) -> Result<LircRemote, Error> {
+        for (name, durations) in &self.raw_codes {
+            // The gap of the remote follows each raw code
+            let mut durations = durations.clone();
+            if durations.len() % 2 == 1 && self.gap > 0 {
+                durations.push(self.gap);
+            }
+
+            remote.buttons.push(LircButton {
+                name: name.clone(),
+                code: IrCode::new(durations),
+            });
+        }
+
+        if self.codes.is_empty() {
+            return Ok(remote);
+        }
+
+        let encoding = if self.has_flag("RC6") {
+            Encoding::Rc6
+        } else if self.has_flag("RC5") || self.has_flag("SHIFT_ENC") {
+            Encoding::Shift
+        } else if let Some(flag) = self
+            .flags
+            .iter()
+            .find(|flag| ["RCMM", "XMP", "GRUNDIG", "BO", "SERIAL"].contains(&flag.as_str()))
+        {
+            return Err(Error::InvalidArgument(format!(
+                "Unsupported encoding {}",
+                flag
+            )));
+        } else {
+            Encoding::Space
+        };
+
+        for (name, data) in &self.codes {
+            let mut pulses = Pulses::default();
+            for &code in data {
+                self.encode(&mut pulses, encoding, code);
+            }
+
+            remote.buttons.push(LircButton {
+                name: name.clone(),
+                code: IrCode::new(pulses.durations),
+            });
+        }
+
+        return Ok(remote);
+    }
+
+    /// Encode a single code, as sent by LIRC.
+    fn encode(&self, pulses: &mut Pulses, encoding: Encoding, code: u64) {
+        let start = pulses.elapsed();
+        let send_pair = |pulses: &mut Pulses, pair: (u32, u32)| {
+            if pair != (0, 0) {
+                pulses.pulse(pair.0);
+                pulses.space(pair.1);
+            }
+        };
+
+        send_pair(pulses, self.header);
+        if self.plead > 0 {
+            pulses.pulse(self.plead);
+        }
+
+        // RC6 masks apply to the bits of the whole code, starting with the pre data
+        let total = self.pre_data.1 + self.bits + self.post_data.1;
+        let mut sent = 0;
+        self.encode_bits(pulses, encoding, self.pre_data, total, &mut sent);
+        send_pair(pulses, self.pre);
+        self.encode_bits(pulses, encoding, (code, self.bits), total, &mut sent);
+        send_pair(pulses, self.post);
+        self.encode_bits(pulses, encoding, self.post_data, total, &mut sent);
+
+        if self.ptrail > 0 {
+            pulses.pulse(self.ptrail);
+        }
+        send_pair(pulses, self.foot);
+
+        // Constant length codes are padded to the gap
+        let gap = if self.has_flag("CONST_LENGTH") {
+            self.gap.saturating_sub(pulses.elapsed() - start)
+        } else {
+            self.gap
+        };
+        if gap > 0 {
+            pulses.space(gap);
+        }
+    }
+
+    /// Encode the bits of a value, with the most significant bit first unless `REVERSE` is set.
+    fn encode_bits(
+        &self,
+        pulses: &mut Pulses,
+        encoding: Encoding,
+        (value, bits): (u64, u32),
+        total: u32,
+        sent: &mut u32,
+    ) {
+        for index in 0..bits {
+            let shift = if self.has_flag("REVERSE") {
+                index
+            } else {
+                bits - 1 - index
+            };
+            let bit = (value >> shift) & 1 == 1;
+            let width = match total.checked_sub(*sent + 1) {
+                Some(position) if position < 64 && (self.rc6_mask >> position) & 1 == 1 => 2,
+                _ => 1,
+            };
+            *sent += 1;
+
+            match (encoding, bit) {
+                (Encoding::Space, true) => {
+                    pulses.pulse(self.one.0);
+                    pulses.space(self.one.1);
+                }
+                (Encoding::Space, false) => {
+                    pulses.pulse(self.zero.0);
+                    pulses.space(self.zero.1);
+                }
+                (Encoding::Shift, true) => {
+                    pulses.space(self.one.1);
+                    pulses.pulse(self.one.0);
+                }
+                (Encoding::Shift, false) => {
+                    pulses.pulse(self.zero.0);
+                    pulses.space(self.zero.1);
+                }
+                (Encoding::Rc6, true) => {
+                    pulses.pulse(width * self.one.0);
+                    pulses.space(width * self.one.1);
+                }
+                (Encoding::Rc6, false) => {
+                    pulses.space(width * self.zero.1);
+                    pulses.pulse(width * self.zero.0);
+                }
+            };
+        }
+    }
+}
+
+/// Parse a number of a LIRC config, either decimal or hexadecimal.
+fn parse_number(value: &str) -> Result<u64, Error> {
+    let parsed = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+
+    return parsed
+        .map_err(|e| Error::InvalidArgument(format!("Invalid number {:?}! {}", value, e)));
+}
+
+/// Ensure that a name is a single word, so that it can be read back from a config.
+fn check_name(name: &str) -> Result<(), Error> {
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '#') {
+        return Err(Error::InvalidArgument(format!(
+            "Name {:?} cannot be exported to a LIRC config, as it must be a single word",
+            name
+        )));
+    }
+
+    return Ok(());
+}
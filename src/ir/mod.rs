@@ -0,0 +1,15 @@
+//! Typed representations of the IR / RF codes sent and learned by remotes.
+//!
+//! Refer to the following for the code format -> <https://github.com/mjg59/python-broadlink/blob/9ff6b2d48e58f005765088cdf3dc5cc553cdb01a/protocol.md>
+
+mod code;
+mod decode;
+mod lirc;
+mod pronto;
+mod protocol;
+
+pub use code::*;
+pub use decode::*;
+pub use lirc::*;
+pub use pronto::*;
+pub use protocol::*;
@@ -0,0 +1,206 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    ir::{IrCode, TERMINATOR_TICKS, TICK_US},
+    Error,
+};
+
+/// The duration of a unit of the Pronto carrier frequency word, in µs.
+const PRONTO_CLOCK_US: f64 = 0.241246;
+
+/// The format word of raw, modulated Pronto codes.
+const PRONTO_RAW: u16 = 0x0000;
+
+/// An IR code in the Pronto Hex format (e.g. `0000 006D 0022 0002 ...`).
+///
+/// Durations are kept in µs, while the Pronto format counts them in cycles of the carrier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProntoCode {
+    /// The frequency of the carrier, in Hz
+    pub frequency: u32,
+
+    /// The durations sent once, in µs, starting with a pulse and alternating with spaces
+    pub once: Vec<u32>,
+
+    /// The durations repeated while a button is held, in µs, starting with a pulse and alternating with spaces
+    pub repeat: Vec<u32>,
+}
+
+impl ProntoCode {
+    /// Create a ProntoCode from an IR code, using the given carrier frequency in Hz.
+    ///
+    /// The durations of the code make up the sequence sent once.
+    pub fn from_ir_code(code: &IrCode, frequency: u32) -> ProntoCode {
+        let mut once = code.durations.clone();
+
+        // Pronto codes are made of pulse / space pairs, so end with the same gap as remotes
+        if once.len() % 2 == 1 {
+            once.push((f64::from(TERMINATOR_TICKS) * TICK_US).round() as u32);
+        }
+
+        return ProntoCode {
+            frequency,
+            once,
+            repeat: vec![],
+        };
+    }
+
+    /// Convert to an IR code, sending the repeat sequence `repeats` more times after the first.
+    ///
+    /// Note: Remotes always use their own carrier frequency, so the frequency is not kept.
+    pub fn to_ir_code(&self, repeats: u8) -> IrCode {
+        // Codes without a sequence sent once can make use of the repeat count of the remote
+        if self.once.is_empty() {
+            return IrCode {
+                repeat: repeats,
+                durations: self.repeat.clone(),
+            };
+        }
+
+        let mut durations = self.once.clone();
+        for _ in 0..=repeats {
+            durations.extend(&self.repeat);
+        }
+
+        return IrCode::new(durations);
+    }
+
+    /// Encode the ProntoCode into its hex representation, e.g. `0000 006D 0022 0002 ...`.
+    ///
+    /// Fails if the carrier frequency or a duration cannot be encoded, or if a sequence is not
+    /// made of pulse / space pairs.
+    pub fn to_pronto_hex(&self) -> Result<String, Error> {
+        return Ok(self
+            .to_words()?
+            .iter()
+            .map(|word| format!("{:04X}", word))
+            .collect::<Vec<String>>()
+            .join(" "));
+    }
+
+    /// Get the duration of a single cycle of the carrier, in µs.
+    fn cycle_us(frequency_word: u16) -> f64 {
+        return f64::from(frequency_word) * PRONTO_CLOCK_US;
+    }
+
+    /// Get the frequency word encoding the carrier frequency.
+    fn frequency_word(&self) -> Result<u16, Error> {
+        let word = (1_000_000.0 / (f64::from(self.frequency) * PRONTO_CLOCK_US)).round();
+        if !(1.0..=f64::from(u16::MAX)).contains(&word) {
+            return Err(Error::InvalidArgument(format!(
+                "Carrier frequency is out of range, got {}Hz",
+                self.frequency
+            )));
+        }
+
+        return Ok(word as u16);
+    }
+
+    /// Encode the ProntoCode into its hex words.
+    fn to_words(&self) -> Result<Vec<u16>, Error> {
+        for sequence in [&self.once, &self.repeat] {
+            if sequence.len() % 2 == 1 {
+                return Err(Error::InvalidArgument(
+                    "Sequences must be made of pulse / space pairs".into(),
+                ));
+            }
+        }
+
+        let frequency_word = self.frequency_word()?;
+        let pairs = |sequence: &Vec<u32>| {
+            u16::try_from(sequence.len() / 2)
+                .map_err(|e| Error::InvalidArgument(format!("Sequence is too long! {}", e)))
+        };
+
+        let mut words = vec![
+            PRONTO_RAW,
+            frequency_word,
+            pairs(&self.once)?,
+            pairs(&self.repeat)?,
+        ];
+        for &duration in self.once.iter().chain(self.repeat.iter()) {
+            let cycles = (f64::from(duration) / ProntoCode::cycle_us(frequency_word)).round();
+            if cycles > f64::from(u16::MAX) {
+                return Err(Error::InvalidArgument(format!(
+                    "Duration is too long for the carrier frequency, got {}µs",
+                    duration
+                )));
+            }
+
+            words.push(cycles as u16);
+        }
+
+        return Ok(words);
+    }
+}
+
+/// Formats the code as Pronto Hex, or describes why it cannot be encoded.
+/// Use [ProntoCode::to_pronto_hex] to handle codes which cannot be encoded.
+impl fmt::Display for ProntoCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_pronto_hex() {
+            Ok(hex) => write!(f, "{}", hex),
+            Err(e) => write!(f, "<invalid Pronto code: {}>", e),
+        }
+    }
+}
+
+impl FromStr for ProntoCode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s
+            .split_whitespace()
+            .map(|word| {
+                u16::from_str_radix(word, 16).map_err(|e| {
+                    Error::InvalidArgument(format!("Invalid Pronto word {:?}! {}", word, e))
+                })
+            })
+            .collect::<Result<Vec<u16>, Error>>()?;
+
+        if words.len() < 4 {
+            return Err(Error::InvalidArgument(format!(
+                "Pronto code is too short! Expected at least 4 words, got {}",
+                words.len()
+            )));
+        }
+
+        // Only raw codes hold the durations themselves
+        if words[0] != PRONTO_RAW {
+            return Err(Error::InvalidArgument(format!(
+                "Only raw Pronto codes (0000) are supported, got {:04X}",
+                words[0]
+            )));
+        }
+        if words[1] == 0 {
+            return Err(Error::InvalidArgument(
+                "Pronto code has no carrier frequency".into(),
+            ));
+        }
+
+        let once_length = usize::from(words[2]) * 2;
+        let repeat_length = usize::from(words[3]) * 2;
+        let durations = &words[4..];
+        if durations.len() != once_length + repeat_length {
+            return Err(Error::InvalidArgument(format!(
+                "Pronto code advertises {} durations, got {}",
+                once_length + repeat_length,
+                durations.len()
+            )));
+        }
+
+        let cycle_us = ProntoCode::cycle_us(words[1]);
+        let to_us = |cycles: &[u16]| {
+            cycles
+                .iter()
+                .map(|&c| (f64::from(c) * cycle_us).round() as u32)
+                .collect::<Vec<u32>>()
+        };
+
+        return Ok(ProntoCode {
+            frequency: (1_000_000.0 / cycle_us).round() as u32,
+            once: to_us(&durations[..once_length]),
+            repeat: to_us(&durations[once_length..]),
+        });
+    }
+}
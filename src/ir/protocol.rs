@@ -0,0 +1,371 @@
+use std::fmt;
+
+use crate::{ir::IrCode, Error};
+
+/// The timings of NEC codes, in µs.
+pub(crate) const NEC_UNIT: u32 = 560;
+pub(crate) const NEC_HEADER: (u32, u32) = (9000, 4500);
+pub(crate) const NEC_REPEAT: (u32, u32) = (9000, 2250);
+const NEC_PERIOD: u32 = 108_000;
+
+/// The timings of NECx and Samsung codes, in µs.
+pub(crate) const NECX_UNIT: u32 = 560;
+pub(crate) const NECX_HEADER: (u32, u32) = (4500, 4500);
+
+/// The timings of Sony SIRC codes, in µs.
+pub(crate) const SONY_UNIT: u32 = 600;
+pub(crate) const SONY_HEADER: (u32, u32) = (4 * SONY_UNIT, SONY_UNIT);
+const SONY_PERIOD: u32 = 45_000;
+
+/// The timings of RC5 codes, in µs.
+pub(crate) const RC5_UNIT: u32 = 889;
+const RC5_PERIOD: u32 = 113_778;
+
+/// The timings of RC6 codes, in µs.
+pub(crate) const RC6_UNIT: u32 = 444;
+pub(crate) const RC6_HEADER: (u32, u32) = (6 * RC6_UNIT, 2 * RC6_UNIT);
+const RC6_PERIOD: u32 = 107_000;
+
+/// The timings of Panasonic codes, in µs.
+pub(crate) const PANASONIC_UNIT: u32 = 432;
+pub(crate) const PANASONIC_HEADER: (u32, u32) = (8 * PANASONIC_UNIT, 4 * PANASONIC_UNIT);
+const PANASONIC_PERIOD: u32 = 130_000;
+
+/// The vendor ID sent at the start of Panasonic codes, as bytes.
+pub(crate) const PANASONIC_VENDOR: [u8; 2] = [0x02, 0x20];
+
+/// An IR protocol which codes can be generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrProtocol {
+    /// NEC, with an 8 bit (or 16 bit extended) address and an 8 bit command.
+    /// Held buttons send short repeat frames.
+    Nec,
+
+    /// NECx, with a 16 bit address and an 8 bit command. Held buttons resend the full frame.
+    NecExtended,
+
+    /// Philips RC5, with a 5 bit address and a 7 bit command (RC5x).
+    Rc5,
+
+    /// Philips RC6 mode 0, with an 8 bit address and an 8 bit command.
+    Rc6,
+
+    /// Sony SIRC, with a 7 bit command and a 5, 8 or 13 bit address.
+    Sony,
+
+    /// Samsung, with an 8 bit address and an 8 bit command.
+    Samsung,
+
+    /// Panasonic (Kaseikyo), with a 12 bit address and an 8 bit command.
+    Panasonic,
+}
+
+impl IrProtocol {
+    /// Get the supported amounts of data bits in a frame, the first being the default.
+    pub fn bit_counts(&self) -> &'static [u8] {
+        return match self {
+            IrProtocol::Nec | IrProtocol::NecExtended | IrProtocol::Samsung => &[32],
+            IrProtocol::Rc5 => &[14],
+            IrProtocol::Rc6 => &[16],
+            IrProtocol::Sony => &[12, 15, 20],
+            IrProtocol::Panasonic => &[48],
+        };
+    }
+
+    /// Get the largest address supported with the given amount of bits.
+    fn max_address(&self, bits: u8) -> u16 {
+        return match self {
+            IrProtocol::Nec | IrProtocol::NecExtended => 0xFFFF,
+            IrProtocol::Rc5 => 0x1F,
+            IrProtocol::Rc6 | IrProtocol::Samsung => 0xFF,
+            IrProtocol::Sony => (1 << (bits - 7)) - 1,
+            IrProtocol::Panasonic => 0xFFF,
+        };
+    }
+
+    /// Get the largest command supported.
+    fn max_command(&self) -> u16 {
+        return match self {
+            IrProtocol::Rc5 | IrProtocol::Sony => 0x7F,
+            _ => 0xFF,
+        };
+    }
+}
+
+impl fmt::Display for IrProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            IrProtocol::Nec => "NEC",
+            IrProtocol::NecExtended => "NECx",
+            IrProtocol::Rc5 => "RC5",
+            IrProtocol::Rc6 => "RC6",
+            IrProtocol::Sony => "Sony",
+            IrProtocol::Samsung => "Samsung",
+            IrProtocol::Panasonic => "Panasonic",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// An IR code described by its protocol parameters, instead of its durations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolCode {
+    /// The protocol of the code
+    pub protocol: IrProtocol,
+
+    /// The address of the device receiving the code
+    pub address: u16,
+
+    /// The command sent to the device
+    pub command: u16,
+
+    /// The amount of data bits in a frame, see [IrProtocol::bit_counts]
+    pub bits: u8,
+
+    /// How many repeat frames to send after the first, as when a button is held
+    pub repeats: u8,
+
+    /// The toggle bit of RC5 / RC6 codes, which should be flipped on every new button press
+    pub toggle: bool,
+}
+
+impl ProtocolCode {
+    /// Create a new ProtocolCode, using the default amount of bits of the protocol.
+    pub fn new(protocol: IrProtocol, address: u16, command: u16) -> ProtocolCode {
+        return ProtocolCode {
+            protocol,
+            address,
+            command,
+            bits: protocol.bit_counts()[0],
+            repeats: 0,
+            toggle: false,
+        };
+    }
+
+    /// Generate the IR code, as pulse / space durations.
+    pub fn to_ir_code(&self) -> Result<IrCode, Error> {
+        if !self.protocol.bit_counts().contains(&self.bits) {
+            return Err(Error::InvalidArgument(format!(
+                "Unsupported amount of bits for {:?}! Expected one of {:?}, got {}",
+                self.protocol,
+                self.protocol.bit_counts(),
+                self.bits
+            )));
+        }
+        if self.address > self.protocol.max_address(self.bits) {
+            return Err(Error::InvalidArgument(format!(
+                "Address is out of range for {:?}! Expected at most {:#X}, got {:#X}",
+                self.protocol,
+                self.protocol.max_address(self.bits),
+                self.address
+            )));
+        }
+        if self.command > self.protocol.max_command() {
+            return Err(Error::InvalidArgument(format!(
+                "Command is out of range for {:?}! Expected at most {:#X}, got {:#X}",
+                self.protocol,
+                self.protocol.max_command(),
+                self.command
+            )));
+        }
+
+        let address = u64::from(self.address);
+        let command = u64::from(self.command);
+        let mut pulses = Pulses::default();
+
+        match self.protocol {
+            IrProtocol::Nec => {
+                // Short addresses are followed by their inverse, extended ones are not
+                let address = if address > 0xFF {
+                    address
+                } else {
+                    address | ((!address & 0xFF) << 8)
+                };
+                pulses.pulse_distance(
+                    NEC_HEADER,
+                    address | (command << 16) | ((!command & 0xFF) << 24),
+                    32,
+                    NEC_UNIT,
+                    NEC_PERIOD,
+                );
+
+                // NEC is the only protocol with dedicated repeat frames, others are resent in full
+                for _ in 0..self.repeats {
+                    let start = pulses.elapsed();
+                    pulses.pulse(NEC_REPEAT.0);
+                    pulses.space(NEC_REPEAT.1);
+                    pulses.pulse(NEC_UNIT);
+                    pulses.gap(start, NEC_PERIOD);
+                }
+
+                return Ok(IrCode::new(pulses.durations));
+            }
+            IrProtocol::NecExtended => pulses.pulse_distance(
+                NECX_HEADER,
+                address | (command << 16) | ((!command & 0xFF) << 24),
+                32,
+                NECX_UNIT,
+                NEC_PERIOD,
+            ),
+            IrProtocol::Samsung => pulses.pulse_distance(
+                NECX_HEADER,
+                address | (address << 8) | (command << 16) | ((!command & 0xFF) << 24),
+                32,
+                NECX_UNIT,
+                NEC_PERIOD,
+            ),
+            IrProtocol::Sony => {
+                // Sony codes are pulse width encoded, with the command sent first
+                pulses.pulse(SONY_HEADER.0);
+                pulses.space(SONY_HEADER.1);
+
+                let data = command | (address << 7);
+                for bit in 0..self.bits {
+                    let width = if (data >> bit) & 1 == 1 { 2 } else { 1 };
+                    pulses.pulse(width * SONY_UNIT);
+                    pulses.space(SONY_UNIT);
+                }
+
+                pulses.gap(0, SONY_PERIOD);
+            }
+            IrProtocol::Rc5 => {
+                // The second start bit holds the inverted 7th bit of the command
+                let field = (!command >> 6) & 1;
+                let data = (1 << 13)
+                    | (field << 12)
+                    | (u64::from(self.toggle) << 11)
+                    | (address << 6)
+                    | (command & 0x3F);
+
+                // RC5 sends ones as a space followed by a pulse
+                for bit in (0..=13).rev() {
+                    if (data >> bit) & 1 == 1 {
+                        pulses.space(RC5_UNIT);
+                        pulses.pulse(RC5_UNIT);
+                    } else {
+                        pulses.pulse(RC5_UNIT);
+                        pulses.space(RC5_UNIT);
+                    }
+                }
+
+                pulses.gap(0, RC5_PERIOD);
+            }
+            IrProtocol::Rc6 => {
+                pulses.pulse(RC6_HEADER.0);
+                pulses.space(RC6_HEADER.1);
+
+                // The start bit, followed by mode 0
+                for bit in [true, false, false, false] {
+                    pulses.manchester(bit, RC6_UNIT);
+                }
+
+                // The toggle bit lasts twice as long as the others
+                pulses.manchester(self.toggle, 2 * RC6_UNIT);
+
+                let data = (address << 8) | command;
+                for bit in (0..self.bits).rev() {
+                    pulses.manchester((data >> bit) & 1 == 1, RC6_UNIT);
+                }
+
+                pulses.gap(0, RC6_PERIOD);
+            }
+            IrProtocol::Panasonic => {
+                // The vendor parity nibble is zero for Panasonic
+                let bytes = [
+                    PANASONIC_VENDOR[0],
+                    PANASONIC_VENDOR[1],
+                    ((self.address & 0x0F) << 4) as u8,
+                    (self.address >> 4) as u8,
+                    self.command as u8,
+                ];
+                let checksum = bytes[2] ^ bytes[3] ^ bytes[4];
+
+                let data = bytes
+                    .iter()
+                    .chain([checksum].iter())
+                    .rev()
+                    .fold(0u64, |data, &byte| (data << 8) | u64::from(byte));
+                pulses.pulse_distance(PANASONIC_HEADER, data, 48, PANASONIC_UNIT, PANASONIC_PERIOD);
+            }
+        };
+
+        // Full frames can make use of the repeat count of the remote
+        return Ok(IrCode {
+            repeat: self.repeats,
+            durations: pulses.durations,
+        });
+    }
+
+    /// Generate the IR code as bytes, as accepted by [crate::RemoteDevice::send_code].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        return self.to_ir_code()?.to_bytes();
+    }
+}
+
+/// A builder of pulse / space durations, merging consecutive durations of the same level.
+#[derive(Debug, Default)]
+pub(crate) struct Pulses {
+    /// The durations so far, starting with a pulse
+    pub(crate) durations: Vec<u32>,
+}
+
+impl Pulses {
+    /// Append a pulse.
+    pub(crate) fn pulse(&mut self, duration: u32) {
+        let is_pulse = self.durations.len() % 2 == 1;
+        match self.durations.last_mut() {
+            Some(last) if is_pulse => *last += duration,
+            _ => self.durations.push(duration),
+        };
+    }
+
+    /// Append a space. Leading spaces are dropped, as they cannot be told apart from idling.
+    pub(crate) fn space(&mut self, duration: u32) {
+        let is_pulse = self.durations.len() % 2 == 1;
+        match self.durations.last_mut() {
+            None => (),
+            Some(last) if !is_pulse => *last += duration,
+            _ => self.durations.push(duration),
+        };
+    }
+
+    /// Get the total duration so far.
+    pub(crate) fn elapsed(&self) -> u32 {
+        return self.durations.iter().sum();
+    }
+
+    /// Append a space lasting until the end of a frame started at `start`.
+    fn gap(&mut self, start: u32, period: u32) {
+        let elapsed = self.elapsed() - start;
+        self.space(period.saturating_sub(elapsed).max(period / 10));
+    }
+
+    /// Append a Manchester encoded bit, sending ones as a pulse followed by a space.
+    fn manchester(&mut self, bit: bool, unit: u32) {
+        if bit {
+            self.pulse(unit);
+            self.space(unit);
+        } else {
+            self.space(unit);
+            self.pulse(unit);
+        }
+    }
+
+    /// Append a pulse distance encoded frame, sending the least significant bit first.
+    fn pulse_distance(&mut self, header: (u32, u32), data: u64, bits: u8, unit: u32, period: u32) {
+        let start = self.elapsed();
+
+        self.pulse(header.0);
+        self.space(header.1);
+        for bit in 0..bits {
+            let width = if (data >> bit) & 1 == 1 { 3 } else { 1 };
+            self.pulse(unit);
+            self.space(width * unit);
+        }
+
+        // The stop bit ends the last space
+        self.pulse(unit);
+        self.gap(start, period);
+    }
+}
@@ -1,17 +1,52 @@
+// The codebase prefers explicit returns throughout.
+#![allow(clippy::needless_return)]
+
 // Include testing
 mod test;
 
+mod alarm;
+mod bulb;
 mod constants;
+mod curtain;
 mod device;
 mod device_info;
+mod device_model;
+mod device_session;
+mod discovery_iter;
+mod discovery_result;
+mod error;
+mod generic;
 mod hvac;
+mod plug;
+mod power_strip;
 mod remote;
+mod retry_policy;
+mod sensor;
+mod thermostat;
 
 // Manage exports
+pub mod ir;
 pub mod network;
 pub mod traits;
 
+#[cfg(any(test, feature = "simulator"))]
+pub mod simulator;
+
+pub use alarm::*;
+pub use bulb::*;
+pub use curtain::*;
 pub use device::*;
 pub use device_info::*;
+pub use device_model::*;
+pub use device_session::*;
+pub use discovery_iter::*;
+pub use discovery_result::*;
+pub use error::*;
+pub use generic::*;
 pub use hvac::*;
+pub use plug::*;
+pub use power_strip::*;
 pub use remote::*;
+pub use retry_policy::*;
+pub use sensor::*;
+pub use thermostat::*;
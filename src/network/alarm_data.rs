@@ -0,0 +1,189 @@
+use std::str::from_utf8;
+
+use packed_struct::prelude::{PackedStruct, PackedStructSlice, PrimitiveEnum_u8};
+
+use crate::{traits::CommandTrait, Error};
+
+/// The size of the header preceding the list of sensors in a response.
+const SENSORS_HEADER_SIZE: usize = 0x06;
+
+/// The size of a single sensor in a response.
+const SENSOR_SIZE: usize = 83;
+
+/// The type of command to send to an alarm hub.
+#[derive(PrimitiveEnum_u8, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AlarmDataCommand {
+    /// Ask the hub for its paired sensors and their status.
+    GetSensors = 0x06,
+}
+
+/// Enumerates the types of sensors which can be paired with an alarm hub.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlarmSensorType {
+    /// A door / window contact sensor.
+    DoorWindow,
+
+    /// A motion sensor.
+    Motion,
+
+    /// A key fob.
+    KeyFob,
+
+    /// A sensor type not known to this library, with its raw type.
+    Unknown(u8),
+}
+
+impl From<u8> for AlarmSensorType {
+    fn from(value: u8) -> Self {
+        return match value {
+            0x21 => AlarmSensorType::Motion,
+            0x31 => AlarmSensorType::DoorWindow,
+            0x91 => AlarmSensorType::KeyFob,
+            _ => AlarmSensorType::Unknown(value),
+        };
+    }
+}
+
+impl From<AlarmSensorType> for u8 {
+    fn from(sensor_type: AlarmSensorType) -> Self {
+        return match sensor_type {
+            AlarmSensorType::Motion => 0x21,
+            AlarmSensorType::DoorWindow => 0x31,
+            AlarmSensorType::KeyFob => 0x91,
+            AlarmSensorType::Unknown(value) => value,
+        };
+    }
+}
+
+/// A sensor paired with an alarm hub.
+#[derive(PackedStruct, Clone, Copy, Debug, PartialEq, Eq)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "83")]
+pub struct AlarmSensor {
+    /// Raw status flags of the sensor, as reported by the hub
+    #[packed_field(bytes = "0")]
+    pub status: u8,
+
+    /// The position of the sensor in the list of the hub
+    #[packed_field(bytes = "1")]
+    pub order: u8,
+
+    /// Raw type of the sensor
+    #[packed_field(bytes = "3")]
+    sensor_type: u8,
+
+    /// Name of the sensor, padded with zeros
+    #[packed_field(bytes = "4:25")]
+    name: [u8; 22],
+
+    /// Serial number of the sensor
+    #[packed_field(bytes = "26:29")]
+    pub serial: [u8; 4],
+}
+
+impl AlarmSensor {
+    /// Create a new AlarmSensor, e.g. to simulate a hub.
+    pub fn new(
+        serial: [u8; 4],
+        name: &str,
+        sensor_type: AlarmSensorType,
+    ) -> Result<AlarmSensor, Error> {
+        let mut sensor = AlarmSensor {
+            status: 0,
+            order: 0,
+            sensor_type: sensor_type.into(),
+            name: [0; 22],
+            serial,
+        };
+
+        // Ensure that the name fits
+        let name = name.as_bytes();
+        let target = sensor.name.get_mut(0..name.len()).ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "Sensor name is too long! Expected at most 22 bytes, got {}",
+                name.len()
+            ))
+        })?;
+        target.copy_from_slice(name);
+
+        return Ok(sensor);
+    }
+
+    /// Get the name of the sensor.
+    pub fn get_name(&self) -> Result<String, Error> {
+        let name = from_utf8(&self.name)
+            .map_err(|e| Error::InvalidResponse(format!("Could not decode sensor name! {}", e)))?;
+
+        return Ok(name.trim_end_matches('\0').into());
+    }
+
+    /// Get the type of the sensor.
+    pub fn get_sensor_type(&self) -> AlarmSensorType {
+        return self.sensor_type.into();
+    }
+
+    /// Get the serial number of the sensor, as a hex string.
+    pub fn get_serial(&self) -> String {
+        return self.serial.iter().map(|b| format!("{:02x}", b)).collect();
+    }
+}
+
+/// A message used to communicate with an alarm hub.
+#[derive(PackedStruct, Debug)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "0x10")]
+pub struct AlarmDataMessage {
+    /// Command flag for the message
+    #[packed_field(bytes = "0x00", ty = "enum")]
+    pub(crate) command: AlarmDataCommand,
+}
+
+impl AlarmDataMessage {
+    /// Create a new AlarmDataMessage.
+    pub fn new(command: AlarmDataCommand) -> AlarmDataMessage {
+        return AlarmDataMessage { command };
+    }
+
+    /// Pack the paired sensors, as sent back by a hub.
+    pub fn pack_sensors(sensors: &[AlarmSensor]) -> Result<Vec<u8>, Error> {
+        let count: u8 = sensors
+            .len()
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(format!("Too many sensors! {}", e)))?;
+
+        let mut result = vec![0; SENSORS_HEADER_SIZE];
+        result[0x00] = AlarmDataCommand::GetSensors as u8;
+        result[0x04] = count;
+        for sensor in sensors {
+            result.extend(sensor.pack()?);
+        }
+
+        return Ok(result);
+    }
+
+    /// Unpack the paired sensors from a decrypted response.
+    pub fn unpack_sensors(bytes: &[u8]) -> Result<Vec<AlarmSensor>, Error> {
+        if bytes.len() < SENSORS_HEADER_SIZE {
+            return Ok(vec![]);
+        }
+
+        // Trailing zeros of the last sensor are stripped when decrypting, so restore them here.
+        let mut bytes = bytes[SENSORS_HEADER_SIZE..].to_vec();
+        bytes.resize(bytes.len().div_ceil(SENSOR_SIZE) * SENSOR_SIZE, 0);
+
+        // Unused slots have no serial number
+        let mut sensors = vec![];
+        for chunk in bytes.chunks_exact(SENSOR_SIZE) {
+            let sensor = AlarmSensor::unpack_from_slice(chunk)?;
+            if sensor.serial != [0; 4] {
+                sensors.push(sensor);
+            }
+        }
+
+        return Ok(sensors);
+    }
+}
+
+impl CommandTrait for AlarmDataMessage {
+    fn packet_type() -> u16 {
+        return 0x006A;
+    }
+}
@@ -0,0 +1,170 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    network::{JsonDataCommand, JsonDataMessage},
+    Error,
+};
+
+/// Enumerates the color modes of a bulb.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(from = "u8", into = "u8")]
+pub enum BulbColorMode {
+    Rgb,
+    White,
+    Scene,
+
+    /// A color mode not known to this library, with its raw value.
+    Unknown(u8),
+}
+
+impl From<u8> for BulbColorMode {
+    fn from(value: u8) -> Self {
+        return match value {
+            0 => BulbColorMode::Rgb,
+            1 => BulbColorMode::White,
+            2 => BulbColorMode::Scene,
+            _ => BulbColorMode::Unknown(value),
+        };
+    }
+}
+
+impl From<BulbColorMode> for u8 {
+    fn from(mode: BulbColorMode) -> Self {
+        return match mode {
+            BulbColorMode::Rgb => 0,
+            BulbColorMode::White => 1,
+            BulbColorMode::Scene => 2,
+            BulbColorMode::Unknown(value) => value,
+        };
+    }
+}
+
+/// The state of a bulb, as exchanged with the device.
+///
+/// Every field is optional, so that only the fields which are set are changed when updating the state.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BulbState {
+    /// Power state (on/off)
+    #[serde(
+        rename = "pwr",
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_flag",
+        deserialize_with = "deserialize_flag"
+    )]
+    pub power: Option<bool>,
+
+    /// Brightness, in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<u8>,
+
+    /// Color temperature, in Kelvin (about 2700 to 6500)
+    #[serde(rename = "colortemp", default, skip_serializing_if = "Option::is_none")]
+    pub color_temp: Option<u16>,
+
+    /// Red component of the color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub red: Option<u8>,
+
+    /// Green component of the color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub green: Option<u8>,
+
+    /// Blue component of the color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blue: Option<u8>,
+
+    /// Hue of the color, in degrees
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hue: Option<u16>,
+
+    /// Saturation of the color, in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saturation: Option<u8>,
+
+    /// How long to take when changing the state, in ms
+    #[serde(
+        rename = "transitionduration",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub transition_duration: Option<u32>,
+
+    /// How long to stay on before turning off automatically, in minutes (0 to disable)
+    #[serde(
+        rename = "maxworktime",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_work_time: Option<u32>,
+
+    /// Color mode (RGB, white or scene)
+    #[serde(
+        rename = "bulb_colormode",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub color_mode: Option<BulbColorMode>,
+
+    /// The scenes stored on the bulb, as a JSON encoded string
+    #[serde(
+        rename = "bulb_scenes",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub scenes: Option<String>,
+
+    /// The active scene
+    #[serde(
+        rename = "bulb_scene",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub scene: Option<String>,
+
+    /// The index of the active scene
+    #[serde(
+        rename = "bulb_sceneidx",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub scene_index: Option<u32>,
+}
+
+impl BulbState {
+    /// Pack the state into a [JsonDataMessage], optionally prefixed with its length.
+    pub fn pack_message(
+        &self,
+        command: JsonDataCommand,
+        length_prefix: bool,
+    ) -> Result<Vec<u8>, Error> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| Error::InvalidArgument(format!("Could not encode bulb state! {}", e)))?;
+
+        return JsonDataMessage::new(command).pack_with_payload(&json, length_prefix);
+    }
+
+    /// Unpack the state from a [JsonDataMessage], optionally prefixed with its length.
+    pub fn unpack_message(bytes: &[u8], length_prefix: bool) -> Result<BulbState, Error> {
+        let json = JsonDataMessage::unpack_with_payload(bytes, length_prefix)?;
+
+        return serde_json::from_str(&json)
+            .map_err(|e| Error::InvalidResponse(format!("Could not parse bulb state! {}", e)));
+    }
+}
+
+/// Encodes an on / off flag as 1 / 0.
+fn serialize_flag<S>(value: &Option<bool>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    return value.map(u8::from).serialize(serializer);
+}
+
+/// Decodes an on / off flag from 1 / 0.
+fn deserialize_flag<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    return Ok(Option::<u8>::deserialize(deserializer)?.map(|value| value != 0));
+}
@@ -8,6 +8,7 @@ use crate::{
     constants,
     network::util::{checksum, reverse_mac},
     traits::CommandTrait,
+    Error,
 };
 
 /// Represents a block-based AES 128-bit encryption cipher.
@@ -28,7 +29,7 @@ pub struct CommandMessage {
     /// The type of packet being sent. This should be populated from the wrapped
     /// message type using the [CommandTrait].
     #[packed_field(bytes = "0x26:0x27")]
-    packet_type: u16,
+    pub(crate) packet_type: u16,
 
     /// The message count.
     #[packed_field(bytes = "0x28:0x29")]
@@ -46,6 +47,10 @@ pub struct CommandMessage {
     #[packed_field(bytes = "0x20:0x21")]
     checksum: u16,
 
+    /// The error code reported by the device. Always 0 when sending.
+    #[packed_field(bytes = "0x22:0x23")]
+    pub(crate) error_code: u16,
+
     /// The checksum of just the payload, before encryption
     #[packed_field(bytes = "0x34:0x35")]
     payload_checksum: u16,
@@ -72,7 +77,8 @@ impl CommandMessage {
             packet_type: T::packet_type(),
             count: count | 0x8000,
             mac_reversed: reverse_mac(mac),
-            id: id,
+            id,
+            error_code: 0,
             checksum: 0,         // This will be populated later.
             payload_checksum: 0, // This will be populated later.
         };
@@ -90,20 +96,17 @@ impl CommandMessage {
     }
 
     /// Pack the command message while appending the payload.
-    pub fn pack_with_payload(mut self, payload: &[u8], key: &[u8; 16]) -> Result<Vec<u8>, String> {
-        let cipher = AesCbc::new_from_slices(key, &constants::INITIAL_VECTOR)
-            .map_err(|e| format!("Could not construct cipher! {}", e))?;
+    pub fn pack_with_payload(mut self, payload: &[u8], key: &[u8; 16]) -> Result<Vec<u8>, Error> {
+        let cipher = AesCbc::new_from_slices(key, &constants::INITIAL_VECTOR)?;
 
         // Save the checksum of the payload before encrypting
-        self.payload_checksum = checksum(&payload);
+        self.payload_checksum = checksum(payload);
 
         // Encrypt the payload
-        let encrypted = cipher.encrypt_vec(&payload);
+        let encrypted = cipher.encrypt_vec(payload);
 
         // Pack the command with the payload appended
-        let packed = self
-            .pack()
-            .map_err(|e| format!("Could not pack command header! {}", e))?;
+        let packed = self.pack()?;
 
         let mut appended = packed.to_vec();
         appended.extend(&encrypted);
@@ -112,9 +115,7 @@ impl CommandMessage {
         self.checksum = checksum(&appended);
 
         // Construct the final message
-        let completely_packed = self
-            .pack()
-            .map_err(|e| format!("Could not pack completed command! {}", e))?;
+        let completely_packed = self.pack()?;
 
         let mut complete_command: Vec<u8> = completely_packed.to_vec();
         complete_command.extend(&encrypted);
@@ -123,18 +124,17 @@ impl CommandMessage {
     }
 
     /// Unpack the command message with the associated payload.
-    pub fn unpack_with_payload(mut bytes: Vec<u8>, key: &[u8; 16]) -> Result<Vec<u8>, String> {
+    pub fn unpack_with_payload(mut bytes: Vec<u8>, key: &[u8; 16]) -> Result<Vec<u8>, Error> {
         // Ensure that the data is correct
         if bytes.len() < 0x38 {
-            return Err(format!(
+            return Err(Error::InvalidResponse(format!(
                 "Command is too short! Expected 0x38 bytes, got {}",
                 bytes.len()
-            ));
+            )));
         }
 
         // Unpack the header
-        let command_header = CommandMessage::unpack_from_slice(&bytes[0..0x38])
-            .map_err(|e| format!("Could not unpack command from bytes! {}", e))?;
+        let command_header = CommandMessage::unpack_from_slice(&bytes[0..0x38])?;
 
         // Zero out the checksum from the header for verification
         // TODO: Is there a nicer way to do this?
@@ -144,27 +144,30 @@ impl CommandMessage {
         // Ensure that the checksums match
         let real_checksum = checksum(&bytes);
         if command_header.checksum != real_checksum {
-            return Err(format!(
-                "Command checksum does not match actual checksum! Expected {:#06X} got {:#06X}",
-                real_checksum, command_header.checksum,
-            ));
+            return Err(Error::ChecksumMismatch {
+                expected: command_header.checksum,
+                actual: real_checksum,
+            });
         }
 
-        // Decrypt the message
-        let cipher = AesCbc::new_from_slices(key, &constants::INITIAL_VECTOR)
-            .map_err(|e| format!("Could not construct cipher! {}", e))?;
+        // Bail out if the device reported an error
+        if command_header.error_code != 0 {
+            return Err(Error::Device {
+                code: command_header.error_code as i16,
+            });
+        }
 
-        let decrypted = cipher
-            .decrypt_vec(&bytes[0x38..])
-            .map_err(|e| format!("Could not decrypt command payload! {}", e))?;
+        // Decrypt the message
+        let cipher = AesCbc::new_from_slices(key, &constants::INITIAL_VECTOR)?;
+        let decrypted = cipher.decrypt_vec(&bytes[0x38..])?;
 
         // Ensure that the payload checksums match
         let real_checksum = checksum(&decrypted);
         if command_header.payload_checksum != real_checksum {
-            return Err(format!(
-                "Payload checksum does not match actual checksum! Expected {:#06X} got {:#06X}",
-                real_checksum, command_header.payload_checksum,
-            ));
+            return Err(Error::ChecksumMismatch {
+                expected: command_header.payload_checksum,
+                actual: real_checksum,
+            });
         }
 
         return Ok(decrypted);
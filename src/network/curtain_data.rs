@@ -0,0 +1,87 @@
+use packed_struct::prelude::{PackedStruct, PrimitiveEnum_u8};
+
+use crate::{network::util::unpack_zero_padded, traits::CommandTrait, Error};
+
+/// The type of command to send to a curtain motor.
+#[derive(PrimitiveEnum_u8, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CurtainCommand {
+    /// Start opening the curtain.
+    Open = 0x01,
+
+    /// Start closing the curtain.
+    Close = 0x02,
+
+    /// Stop moving the curtain.
+    Stop = 0x03,
+
+    /// Ask the curtain motor for its position.
+    GetPosition = 0x06,
+}
+
+/// A message used to control a curtain motor.
+#[derive(PackedStruct, Debug)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "0x10")]
+pub struct CurtainDataMessage {
+    /// Constant magic value
+    #[packed_field(bytes = "0x00")]
+    magic1: u8,
+
+    /// Constant magic value
+    #[packed_field(bytes = "0x02")]
+    magic2: u8,
+
+    /// Command flag for the message
+    #[packed_field(bytes = "0x03", ty = "enum")]
+    pub(crate) command: CurtainCommand,
+
+    /// Attribute of the command. Holds the position, in percent, in responses.
+    #[packed_field(bytes = "0x04")]
+    pub(crate) attribute: u8,
+
+    /// Constant magic value
+    #[packed_field(bytes = "0x09:0x0A")]
+    magic3: [u8; 2],
+}
+
+impl CurtainDataMessage {
+    /// Create a new CurtainDataMessage.
+    pub fn new(command: CurtainCommand) -> CurtainDataMessage {
+        // Asking for the position requires a specific attribute
+        let attribute = match command {
+            CurtainCommand::GetPosition => 0x5D,
+            _ => 0x00,
+        };
+
+        return CurtainDataMessage {
+            magic1: 0x09,
+            magic2: 0xBB,
+            command,
+            attribute,
+            magic3: [0xFA, 0x44],
+        };
+    }
+
+    /// Unpack a CurtainDataMessage from a decrypted request or response.
+    pub fn unpack_response(bytes: &[u8]) -> Result<CurtainDataMessage, Error> {
+        return unpack_zero_padded(bytes);
+    }
+
+    /// Unpack the position of the curtain, in percent, from a decrypted response.
+    pub fn unpack_position(bytes: &[u8]) -> Result<u8, Error> {
+        if bytes.len() < 0x04 {
+            return Err(Error::InvalidResponse(format!(
+                "Curtain response is too short! Expected at least 0x04 bytes, got {}",
+                bytes.len()
+            )));
+        }
+
+        // Any trailing zeros are stripped when decrypting, so the position may be missing.
+        return Ok(bytes.get(0x04).copied().unwrap_or(0));
+    }
+}
+
+impl CommandTrait for CurtainDataMessage {
+    fn packet_type() -> u16 {
+        return 0x006A;
+    }
+}
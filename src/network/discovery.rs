@@ -3,7 +3,7 @@ use std::net::IpAddr;
 use chrono::prelude::{DateTime, Datelike, Local, Timelike};
 use packed_struct::prelude::PackedStruct;
 
-use crate::network::util::checksum;
+use crate::{network::util::checksum, Error};
 
 /// A message used to discover all broadlink devices on the network.
 #[derive(PackedStruct, Debug)]
@@ -85,17 +85,18 @@ impl DiscoveryMessage {
         addr: IpAddr,
         port: u16,
         time: Option<DateTime<Local>>,
-    ) -> Result<DiscoveryMessage, String> {
+    ) -> Result<DiscoveryMessage, Error> {
         // Get the time
-        let time = match time {
-            Some(t) => t,
-            None => Local::now(),
-        };
+        let time = time.unwrap_or_else(Local::now);
 
         // Get the ip addr. Note: The device only supports IPv4
         let selected_ip = match addr {
             IpAddr::V4(ipv4) => ipv4,
-            _ => return Err("Could not construct DiscoveryMessage! IP address is not IPv4".into()),
+            _ => {
+                return Err(Error::InvalidArgument(
+                    "Could not construct DiscoveryMessage! IP address is not IPv4".into(),
+                ))
+            }
         };
 
         // Reverse the IP octet
@@ -105,14 +106,10 @@ impl DiscoveryMessage {
 
         // Chrono returns the information in u32, so we need to convert them here.
         // These conversions should, in theory, not fail. But we check nonetheless.
-        let mut msg = construct_message(reversed_ip, port, time)
-            .map_err(|e| format!("Could not construct DiscoveryMessage! {}", e))?;
+        let mut msg = construct_message(reversed_ip, port, time)?;
 
         // Calculate the checksum
-        msg.checksum = checksum(
-            &msg.pack()
-                .map_err(|e| format!("Could not pack DiscoveryMessage! {}", e))?,
-        );
+        msg.checksum = checksum(&msg.pack()?);
 
         return Ok(msg);
     }
@@ -122,35 +119,37 @@ fn construct_message(
     reversed_ip: [u8; 4],
     port: u16,
     time: DateTime<Local>,
-) -> Result<DiscoveryMessage, String> {
+) -> Result<DiscoveryMessage, Error> {
     Ok(DiscoveryMessage {
         gmt_offset: time.offset().local_minus_utc(),
         year: time
             .year()
             .try_into()
-            .map_err(|e| format!("Year is out of range. {}", e))?,
+            .map_err(|e| Error::InvalidArgument(format!("Year is out of range. {}", e)))?,
         minute: time
             .minute()
             .try_into()
-            .map_err(|e| format!("Minutes are out of range. {}", e))?,
+            .map_err(|e| Error::InvalidArgument(format!("Minutes are out of range. {}", e)))?,
         hour: time
             .hour()
             .try_into()
-            .map_err(|e| format!("Hour is out of range. {}", e))?,
+            .map_err(|e| Error::InvalidArgument(format!("Hour is out of range. {}", e)))?,
         year_without_century: (time.year() % 100) as u8,
         day_of_the_week: time
             .weekday()
             .number_from_monday()
             .try_into()
-            .map_err(|e| format!("Day of the week is out of range. {}", e))?,
+            .map_err(|e| {
+                Error::InvalidArgument(format!("Day of the week is out of range. {}", e))
+            })?,
         day_of_the_month: time
             .day()
             .try_into()
-            .map_err(|e| format!("Day is out of range. {}", e))?,
+            .map_err(|e| Error::InvalidArgument(format!("Day is out of range. {}", e)))?,
         month: time
             .month()
             .try_into()
-            .map_err(|e| format!("Month is out of range. {}", e))?,
+            .map_err(|e| Error::InvalidArgument(format!("Month is out of range. {}", e)))?,
         local_ip_reversed: reversed_ip,
         local_port: port,
 
@@ -0,0 +1,95 @@
+use std::{fmt, net::Ipv4Addr, str::FromStr};
+
+use crate::Error;
+
+/// Where to look for devices when discovering them. Refer to [crate::Device::discover].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiscoveryTarget {
+    /// Broadcast on the subnet of every local, non-loopback interface.
+    AllInterfaces,
+
+    /// Broadcast on the subnet of the interface with the specified name (e.g. eth0).
+    Interface(String),
+
+    /// Send directly to a single address.
+    Address(Ipv4Addr),
+
+    /// Send directly to every host of a range, given as an address and its prefix length.
+    Range(Ipv4Addr, u8),
+}
+
+impl DiscoveryTarget {
+    /// The shortest prefix length accepted for a range, to avoid flooding the network.
+    pub const MIN_PREFIX_LENGTH: u8 = 16;
+
+    /// Get every address that this target sends directly to.
+    ///
+    /// Interfaces are broadcast to instead, so they have no addresses.
+    pub fn addresses(&self) -> Result<Vec<Ipv4Addr>, Error> {
+        return match self {
+            DiscoveryTarget::AllInterfaces | DiscoveryTarget::Interface(_) => Ok(vec![]),
+            DiscoveryTarget::Address(addr) => Ok(vec![*addr]),
+            DiscoveryTarget::Range(addr, prefix) => {
+                if *prefix < DiscoveryTarget::MIN_PREFIX_LENGTH || *prefix > 32 {
+                    return Err(Error::InvalidArgument(format!(
+                        "Range prefix must be between {} and 32! Got {}",
+                        DiscoveryTarget::MIN_PREFIX_LENGTH,
+                        prefix
+                    )));
+                }
+
+                let mask = u32::MAX.checked_shl(32 - u32::from(*prefix)).unwrap_or(0);
+                let network = u32::from(*addr) & mask;
+                let broadcast = network | !mask;
+
+                // Skip the network and broadcast addresses, unless the range is too small to have them
+                let hosts = if *prefix >= 31 {
+                    network..=broadcast
+                } else {
+                    network + 1..=broadcast - 1
+                };
+
+                Ok(hosts.map(Ipv4Addr::from).collect())
+            }
+        };
+    }
+}
+
+/// Parses a target from either `*` (all interfaces), an address, a range in CIDR notation
+/// (e.g. 10.0.0.0/24) or an interface name.
+impl FromStr for DiscoveryTarget {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" {
+            return Ok(DiscoveryTarget::AllInterfaces);
+        }
+
+        if let Some((addr, prefix)) = s.split_once('/') {
+            let addr = addr
+                .parse::<Ipv4Addr>()
+                .map_err(|e| Error::InvalidArgument(format!("Invalid range address! {}", e)))?;
+            let prefix = prefix
+                .parse::<u8>()
+                .map_err(|e| Error::InvalidArgument(format!("Invalid range prefix! {}", e)))?;
+
+            return Ok(DiscoveryTarget::Range(addr, prefix));
+        }
+
+        return Ok(match s.parse::<Ipv4Addr>() {
+            Ok(addr) => DiscoveryTarget::Address(addr),
+            Err(_) => DiscoveryTarget::Interface(s.into()),
+        });
+    }
+}
+
+impl fmt::Display for DiscoveryTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscoveryTarget::AllInterfaces => write!(f, "*"),
+            DiscoveryTarget::Interface(name) => write!(f, "{}", name),
+            DiscoveryTarget::Address(addr) => write!(f, "{}", addr),
+            DiscoveryTarget::Range(addr, prefix) => write!(f, "{}/{}", addr, prefix),
+        }
+    }
+}
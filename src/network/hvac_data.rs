@@ -2,7 +2,7 @@ use packed_struct::prelude::{
     packed_bits, Integer, PackedStruct, PackedStructSlice, PrimitiveEnum_u8,
 };
 
-use crate::{network::util::compute_generic_checksum, traits::CommandTrait};
+use crate::{network::util::compute_generic_checksum, traits::CommandTrait, Error};
 
 /// The type of command to send to the unit.
 #[derive(PrimitiveEnum_u8, Debug, Copy, Clone)]
@@ -131,14 +131,11 @@ pub struct AirCondState {
 }
 
 impl AirCondState {
-    pub fn prepare_and_pack(&mut self) -> Result<Vec<u8>, String> {
+    pub fn prepare_and_pack(&mut self) -> Result<Vec<u8>, Error> {
         // set magic values before sending
         self.magic1 = 0x0f.into();
 
-        Ok(self
-            .pack()
-            .map_err(|e| format!("Could not pack message! {}", e))?
-            .to_vec())
+        Ok(self.pack()?.to_vec())
     }
 
     /// Calculate final temperature value from internal partial fields.
@@ -147,9 +144,11 @@ impl AirCondState {
     }
 
     /// Set target temperature from input.
-    pub fn set_target_temp(&mut self, input: f32) -> Result<(), String> {
-        if input < 16.0 || input > 32.0 {
-            return Err("Target temperature is out of range (16-32)".into());
+    pub fn set_target_temp(&mut self, input: f32) -> Result<(), Error> {
+        if !(16.0..=32.0).contains(&input) {
+            return Err(Error::InvalidArgument(
+                "Target temperature is out of range (16-32)".into(),
+            ));
         }
         // TODO: some units also have a 0.5 degree resolution, so in this
         // case the formula would be:
@@ -200,7 +199,7 @@ pub struct HvacDataMessage {
 
     /// Command flag for the message
     #[packed_field(bytes = "0x0a:0x0b")]
-    command: u16,
+    pub(crate) command: u16,
 }
 
 impl HvacDataMessage {
@@ -217,22 +216,18 @@ impl HvacDataMessage {
     }
 
     /// Pack the HvacDataMessage with an associated payload.
-    pub fn pack_with_payload(mut self, payload: &[u8]) -> Result<Vec<u8>, String> {
+    pub fn pack_with_payload(mut self, payload: &[u8]) -> Result<Vec<u8>, Error> {
         // Calculate tyhe length of the payload
         self.data_length += <usize as TryInto<u16>>::try_into(payload.len())
-            .map_err(|e| format!("Payload is too long! {}", e))?;
+            .map_err(|e| Error::InvalidArgument(format!("Payload is too long! {}", e)))?;
 
         // Add 10 bytes for the header
-        self.payload_length = self
-            .data_length
-            .checked_add(10u16)
-            .ok_or_else(|| "Could not add the start buffer! Payload is too long")?;
+        self.payload_length = self.data_length.checked_add(10u16).ok_or_else(|| {
+            Error::InvalidArgument("Could not add the start buffer! Payload is too long".into())
+        })?;
 
         // Append the payload to the header
-        let mut result = self
-            .pack()
-            .map_err(|e| format!("Could not pack message! {}", e))?
-            .to_vec();
+        let mut result = self.pack()?.to_vec();
         result.extend(payload);
 
         // Compute and add the final payload checksum
@@ -243,19 +238,33 @@ impl HvacDataMessage {
     }
 
     /// Unpack a HvacDataMessage and return the associated payload.
-    pub fn unpack_with_payload(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    pub fn unpack_with_payload(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        // Ensure that we at least have a header
+        if bytes.len() < 12 {
+            return Err(Error::InvalidResponse(format!(
+                "HVAC response is too short! Expected at least 12 bytes, got {}",
+                bytes.len()
+            )));
+        }
+
         // Unpack the header
-        let command_header = HvacDataMessage::unpack_from_slice(&bytes[0..12])
-            .map_err(|e| format!("Could not unpack command from bytes! {}", e))?;
+        let command_header = HvacDataMessage::unpack_from_slice(&bytes[0..12])?;
+
+        // Trailing zeros (e.g. in the checksum) are stripped when decrypting, so restore them here.
+        let mut bytes = bytes.to_vec();
+        let expected_size = usize::from(command_header.payload_length) + 2;
+        if bytes.len() < expected_size {
+            bytes.resize(expected_size, 0);
+        }
 
         // Check total payload length:
         // get real size and substract 2 bytes length field for correct comparision
         let real_size: u16 = (bytes.len() as u16) - 2;
         if real_size != command_header.payload_length {
-            return Err(format!(
-                "Command checksum does not match actual checksum! Expected {:#06X} got {:#06X}",
+            return Err(Error::InvalidResponse(format!(
+                "Payload length does not match actual length! Expected {:#06X} got {:#06X}",
                 command_header.payload_length, real_size,
-            ));
+            )));
         }
 
         // Ensure that the checksums match
@@ -263,10 +272,10 @@ impl HvacDataMessage {
         let data_crc = u16::from_le_bytes([bytes[crc_offset], bytes[crc_offset + 1]]);
         let real_checksum = compute_generic_checksum(&bytes[0x02..crc_offset]);
         if data_crc != real_checksum {
-            return Err(format!(
-                "Data checksum does not match actual checksum! Expected {:#06X} got {:#06X}",
-                data_crc, real_checksum,
-            ));
+            return Err(Error::ChecksumMismatch {
+                expected: data_crc,
+                actual: real_checksum,
+            });
         }
 
         // Extract the data:
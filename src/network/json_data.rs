@@ -0,0 +1,122 @@
+use packed_struct::prelude::{PackedStruct, PackedStructSlice, PrimitiveEnum_u8};
+
+use crate::{network::util::checksum, traits::CommandTrait, Error};
+
+/// The type of command to send with a JSON encoded state.
+#[derive(PrimitiveEnum_u8, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JsonDataCommand {
+    /// Ask the device for its state.
+    GetState = 0x01,
+
+    /// Update the state of the device.
+    SetState = 0x02,
+}
+
+/// A message used to exchange JSON encoded state with SP4 plugs and LB1 / LB2 bulbs.
+///
+/// Newer SP4 plugs (SP4B) and LB1 bulbs prefix this message with its total length, as a u16.
+#[derive(PackedStruct, Debug)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "0x0C")]
+pub struct JsonDataMessage {
+    /// Constant magic value
+    #[packed_field(bytes = "0x00:0x01")]
+    magic1: u16,
+
+    /// Constant magic value
+    #[packed_field(bytes = "0x02:0x03")]
+    magic2: u16,
+
+    /// The checksum of the message, including the JSON payload
+    #[packed_field(bytes = "0x04:0x05")]
+    checksum: u16,
+
+    /// Command flag for the message
+    #[packed_field(bytes = "0x06", ty = "enum")]
+    pub(crate) command: JsonDataCommand,
+
+    /// Constant message type
+    #[packed_field(bytes = "0x07")]
+    message_type: u8,
+
+    /// Length of the JSON payload
+    #[packed_field(bytes = "0x08:0x0B")]
+    payload_length: u32,
+}
+
+impl JsonDataMessage {
+    /// Create a new JsonDataMessage.
+    pub fn new(command: JsonDataCommand) -> JsonDataMessage {
+        return JsonDataMessage {
+            magic1: 0xA5A5,
+            magic2: 0x5A5A,
+            checksum: 0, // This will be populated later.
+            command,
+            message_type: 0x0B,
+            payload_length: 0,
+        };
+    }
+
+    /// Pack the JsonDataMessage with an associated JSON payload, optionally prefixed with its length.
+    pub fn pack_with_payload(mut self, json: &str, length_prefix: bool) -> Result<Vec<u8>, Error> {
+        self.payload_length = json
+            .len()
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(format!("Payload is too long! {}", e)))?;
+
+        let mut result = self.pack()?.to_vec();
+        result.extend(json.as_bytes());
+
+        // Save the checksum of the complete message
+        let checksum = checksum(&result).to_le_bytes();
+        result[0x04..0x06].copy_from_slice(&checksum);
+
+        if length_prefix {
+            let length: u16 = result
+                .len()
+                .try_into()
+                .map_err(|e| Error::InvalidArgument(format!("Payload is too long! {}", e)))?;
+
+            result.splice(0..0, length.to_le_bytes());
+        }
+
+        return Ok(result);
+    }
+
+    /// Unpack a JsonDataMessage and return the associated JSON payload.
+    pub fn unpack_with_payload(bytes: &[u8], length_prefix: bool) -> Result<String, Error> {
+        let bytes = if length_prefix {
+            bytes.get(0x02..).unwrap_or_default()
+        } else {
+            bytes
+        };
+
+        if bytes.len() < 0x0C {
+            return Err(Error::InvalidResponse(format!(
+                "JSON message is too short! Expected at least 0x0C bytes, got {}",
+                bytes.len()
+            )));
+        }
+
+        let info = JsonDataMessage::unpack_from_slice(&bytes[0x00..0x0C])?;
+        let payload = usize::try_from(info.payload_length)
+            .ok()
+            .and_then(|length| bytes.get(0x0C..0x0C + length))
+            .ok_or_else(|| {
+                Error::InvalidResponse(format!(
+                    "JSON message is shorter than its advertised length of {}",
+                    info.payload_length
+                ))
+            })?;
+
+        let json = String::from_utf8(payload.to_vec())
+            .map_err(|e| Error::InvalidResponse(format!("Could not decode JSON payload! {}", e)))?;
+
+        return Ok(json);
+    }
+}
+
+impl CommandTrait for JsonDataMessage {
+    fn packet_type() -> u16 {
+        return 0x006A;
+    }
+}
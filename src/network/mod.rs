@@ -2,18 +2,42 @@
 //!
 //! Refer to the following for protocol information -> <https://github.com/mjg59/python-broadlink/blob/9ff6b2d48e58f005765088cdf3dc5cc553cdb01a/protocol.md>
 
+mod alarm_data;
 mod authentication;
+mod bulb_data;
 mod command;
+mod curtain_data;
 mod discovery;
+mod discovery_target;
 mod hvac_data;
+mod json_data;
+mod network_config;
+mod network_interface;
+mod plug_data;
+mod power_strip_data;
 mod remote_data;
+mod sensor_data;
+mod thermostat_data;
+mod udp_transport;
 mod wireless_connection;
 
 pub mod util;
 
+pub use alarm_data::*;
 pub use authentication::*;
+pub use bulb_data::*;
 pub use command::*;
+pub use curtain_data::*;
 pub use discovery::*;
+pub use discovery_target::*;
 pub use hvac_data::*;
+pub use json_data::*;
+pub use network_config::*;
+pub use network_interface::*;
+pub use plug_data::*;
+pub use power_strip_data::*;
 pub use remote_data::*;
+pub use sensor_data::*;
+pub use thermostat_data::*;
+pub use udp_transport::*;
 pub use wireless_connection::*;
@@ -0,0 +1,37 @@
+use std::{net::Ipv4Addr, time::Duration};
+
+use crate::network::util::DEVICE_PORT;
+
+/// The port that responses to discovery messages are received on, unless configured otherwise.
+pub const DISCOVERY_PORT: u16 = 42424;
+
+/// Settings used when communicating with broadlink devices over UDP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkConfig {
+    /// The local address to bind sockets to.
+    pub bind_addr: Ipv4Addr,
+
+    /// The local port that discovery responses are received on. Use 0 to pick any free port.
+    pub discovery_port: u16,
+
+    /// The port that devices listen on.
+    pub device_port: u16,
+
+    /// How long to wait for devices to answer a discovery broadcast.
+    pub discovery_timeout: Duration,
+
+    /// How long to wait for a device to answer a single command.
+    pub command_timeout: Duration,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        return NetworkConfig {
+            bind_addr: Ipv4Addr::UNSPECIFIED,
+            discovery_port: DISCOVERY_PORT,
+            device_port: DEVICE_PORT,
+            discovery_timeout: Duration::from_secs(10),
+            command_timeout: Duration::from_secs(10),
+        };
+    }
+}
@@ -0,0 +1,64 @@
+use std::{fmt, net::Ipv4Addr};
+
+use crate::Error;
+
+/// A local IPv4 network interface, which can be used for discovering devices.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NetworkInterface {
+    /// The name of the interface (e.g. eth0).
+    pub name: String,
+
+    /// The local address of the interface.
+    pub address: Ipv4Addr,
+
+    /// The netmask of the subnet that the interface is connected to.
+    pub netmask: Ipv4Addr,
+}
+
+impl NetworkInterface {
+    /// List the IPv4 addresses of every local network interface, including loopback.
+    pub fn list() -> Result<Vec<NetworkInterface>, Error> {
+        let interfaces = get_if_addrs::get_if_addrs()?
+            .into_iter()
+            .filter_map(|x| match x.addr {
+                get_if_addrs::IfAddr::V4(addr) => Some(NetworkInterface {
+                    name: x.name,
+                    address: addr.ip,
+                    netmask: addr.netmask,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        return Ok(interfaces);
+    }
+
+    /// Get the subnet-directed broadcast address of this interface.
+    pub fn broadcast(&self) -> Ipv4Addr {
+        return Ipv4Addr::from(u32::from(self.address) | !u32::from(self.netmask));
+    }
+
+    /// Returns true if the specified address is within the subnet of this interface.
+    pub fn contains(&self, addr: Ipv4Addr) -> bool {
+        let mask = u32::from(self.netmask);
+
+        return u32::from(self.address) & mask == u32::from(addr) & mask;
+    }
+
+    /// Returns true if this is a loopback interface.
+    pub fn is_loopback(&self) -> bool {
+        return self.address.is_loopback();
+    }
+}
+
+impl fmt::Display for NetworkInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}/{})",
+            self.name,
+            self.address,
+            u32::from(self.netmask).count_ones()
+        )
+    }
+}
@@ -0,0 +1,135 @@
+use packed_struct::prelude::{packed_bits, Integer, PackedStruct, PrimitiveEnum_u8};
+
+use crate::{network::util::unpack_zero_padded, traits::CommandTrait, Error};
+
+/// The type of command to send to an SP2 / SP3 plug.
+#[derive(PrimitiveEnum_u8, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlugDataCommand {
+    /// Ask the plug for its power and nightlight state.
+    GetState = 0x01,
+
+    /// Set the power and nightlight state of the plug.
+    SetState = 0x02,
+
+    /// Ask the plug for its current power usage. Only supported by SP2S plugs.
+    GetEnergy = 0x04,
+}
+
+/// A message used to control SP2 / SP3 plugs.
+#[derive(PackedStruct, Debug)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "0x10")]
+pub struct PlugDataMessage {
+    /// Command flag for the message
+    #[packed_field(bytes = "0x00", ty = "enum")]
+    pub(crate) command: PlugDataCommand,
+
+    /// The state flags (bit 0 is the power, bit 1 the nightlight), or the power usage in mW
+    /// when answering a [PlugDataCommand::GetEnergy].
+    #[packed_field(bytes = "0x04:0x06")]
+    pub(crate) value: Integer<u32, packed_bits::Bits<24>>,
+}
+
+impl PlugDataMessage {
+    /// Create a new PlugDataMessage.
+    pub fn new(command: PlugDataCommand, value: u32) -> PlugDataMessage {
+        return PlugDataMessage {
+            command,
+            value: value.into(),
+        };
+    }
+
+    /// Get the value sent with this message.
+    pub fn value(&self) -> u32 {
+        return self.value.into();
+    }
+
+    /// Unpack a PlugDataMessage from a decrypted response.
+    pub fn unpack_response(bytes: &[u8]) -> Result<PlugDataMessage, Error> {
+        return unpack_zero_padded(bytes);
+    }
+}
+
+impl CommandTrait for PlugDataMessage {
+    fn packet_type() -> u16 {
+        return 0x006A;
+    }
+}
+
+/// A message used to switch SP1 plugs, which use their own packet type.
+#[derive(PackedStruct, Debug)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "0x04")]
+pub struct PlugPowerMessage {
+    /// Whether to turn the plug on
+    #[packed_field(bytes = "0x00")]
+    pub power: u8,
+}
+
+impl PlugPowerMessage {
+    /// Create a new PlugPowerMessage.
+    pub fn new(power: bool) -> PlugPowerMessage {
+        return PlugPowerMessage {
+            power: power.into(),
+        };
+    }
+}
+
+impl CommandTrait for PlugPowerMessage {
+    fn packet_type() -> u16 {
+        return 0x0066;
+    }
+}
+
+/// A message used to read the power usage of SP3S plugs.
+#[derive(PackedStruct, Debug)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "0x0A")]
+pub struct PlugEnergyMessage {
+    /// Constant magic value
+    #[packed_field(bytes = "0x00:0x09")]
+    magic: [u8; 0x0A],
+}
+
+impl PlugEnergyMessage {
+    /// Create a new PlugEnergyMessage.
+    pub fn new() -> PlugEnergyMessage {
+        return PlugEnergyMessage {
+            magic: [0x08, 0x00, 0xFE, 0x01, 0x05, 0x01, 0x00, 0x00, 0x00, 0x2D],
+        };
+    }
+
+    /// Unpack the power usage, in W, from a decrypted response.
+    ///
+    /// The usage is sent as 3 BCD encoded bytes, in hundredths of a W, with the most
+    /// significant byte last.
+    pub fn unpack_energy(bytes: &[u8]) -> Result<f32, Error> {
+        // Any trailing zeros are stripped when decrypting, so restore them here.
+        let mut bytes = bytes.to_vec();
+        bytes.resize(0x08, 0);
+
+        let mut energy = 0u32;
+        for &byte in bytes[0x05..0x08].iter().rev() {
+            let (high, low) = (byte >> 4, byte & 0x0F);
+            if high > 9 || low > 9 {
+                return Err(Error::InvalidResponse(format!(
+                    "Power usage is not a valid BCD value! Got {:#04X}",
+                    byte
+                )));
+            }
+
+            energy = energy * 100 + u32::from(high) * 10 + u32::from(low);
+        }
+
+        return Ok(energy as f32 / 100.0);
+    }
+}
+
+impl Default for PlugEnergyMessage {
+    fn default() -> Self {
+        return PlugEnergyMessage::new();
+    }
+}
+
+impl CommandTrait for PlugEnergyMessage {
+    fn packet_type() -> u16 {
+        return 0x006A;
+    }
+}
@@ -0,0 +1,100 @@
+use packed_struct::prelude::{PackedStruct, PrimitiveEnum_u8};
+
+use crate::{network::util::checksum, traits::CommandTrait, Error};
+
+/// The type of command to send to a power strip.
+#[derive(PrimitiveEnum_u8, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PowerStripCommand {
+    /// Ask the power strip for the state of all of its sockets.
+    GetState = 0x01,
+
+    /// Switch some of the sockets of the power strip.
+    SetState = 0x02,
+}
+
+/// A message used to control MP1 power strips.
+#[derive(PackedStruct, Debug)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "0x10")]
+pub struct PowerStripDataMessage {
+    /// Length of the message, excluding this field
+    #[packed_field(bytes = "0x00:0x01")]
+    length: u16,
+
+    /// Constant magic value
+    #[packed_field(bytes = "0x02:0x05")]
+    magic: [u8; 4],
+
+    /// The checksum of the message, excluding the length
+    #[packed_field(bytes = "0x06:0x07")]
+    checksum: u16,
+
+    /// Command flag for the message
+    #[packed_field(bytes = "0x08", ty = "enum")]
+    pub(crate) command: PowerStripCommand,
+
+    /// Constant magic value, only set when switching sockets
+    #[packed_field(bytes = "0x0A")]
+    set_magic: u8,
+
+    /// The sockets to switch, one bit per socket
+    #[packed_field(bytes = "0x0D")]
+    pub(crate) socket_mask: u8,
+
+    /// The state of the sockets, one bit per socket
+    #[packed_field(bytes = "0x0E")]
+    pub(crate) state_mask: u8,
+}
+
+impl PowerStripDataMessage {
+    /// Create a new PowerStripDataMessage which asks for the state of all sockets.
+    pub fn get_state() -> PowerStripDataMessage {
+        return PowerStripDataMessage {
+            length: 0x0A,
+            magic: [0xA5, 0xA5, 0x5A, 0x5A],
+            checksum: 0, // This will be populated later.
+            command: PowerStripCommand::GetState,
+            set_magic: 0,
+            socket_mask: 0,
+            state_mask: 0,
+        };
+    }
+
+    /// Create a new PowerStripDataMessage which switches the sockets in the mask on or off.
+    pub fn set_state(socket_mask: u8, power: bool) -> PowerStripDataMessage {
+        return PowerStripDataMessage {
+            length: 0x0D,
+            command: PowerStripCommand::SetState,
+            set_magic: 0x03,
+            socket_mask,
+            state_mask: if power { socket_mask } else { 0 },
+            ..PowerStripDataMessage::get_state()
+        };
+    }
+
+    /// Pack the PowerStripDataMessage, computing its checksum.
+    pub fn pack_with_checksum(mut self) -> Result<Vec<u8>, Error> {
+        self.checksum = 0;
+        self.checksum = checksum(&self.pack()?[0x02..]);
+
+        return Ok(self.pack()?.to_vec());
+    }
+
+    /// Unpack the state of the sockets, one bit per socket, from a decrypted response.
+    pub fn unpack_state(bytes: &[u8]) -> Result<u8, Error> {
+        if bytes.len() < 0x08 {
+            return Err(Error::InvalidResponse(format!(
+                "Power strip response is too short! Expected at least 0x08 bytes, got {}",
+                bytes.len()
+            )));
+        }
+
+        // Any trailing zeros are stripped when decrypting, so the state may be missing.
+        return Ok(bytes.get(0x0E).copied().unwrap_or(0));
+    }
+}
+
+impl CommandTrait for PowerStripDataMessage {
+    fn packet_type() -> u16 {
+        return 0x006A;
+    }
+}
@@ -1,6 +1,6 @@
-use packed_struct::prelude::{PackedStruct, PackedStructSlice, PrimitiveEnum_u8};
+use packed_struct::prelude::{PackedStruct, PackedStructSlice, PrimitiveEnum, PrimitiveEnum_u8};
 
-use crate::traits::CommandTrait;
+use crate::{network::util::unpack_zero_padded, traits::CommandTrait, Error};
 
 /// The type of command to send with the remote's data.
 #[derive(PrimitiveEnum_u8, Debug, Copy, Clone)]
@@ -27,9 +27,16 @@ pub enum RemoteDataCommand {
 
     /// Inform the device to see if an RF frequency has been found during the sweep.
     CheckFrequency = 0x1A,
+
+    /// Inform the device to return its temperature and humidity readings.
+    CheckSensors = 0x24,
 }
 
 /// A message used to inform a remote of data to blast.
+///
+/// RM4 generation remotes (and the newest RM mini 3) expect the command to be prefixed with the
+/// length of the message. Older remotes expect the 4 byte command only, refer to
+/// [RemoteDataMessage::pack_legacy_with_payload].
 #[derive(PackedStruct, Debug)]
 #[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "0x06")]
 pub struct RemoteDataMessage {
@@ -39,7 +46,7 @@ pub struct RemoteDataMessage {
 
     /// Command flag for the message
     #[packed_field(bytes = "0x02", ty = "enum")]
-    command: RemoteDataCommand,
+    pub(crate) command: RemoteDataCommand,
 }
 
 impl RemoteDataMessage {
@@ -52,24 +59,20 @@ impl RemoteDataMessage {
     }
 
     /// Pack the RemoteDataMessage with an associated payload.
-    pub fn pack_with_payload(mut self, payload: &[u8]) -> Result<Vec<u8>, String> {
+    pub fn pack_with_payload(mut self, payload: &[u8]) -> Result<Vec<u8>, Error> {
         // Calculate tyhe length of the payload
         self.payload_length = payload
             .len()
             .try_into()
-            .map_err(|e| format!("Payload is too long! {}", e))?;
+            .map_err(|e| Error::InvalidArgument(format!("Payload is too long! {}", e)))?;
 
         // Add 4 for the needed stop sequence
-        self.payload_length = self
-            .payload_length
-            .checked_add(4u16)
-            .ok_or_else(|| "Could not add the start buffer! Payload is too long")?;
+        self.payload_length = self.payload_length.checked_add(4u16).ok_or_else(|| {
+            Error::InvalidArgument("Could not add the start buffer! Payload is too long".into())
+        })?;
 
         // Append the payload to the header
-        let mut result = self
-            .pack()
-            .map_err(|e| format!("Could not pack message! {}", e))?
-            .to_vec();
+        let mut result = self.pack()?.to_vec();
         result.extend(payload);
 
         return Ok(result);
@@ -80,7 +83,7 @@ impl RemoteDataMessage {
     /// Note: The RemoteDataMessage will sometimes respond with unknown data,
     /// so this method returns no data at all if the response is not at least
     /// as large as the header.
-    pub fn unpack_with_payload(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    pub fn unpack_with_payload(bytes: &[u8]) -> Result<Vec<u8>, Error> {
         // This is somewhat different than other messages. If there is no data, the
         // device will send us anywhere from 1 to 3 bytes, which is useless. So
         // we just discard anything that is below the threshold.
@@ -89,14 +92,34 @@ impl RemoteDataMessage {
         }
 
         // Attempt to unpack the header
-        let info = RemoteDataMessage::unpack_from_slice(&bytes[0x00..0x06])
-            .map_err(|e| format!("Could not unpack remote data response! {}", e))?;
+        let info = RemoteDataMessage::unpack_from_slice(&bytes[0x00..0x06])?;
+
+        // Extract the payload. The advertised length includes the 4 byte command, and any
+        // trailing zeros are stripped when decrypting, so restore them here.
+        let payload_length = usize::from(info.payload_length).saturating_sub(4);
+        let mut payload = bytes[0x06..].to_vec();
+        payload.resize(payload_length, 0);
+
+        return Ok(payload);
+    }
 
-        // Extract the payload
-        let payload_length = usize::from(info.payload_length + 1);
-        let payload = &bytes[0x06..payload_length];
+    /// Pack the RemoteDataMessage with an associated payload, as expected by older remotes
+    /// (e.g. RM mini 3, RM pro+). These use a 4 byte command without any length.
+    pub fn pack_legacy_with_payload(self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut result = u32::from(self.command.to_primitive())
+            .to_le_bytes()
+            .to_vec();
+        result.extend(payload);
 
-        return Ok(payload.to_vec());
+        return Ok(result);
+    }
+
+    /// Unpack the payload of a response sent by older remotes, skipping the 4 byte command.
+    ///
+    /// Note: These remotes do not send the length of the payload, so any trailing zeros
+    /// stripped when decrypting cannot be restored.
+    pub fn unpack_legacy_with_payload(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        return Ok(bytes.get(0x04..).unwrap_or_default().to_vec());
     }
 }
 
@@ -105,3 +128,72 @@ impl CommandTrait for RemoteDataMessage {
         return 0x006A;
     }
 }
+
+/// A struct with the temperature and humidity readings of a remote.
+#[derive(PackedStruct, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "0x04")]
+pub struct RemoteSensorReadings {
+    /// Temperature (integer part)
+    #[packed_field(bytes = "0x00")]
+    temperature_int: i8,
+
+    /// Temperature (hundredths)
+    #[packed_field(bytes = "0x01")]
+    temperature_fract: u8,
+
+    /// Relative humidity (integer part)
+    #[packed_field(bytes = "0x02")]
+    humidity_int: u8,
+
+    /// Relative humidity (hundredths)
+    #[packed_field(bytes = "0x03")]
+    humidity_fract: u8,
+}
+
+impl RemoteSensorReadings {
+    /// Unpack the readings from the payload returned by [RemoteDataMessage::unpack_with_payload].
+    pub fn unpack_response(bytes: &[u8]) -> Result<RemoteSensorReadings, Error> {
+        return unpack_zero_padded(bytes);
+    }
+
+    /// Calculate the temperature, in degrees Celsius, from internal partial fields.
+    pub fn get_temperature(&self) -> f32 {
+        return self.temperature_int as f32 + self.temperature_fract as f32 / 100.0;
+    }
+
+    /// Set the temperature, in degrees Celsius. Only the first two decimals are kept.
+    pub fn set_temperature(&mut self, input: f32) -> Result<(), Error> {
+        // Check the rounded value, since e.g. 127.999 would otherwise wrap around
+        let hundredths = (input * 100.0).round() as i32;
+        if input.is_nan() || !(-12800..12800).contains(&hundredths) {
+            return Err(Error::InvalidArgument(
+                "Temperature is out of range (-128-127.99)".into(),
+            ));
+        }
+
+        self.temperature_int = hundredths.div_euclid(100) as i8;
+        self.temperature_fract = hundredths.rem_euclid(100) as u8;
+
+        return Ok(());
+    }
+
+    /// Calculate the relative humidity, in percent, from internal partial fields.
+    pub fn get_humidity(&self) -> f32 {
+        return self.humidity_int as f32 + self.humidity_fract as f32 / 100.0;
+    }
+
+    /// Set the relative humidity, in percent. Only the first two decimals are kept.
+    pub fn set_humidity(&mut self, input: f32) -> Result<(), Error> {
+        if !(0.0..=100.0).contains(&input) {
+            return Err(Error::InvalidArgument(
+                "Humidity is out of range (0-100)".into(),
+            ));
+        }
+
+        let hundredths = (input * 100.0).round() as u32;
+        self.humidity_int = (hundredths / 100) as u8;
+        self.humidity_fract = (hundredths % 100) as u8;
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,267 @@
+use std::ops::RangeInclusive;
+
+use packed_struct::prelude::{PackedStruct, PrimitiveEnum_u8};
+
+use crate::{network::util::unpack_zero_padded, traits::CommandTrait, Error};
+
+/// The type of command to send to an environmental sensor.
+#[derive(PrimitiveEnum_u8, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SensorDataCommand {
+    /// Ask the sensor for its current readings.
+    GetReadings = 0x01,
+}
+
+/// Enumerates light levels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LightLevel {
+    Dark,
+    Dim,
+    Normal,
+    Bright,
+
+    /// A light level not known to this library, with its raw value.
+    Unknown(u8),
+}
+
+impl From<u8> for LightLevel {
+    fn from(value: u8) -> Self {
+        return match value {
+            0 => LightLevel::Dark,
+            1 => LightLevel::Dim,
+            2 => LightLevel::Normal,
+            3 => LightLevel::Bright,
+            _ => LightLevel::Unknown(value),
+        };
+    }
+}
+
+impl From<LightLevel> for u8 {
+    fn from(level: LightLevel) -> Self {
+        return match level {
+            LightLevel::Dark => 0,
+            LightLevel::Dim => 1,
+            LightLevel::Normal => 2,
+            LightLevel::Bright => 3,
+            LightLevel::Unknown(value) => value,
+        };
+    }
+}
+
+/// Enumerates air quality levels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AirQuality {
+    Excellent,
+    Good,
+    Normal,
+    Bad,
+
+    /// An air quality level not known to this library, with its raw value.
+    Unknown(u8),
+}
+
+impl From<u8> for AirQuality {
+    fn from(value: u8) -> Self {
+        return match value {
+            0 => AirQuality::Excellent,
+            1 => AirQuality::Good,
+            2 => AirQuality::Normal,
+            3 => AirQuality::Bad,
+            _ => AirQuality::Unknown(value),
+        };
+    }
+}
+
+impl From<AirQuality> for u8 {
+    fn from(quality: AirQuality) -> Self {
+        return match quality {
+            AirQuality::Excellent => 0,
+            AirQuality::Good => 1,
+            AirQuality::Normal => 2,
+            AirQuality::Bad => 3,
+            AirQuality::Unknown(value) => value,
+        };
+    }
+}
+
+/// Enumerates noise levels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoiseLevel {
+    Quiet,
+    Normal,
+    Noisy,
+
+    /// A noise level not known to this library, with its raw value.
+    Unknown(u8),
+}
+
+impl From<u8> for NoiseLevel {
+    fn from(value: u8) -> Self {
+        return match value {
+            0 => NoiseLevel::Quiet,
+            1 => NoiseLevel::Normal,
+            2 => NoiseLevel::Noisy,
+            _ => NoiseLevel::Unknown(value),
+        };
+    }
+}
+
+impl From<NoiseLevel> for u8 {
+    fn from(level: NoiseLevel) -> Self {
+        return match level {
+            NoiseLevel::Quiet => 0,
+            NoiseLevel::Normal => 1,
+            NoiseLevel::Noisy => 2,
+            NoiseLevel::Unknown(value) => value,
+        };
+    }
+}
+
+/// A message used to ask an environmental sensor for its readings.
+#[derive(PackedStruct, Debug)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "0x10")]
+pub struct SensorDataMessage {
+    /// Command flag for the message
+    #[packed_field(bytes = "0x00", ty = "enum")]
+    pub(crate) command: SensorDataCommand,
+}
+
+impl SensorDataMessage {
+    /// Create a new SensorDataMessage.
+    pub fn new(command: SensorDataCommand) -> SensorDataMessage {
+        return SensorDataMessage { command };
+    }
+}
+
+impl CommandTrait for SensorDataMessage {
+    fn packet_type() -> u16 {
+        return 0x006A;
+    }
+}
+
+/// A struct with the readings of an environmental sensor.
+#[derive(PackedStruct, Clone, Copy, Debug, PartialEq, Eq)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "0x10")]
+pub struct SensorReadings {
+    /// Temperature (integer part, rounded down)
+    #[packed_field(bytes = "0x04")]
+    temperature_int: i8,
+
+    /// Temperature (tenths)
+    #[packed_field(bytes = "0x05")]
+    temperature_fract: u8,
+
+    /// Relative humidity (integer part)
+    #[packed_field(bytes = "0x06")]
+    humidity_int: u8,
+
+    /// Relative humidity (tenths)
+    #[packed_field(bytes = "0x07")]
+    humidity_fract: u8,
+
+    /// Raw light level
+    #[packed_field(bytes = "0x08")]
+    light: u8,
+
+    /// Raw air quality
+    #[packed_field(bytes = "0x0A")]
+    air_quality: u8,
+
+    /// Raw noise level
+    #[packed_field(bytes = "0x0C")]
+    noise: u8,
+}
+
+impl SensorReadings {
+    /// Unpack the readings from a decrypted response.
+    pub fn unpack_response(bytes: &[u8]) -> Result<SensorReadings, Error> {
+        return unpack_zero_padded(bytes);
+    }
+
+    /// Calculate the temperature, in degrees Celsius, from internal partial fields.
+    pub fn get_temperature(&self) -> f32 {
+        return self.temperature_int as f32 + self.temperature_fract as f32 / 10.0;
+    }
+
+    /// Set the temperature, in degrees Celsius. Only the first decimal is kept.
+    pub fn set_temperature(&mut self, input: f32) -> Result<(), Error> {
+        let (integer, tenths) = split_tenths(input, -128..=127, "Temperature")?;
+        self.temperature_int = integer as i8;
+        self.temperature_fract = tenths;
+
+        return Ok(());
+    }
+
+    /// Calculate the relative humidity, in percent, from internal partial fields.
+    pub fn get_humidity(&self) -> f32 {
+        return self.humidity_int as f32 + self.humidity_fract as f32 / 10.0;
+    }
+
+    /// Set the relative humidity, in percent. Only the first decimal is kept.
+    pub fn set_humidity(&mut self, input: f32) -> Result<(), Error> {
+        let (integer, tenths) = split_tenths(input, 0..=255, "Humidity")?;
+        self.humidity_int = integer as u8;
+        self.humidity_fract = tenths;
+
+        return Ok(());
+    }
+
+    /// Get the light level.
+    pub fn get_light(&self) -> LightLevel {
+        return self.light.into();
+    }
+
+    /// Set the light level.
+    pub fn set_light(&mut self, level: LightLevel) {
+        self.light = level.into();
+    }
+
+    /// Get the air quality.
+    pub fn get_air_quality(&self) -> AirQuality {
+        return self.air_quality.into();
+    }
+
+    /// Set the air quality.
+    pub fn set_air_quality(&mut self, quality: AirQuality) {
+        self.air_quality = quality.into();
+    }
+
+    /// Get the noise level.
+    pub fn get_noise(&self) -> NoiseLevel {
+        return self.noise.into();
+    }
+
+    /// Set the noise level.
+    pub fn set_noise(&mut self, level: NoiseLevel) {
+        self.noise = level.into();
+    }
+}
+
+impl Default for SensorReadings {
+    fn default() -> Self {
+        return SensorReadings {
+            temperature_int: 0,
+            temperature_fract: 0,
+            humidity_int: 0,
+            humidity_fract: 0,
+            light: LightLevel::Dark.into(),
+            air_quality: AirQuality::Excellent.into(),
+            noise: NoiseLevel::Quiet.into(),
+        };
+    }
+}
+
+/// Splits a value into its integer part, rounded down, and tenths, as reported by the sensor.
+fn split_tenths(input: f32, range: RangeInclusive<i32>, name: &str) -> Result<(i32, u8), Error> {
+    let tenths = (input * 10.0).round() as i32;
+    let integer = tenths.div_euclid(10);
+    if input.is_nan() || !range.contains(&integer) {
+        return Err(Error::InvalidArgument(format!(
+            "{} is out of range ({}-{}.9)",
+            name,
+            range.start(),
+            range.end()
+        )));
+    }
+
+    return Ok((integer, tenths.rem_euclid(10) as u8));
+}
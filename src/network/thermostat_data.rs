@@ -0,0 +1,439 @@
+use packed_struct::prelude::{
+    packed_bits, Integer, PackedStruct, PackedStructSlice, PrimitiveEnum_u8,
+};
+
+use crate::{network::util::modbus_crc16, traits::CommandTrait, Error};
+
+/// The Modbus function to perform on a thermostat.
+#[derive(PrimitiveEnum_u8, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ThermostatDataCommand {
+    /// Read a range of holding registers.
+    ReadRegisters = 0x03,
+
+    /// Write a single holding register.
+    WriteRegister = 0x06,
+
+    /// Write a range of holding registers.
+    WriteRegisters = 0x10,
+}
+
+/// Enumerates the operating modes of a thermostat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThermostatMode {
+    /// Keep the target temperature set by hand.
+    Manual,
+
+    /// Follow the weekly schedule.
+    Auto,
+
+    /// An operating mode not known to this library, with its raw value.
+    Unknown(u8),
+}
+
+impl From<u8> for ThermostatMode {
+    fn from(value: u8) -> Self {
+        return match value {
+            0 => ThermostatMode::Manual,
+            1 => ThermostatMode::Auto,
+            _ => ThermostatMode::Unknown(value),
+        };
+    }
+}
+
+impl From<ThermostatMode> for u8 {
+    fn from(mode: ThermostatMode) -> Self {
+        return match mode {
+            ThermostatMode::Manual => 0,
+            ThermostatMode::Auto => 1,
+            ThermostatMode::Unknown(value) => value,
+        };
+    }
+}
+
+/// Enumerates which days of the week follow the weekday schedule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThermostatLoopMode {
+    /// Monday to Friday follow the weekday schedule, Saturday and Sunday the weekend one.
+    Weekdays12345,
+
+    /// Monday to Saturday follow the weekday schedule, Sunday the weekend one.
+    Weekdays123456,
+
+    /// Every day follows the weekday schedule.
+    Weekdays1234567,
+
+    /// A loop mode not known to this library, with its raw value.
+    Unknown(u8),
+}
+
+impl From<u8> for ThermostatLoopMode {
+    fn from(value: u8) -> Self {
+        return match value {
+            1 => ThermostatLoopMode::Weekdays12345,
+            2 => ThermostatLoopMode::Weekdays123456,
+            3 => ThermostatLoopMode::Weekdays1234567,
+            _ => ThermostatLoopMode::Unknown(value),
+        };
+    }
+}
+
+impl From<ThermostatLoopMode> for u8 {
+    fn from(loop_mode: ThermostatLoopMode) -> Self {
+        return match loop_mode {
+            ThermostatLoopMode::Weekdays12345 => 1,
+            ThermostatLoopMode::Weekdays123456 => 2,
+            ThermostatLoopMode::Weekdays1234567 => 3,
+            ThermostatLoopMode::Unknown(value) => value,
+        };
+    }
+}
+
+/// Enumerates the sensors used to control a thermostat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThermostatSensor {
+    /// Control using the internal sensor.
+    Internal,
+
+    /// Control using the external (floor) sensor.
+    External,
+
+    /// Control using the internal sensor, limited by the external sensor.
+    InternalLimitedByExternal,
+
+    /// A sensor setting not known to this library, with its raw value.
+    Unknown(u8),
+}
+
+impl From<u8> for ThermostatSensor {
+    fn from(value: u8) -> Self {
+        return match value {
+            0 => ThermostatSensor::Internal,
+            1 => ThermostatSensor::External,
+            2 => ThermostatSensor::InternalLimitedByExternal,
+            _ => ThermostatSensor::Unknown(value),
+        };
+    }
+}
+
+impl From<ThermostatSensor> for u8 {
+    fn from(sensor: ThermostatSensor) -> Self {
+        return match sensor {
+            ThermostatSensor::Internal => 0,
+            ThermostatSensor::External => 1,
+            ThermostatSensor::InternalLimitedByExternal => 2,
+            ThermostatSensor::Unknown(value) => value,
+        };
+    }
+}
+
+/// A single period of a thermostat schedule.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ThermostatPeriod {
+    /// The hour at which this period starts
+    pub start_hour: u8,
+
+    /// The minute at which this period starts
+    pub start_minute: u8,
+
+    /// The target temperature during this period, in degrees Celsius
+    pub temp: f32,
+}
+
+/// The weekly schedule followed by a thermostat in [ThermostatMode::Auto].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ThermostatSchedule {
+    /// The periods of a weekday, in order
+    pub weekday: [ThermostatPeriod; 6],
+
+    /// The periods of a weekend day, in order
+    pub weekend: [ThermostatPeriod; 2],
+}
+
+/// The full status of a thermostat, as read from its holding registers.
+///
+/// Offsets include the 3 byte Modbus header of the response.
+#[derive(PackedStruct, Clone, Copy, Debug, PartialEq, Eq)]
+#[packed_struct(bit_numbering = "msb0", endian = "lsb", size_bytes = "47")]
+pub struct ThermostatStatus {
+    /// Whether the buttons on the thermostat are locked
+    #[packed_field(bits = "31")]
+    pub remote_lock: bool,
+
+    /// Whether the thermostat is cooling instead of heating
+    #[packed_field(bits = "32")]
+    pub cooling: bool,
+
+    /// Whether the target temperature was set by hand while following the schedule
+    #[packed_field(bits = "33")]
+    pub manual_temp: bool,
+
+    /// Whether the relay is currently switched on
+    #[packed_field(bits = "35")]
+    pub active: bool,
+
+    /// Whether the temperature offset should be added to the measured temperatures
+    #[packed_field(bits = "36")]
+    temp_offset_enabled: bool,
+
+    /// Power state (on/off)
+    #[packed_field(bits = "39")]
+    pub power: bool,
+
+    /// Room temperature, in half degrees
+    #[packed_field(bytes = "5")]
+    room_temp_raw: u8,
+
+    /// Target temperature, in half degrees
+    #[packed_field(bytes = "6")]
+    target_temp_raw: u8,
+
+    /// Raw loop mode, i.e. which days follow the weekday schedule
+    #[packed_field(bits = "56..=59")]
+    loop_mode_raw: Integer<u8, packed_bits::Bits<4>>,
+
+    /// Raw operating mode (manual or scheduled)
+    #[packed_field(bits = "60..=63")]
+    mode_raw: Integer<u8, packed_bits::Bits<4>>,
+
+    /// Raw sensor used to control the thermostat
+    #[packed_field(bytes = "8")]
+    sensor_raw: u8,
+
+    /// Temperature limit of the external sensor (OSV), in degrees Celsius
+    #[packed_field(bytes = "9")]
+    pub external_limit: u8,
+
+    /// Dead zone of the external sensor (dIF), in degrees Celsius
+    #[packed_field(bytes = "10")]
+    pub dead_zone: u8,
+
+    /// Upper temperature limit of the internal sensor (SVH), in degrees Celsius
+    #[packed_field(bytes = "11")]
+    pub max_temp: u8,
+
+    /// Lower temperature limit of the internal sensor (SVL), in degrees Celsius
+    #[packed_field(bytes = "12")]
+    pub min_temp: u8,
+
+    /// Room temperature calibration (AdJ), in tenths of a degree
+    #[packed_field(bytes = "13:14", endian = "msb")]
+    room_temp_adj_raw: i16,
+
+    /// Whether the anti-freezing function is enabled (FrE)
+    #[packed_field(bits = "127")]
+    pub anti_freeze: bool,
+
+    /// Whether the power state is restored after a power loss (POn)
+    #[packed_field(bits = "135")]
+    pub power_on_memory: bool,
+
+    /// Temperature offset, in tenths of a degree minus one
+    #[packed_field(bits = "138..=139")]
+    temp_offset_raw: Integer<u8, packed_bits::Bits<2>>,
+
+    /// External sensor temperature, in half degrees
+    #[packed_field(bytes = "18")]
+    external_temp_raw: u8,
+
+    /// Hour of the thermostat clock
+    #[packed_field(bytes = "19")]
+    pub hour: u8,
+
+    /// Minute of the thermostat clock
+    #[packed_field(bytes = "20")]
+    pub minute: u8,
+
+    /// Second of the thermostat clock
+    #[packed_field(bytes = "21")]
+    pub second: u8,
+
+    /// Day of the week of the thermostat clock, starting with 1 for Monday
+    #[packed_field(bytes = "22")]
+    pub day_of_week: u8,
+
+    /// Start hour and minute of every period of the schedule
+    #[packed_field(bytes = "23:38")]
+    schedule_times: [u8; 16],
+
+    /// Target temperature of every period of the schedule, in half degrees
+    #[packed_field(bytes = "39:46")]
+    schedule_temps: [u8; 8],
+}
+
+impl ThermostatStatus {
+    /// Unpack the status from the Modbus response to a read of all registers.
+    pub fn unpack_response(bytes: &[u8]) -> Result<ThermostatStatus, Error> {
+        if bytes.len() < 47 {
+            return Err(Error::InvalidResponse(format!(
+                "Thermostat status is too short! Expected 47 bytes, got {}",
+                bytes.len()
+            )));
+        }
+
+        return Ok(ThermostatStatus::unpack_from_slice(&bytes[0..47])?);
+    }
+
+    /// Calculate the room temperature, in degrees Celsius, including its offset.
+    pub fn get_room_temp(&self) -> f32 {
+        return self.decode_temp(self.room_temp_raw);
+    }
+
+    /// Calculate the target temperature, in degrees Celsius.
+    pub fn get_target_temp(&self) -> f32 {
+        return self.target_temp_raw as f32 / 2.0;
+    }
+
+    /// Calculate the external sensor temperature, in degrees Celsius, including its offset.
+    pub fn get_external_temp(&self) -> f32 {
+        return self.decode_temp(self.external_temp_raw);
+    }
+
+    /// Calculate the room temperature calibration, in degrees Celsius.
+    pub fn get_room_temp_adj(&self) -> f32 {
+        return self.room_temp_adj_raw as f32 / 10.0;
+    }
+
+    /// Get the operating mode.
+    pub fn get_mode(&self) -> ThermostatMode {
+        return u8::from(self.mode_raw).into();
+    }
+
+    /// Get which days follow the weekday schedule.
+    pub fn get_loop_mode(&self) -> ThermostatLoopMode {
+        return u8::from(self.loop_mode_raw).into();
+    }
+
+    /// Get the sensor used to control the thermostat.
+    pub fn get_sensor(&self) -> ThermostatSensor {
+        return self.sensor_raw.into();
+    }
+
+    /// Get the weekly schedule.
+    pub fn get_schedule(&self) -> ThermostatSchedule {
+        let mut periods = [ThermostatPeriod::default(); 8];
+        for (i, period) in periods.iter_mut().enumerate() {
+            *period = ThermostatPeriod {
+                start_hour: self.schedule_times[2 * i],
+                start_minute: self.schedule_times[2 * i + 1],
+                temp: self.schedule_temps[i] as f32 / 2.0,
+            };
+        }
+
+        let mut schedule = ThermostatSchedule::default();
+        schedule.weekday.copy_from_slice(&periods[0..6]);
+        schedule.weekend.copy_from_slice(&periods[6..8]);
+
+        return schedule;
+    }
+
+    /// Decodes a temperature, adding the offset if enabled.
+    fn decode_temp(&self, raw: u8) -> f32 {
+        let offset = if self.temp_offset_enabled {
+            (u8::from(self.temp_offset_raw) + 1) as f32 / 10.0
+        } else {
+            0.0
+        };
+
+        return raw as f32 / 2.0 + offset;
+    }
+}
+
+/// A message used to exchange Modbus frames with a thermostat.
+///
+/// The frame is prefixed with its length and followed by its CRC.
+#[derive(PackedStruct, Debug)]
+#[packed_struct(bit_numbering = "msb0", endian = "msb", size_bytes = "0x04")]
+pub struct ThermostatDataMessage {
+    /// Modbus address of the thermostat
+    #[packed_field(bytes = "0x00")]
+    address: u8,
+
+    /// Modbus function of the message
+    #[packed_field(bytes = "0x01", ty = "enum")]
+    pub(crate) command: ThermostatDataCommand,
+
+    /// The first register to read or write
+    #[packed_field(bytes = "0x02:0x03")]
+    pub(crate) register: u16,
+}
+
+impl ThermostatDataMessage {
+    /// Create a new ThermostatDataMessage.
+    pub fn new(command: ThermostatDataCommand, register: u16) -> ThermostatDataMessage {
+        return ThermostatDataMessage {
+            address: 0x01,
+            command,
+            register,
+        };
+    }
+
+    /// Pack the ThermostatDataMessage with an associated payload.
+    pub fn pack_with_payload(self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut frame = self.pack()?.to_vec();
+        frame.extend(payload);
+
+        return ThermostatDataMessage::pack_frame(&frame);
+    }
+
+    /// Pack a raw Modbus frame, prefixing its length and appending its CRC.
+    pub fn pack_frame(frame: &[u8]) -> Result<Vec<u8>, Error> {
+        // The length covers the frame and its CRC
+        let length: u16 = (frame.len() + 2)
+            .try_into()
+            .map_err(|e| Error::InvalidArgument(format!("Payload is too long! {}", e)))?;
+
+        let mut result = length.to_le_bytes().to_vec();
+        result.extend(frame);
+        result.extend(modbus_crc16(frame).to_le_bytes());
+
+        return Ok(result);
+    }
+
+    /// Unpack a ThermostatDataMessage and return the Modbus frame, without its CRC.
+    pub fn unpack_with_payload(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        if bytes.len() < 0x02 {
+            return Err(Error::InvalidResponse(format!(
+                "Thermostat response is too short! Expected at least 2 bytes, got {}",
+                bytes.len()
+            )));
+        }
+
+        // Trailing zeros (e.g. in the CRC) are stripped when decrypting, so restore them here.
+        let crc_offset = usize::from(u16::from_le_bytes([bytes[0], bytes[1]]));
+        if crc_offset < 0x04 {
+            return Err(Error::InvalidResponse(format!(
+                "Thermostat response advertises an invalid length of {}",
+                crc_offset
+            )));
+        }
+        let mut bytes = bytes.to_vec();
+        bytes.resize(bytes.len().max(crc_offset + 2), 0);
+
+        // Ensure that the CRCs match
+        let data_crc = u16::from_le_bytes([bytes[crc_offset], bytes[crc_offset + 1]]);
+        let real_crc = modbus_crc16(&bytes[0x02..crc_offset]);
+        if data_crc != real_crc {
+            return Err(Error::ChecksumMismatch {
+                expected: data_crc,
+                actual: real_crc,
+            });
+        }
+
+        // Modbus exceptions are flagged in the high bit of the function
+        let frame = bytes[0x02..crc_offset].to_vec();
+        if frame[1] & 0x80 != 0 {
+            return Err(Error::InvalidResponse(format!(
+                "Thermostat rejected the request with Modbus exception {}",
+                frame.get(2).copied().unwrap_or_default()
+            )));
+        }
+
+        return Ok(frame);
+    }
+}
+
+impl CommandTrait for ThermostatDataMessage {
+    fn packet_type() -> u16 {
+        return 0x006A;
+    }
+}
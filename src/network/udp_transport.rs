@@ -0,0 +1,245 @@
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+
+#[cfg(feature = "tokio")]
+use crate::{
+    network::util::{
+        bind_broadcast_socket_async, send_and_receive_many_async, send_and_receive_one_async,
+        send_from_and_receive_many_async, send_from_and_receive_one_async,
+    },
+    traits::TransportFuture,
+};
+
+use crate::{
+    network::{
+        util::{
+            bind_broadcast_socket, send_and_receive_many, send_and_receive_one,
+            send_from_and_receive_many, send_from_and_receive_one,
+        },
+        NetworkConfig,
+    },
+    traits::{DiscoveryBuilder, Transport},
+    Error,
+};
+
+/// The default [Transport], which sends each message from a fresh UDP socket.
+#[derive(Debug, Clone, Default)]
+pub struct UdpTransport {
+    /// The settings used for every message sent.
+    pub config: NetworkConfig,
+}
+
+impl UdpTransport {
+    /// Create a new UdpTransport using the specified settings.
+    pub fn with_config(config: NetworkConfig) -> UdpTransport {
+        return UdpTransport { config };
+    }
+
+    /// Create a new UdpTransport which sends messages to the specified device port.
+    pub fn with_device_port(device_port: u16) -> UdpTransport {
+        return UdpTransport::with_config(NetworkConfig {
+            device_port,
+            ..Default::default()
+        });
+    }
+
+    /// Get the local address to bind to, optionally on a specific port.
+    fn bind_addr(&self, local_port: Option<u16>) -> SocketAddr {
+        return SocketAddr::from((self.config.bind_addr, local_port.unwrap_or(0)));
+    }
+
+    /// Binds the socket that discovery responses are received on, returning it along with the
+    /// discovery message announcing its port.
+    fn bind_discovery(&self, build: DiscoveryBuilder) -> Result<(UdpSocket, Vec<u8>), Error> {
+        let socket = bind_broadcast_socket(self.bind_addr(Some(self.config.discovery_port)))?;
+
+        // Announce the port that was actually bound
+        let port = socket.local_addr().map_err(Error::Io)?.port();
+
+        return Ok((socket, build(port)?));
+    }
+
+    /// Async version of [UdpTransport::bind_discovery].
+    #[cfg(feature = "tokio")]
+    async fn bind_discovery_async(
+        &self,
+        build: DiscoveryBuilder<'_>,
+    ) -> Result<(tokio::net::UdpSocket, Vec<u8>), Error> {
+        let socket =
+            bind_broadcast_socket_async(self.bind_addr(Some(self.config.discovery_port))).await?;
+
+        // Announce the port that was actually bound
+        let port = socket.local_addr().map_err(Error::Io)?.port();
+
+        return Ok((socket, build(port)?));
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send_and_receive_one(
+        &self,
+        msg: &[u8],
+        addr: Ipv4Addr,
+        local_port: Option<u16>,
+    ) -> Result<(Vec<u8>, SocketAddr), Error> {
+        let destination = SocketAddr::from((addr, self.config.device_port));
+
+        return send_and_receive_one(
+            msg,
+            destination,
+            self.bind_addr(local_port),
+            self.config.command_timeout,
+            |_, bytes, addr| {
+                return Ok((bytes.to_vec(), addr));
+            },
+        );
+    }
+
+    fn send_and_receive_many(
+        &self,
+        msg: &[u8],
+        addr: Ipv4Addr,
+        local_port: Option<u16>,
+    ) -> Result<Vec<(Vec<u8>, SocketAddr)>, Error> {
+        let destination = SocketAddr::from((addr, self.config.device_port));
+
+        return send_and_receive_many(
+            msg,
+            destination,
+            self.bind_addr(local_port),
+            self.config.discovery_timeout,
+            |_, bytes, addr| {
+                return Ok((bytes.to_vec(), addr));
+            },
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    fn send_and_receive_one_async<'a>(
+        &'a self,
+        msg: &'a [u8],
+        addr: Ipv4Addr,
+        local_port: Option<u16>,
+    ) -> TransportFuture<'a, (Vec<u8>, SocketAddr)> {
+        let destination = SocketAddr::from((addr, self.config.device_port));
+
+        return Box::pin(send_and_receive_one_async(
+            msg,
+            destination,
+            self.bind_addr(local_port),
+            self.config.command_timeout,
+            |_, bytes, addr| {
+                return Ok((bytes.to_vec(), addr));
+            },
+        ));
+    }
+
+    #[cfg(feature = "tokio")]
+    fn send_and_receive_many_async<'a>(
+        &'a self,
+        msg: &'a [u8],
+        addr: Ipv4Addr,
+        local_port: Option<u16>,
+    ) -> TransportFuture<'a, Vec<(Vec<u8>, SocketAddr)>> {
+        let destination = SocketAddr::from((addr, self.config.device_port));
+
+        return Box::pin(send_and_receive_many_async(
+            msg,
+            destination,
+            self.bind_addr(local_port),
+            self.config.discovery_timeout,
+            |_, bytes, addr| {
+                return Ok((bytes.to_vec(), addr));
+            },
+        ));
+    }
+
+    fn config(&self) -> NetworkConfig {
+        return self.config;
+    }
+
+    fn discover_one(
+        &self,
+        build: DiscoveryBuilder,
+        addr: Ipv4Addr,
+    ) -> Result<(Vec<u8>, SocketAddr), Error> {
+        let destination = SocketAddr::from((addr, self.config.device_port));
+        let (socket, msg) = self.bind_discovery(build)?;
+
+        return send_from_and_receive_one(
+            &socket,
+            &msg,
+            destination,
+            self.config.command_timeout,
+            |_, bytes, addr| {
+                return Ok((bytes.to_vec(), addr));
+            },
+        );
+    }
+
+    fn discover_many(
+        &self,
+        build: DiscoveryBuilder,
+        addr: Ipv4Addr,
+    ) -> Result<Vec<(Vec<u8>, SocketAddr)>, Error> {
+        let destination = SocketAddr::from((addr, self.config.device_port));
+        let (socket, msg) = self.bind_discovery(build)?;
+
+        return send_from_and_receive_many(
+            &socket,
+            &msg,
+            &[destination],
+            self.config.discovery_timeout,
+            |_, bytes, addr| {
+                return Ok((bytes.to_vec(), addr));
+            },
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    fn discover_one_async<'a>(
+        &'a self,
+        build: DiscoveryBuilder<'a>,
+        addr: Ipv4Addr,
+    ) -> TransportFuture<'a, (Vec<u8>, SocketAddr)> {
+        let destination = SocketAddr::from((addr, self.config.device_port));
+
+        return Box::pin(async move {
+            let (socket, msg) = self.bind_discovery_async(build).await?;
+
+            return send_from_and_receive_one_async(
+                &socket,
+                &msg,
+                destination,
+                self.config.command_timeout,
+                |_, bytes, addr| {
+                    return Ok((bytes.to_vec(), addr));
+                },
+            )
+            .await;
+        });
+    }
+
+    #[cfg(feature = "tokio")]
+    fn discover_many_async<'a>(
+        &'a self,
+        build: DiscoveryBuilder<'a>,
+        addr: Ipv4Addr,
+    ) -> TransportFuture<'a, Vec<(Vec<u8>, SocketAddr)>> {
+        let destination = SocketAddr::from((addr, self.config.device_port));
+
+        return Box::pin(async move {
+            let (socket, msg) = self.bind_discovery_async(build).await?;
+
+            return send_from_and_receive_many_async(
+                &socket,
+                &msg,
+                &[destination],
+                self.config.discovery_timeout,
+                |_, bytes, addr| {
+                    return Ok((bytes.to_vec(), addr));
+                },
+            )
+            .await;
+        });
+    }
+}
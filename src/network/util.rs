@@ -3,9 +3,16 @@
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
     slice::ChunksExact,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+use packed_struct::PackedStructSlice;
+
+use crate::Error;
+
+/// The port broadlink devices listen on for incoming messages.
+pub const DEVICE_PORT: u16 = 80;
+
 /// Computes the checksum of a slice of bytes.
 ///
 /// The checksum is computed by summing all of the bytes with 0xBEAF and masking
@@ -20,6 +27,20 @@ pub fn checksum(data: &[u8]) -> u16 {
     return sum as u16;
 }
 
+/// Unpacks a fixed size structure from the start of a decrypted payload.
+///
+/// Any trailing zeros are stripped when decrypting, so the missing bytes are restored here.
+pub fn unpack_zero_padded<T>(bytes: &[u8]) -> Result<T, Error>
+where
+    T: PackedStructSlice,
+{
+    let size = T::packed_bytes_size(None)?;
+    let mut bytes = bytes.to_vec();
+    bytes.resize(bytes.len().max(size), 0);
+
+    return Ok(T::unpack_from_slice(&bytes[0x00..size])?);
+}
+
 /// Computes the generic checksum of a bytes array.
 ///
 /// This iterates all 16-bit array elements, summing
@@ -30,11 +51,11 @@ pub fn compute_generic_checksum(buf: &[u8]) -> u16 {
     let mut state: u32 = 0xFFFF;
 
     let mut chunks_iter: ChunksExact<u8> = buf.chunks_exact(2);
-    while let Some(chunk) = chunks_iter.next() {
+    for chunk in chunks_iter.by_ref() {
         state += u16::from_le_bytes([chunk[0], chunk[1]]) as u32;
     }
 
-    if let Some(&b) = chunks_iter.remainder().get(0) {
+    if let Some(&b) = chunks_iter.remainder().first() {
         state += u16::from_le_bytes([b, 0]) as u32;
     }
 
@@ -44,72 +65,98 @@ pub fn compute_generic_checksum(buf: &[u8]) -> u16 {
     state as u16
 }
 
+/// Computes the Modbus CRC16 of a slice of bytes.
+///
+/// This is the CRC used by thermostats speaking Modbus over the broadlink protocol,
+/// with a reflected polynomial of 0xA001 and an initial value of 0xFFFF.
+pub fn modbus_crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &d in data {
+        crc ^= u16::from(d);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    return crc;
+}
+
 /// Returns the first available non-local address or the passed IP, if present.
-pub fn local_ip_or(ip: Option<Ipv4Addr>) -> Result<IpAddr, String> {
+pub fn local_ip_or(ip: Option<Ipv4Addr>) -> Result<IpAddr, Error> {
     Ok(match ip {
         Some(ip) => IpAddr::V4(ip),
-        None => get_if_addrs::get_if_addrs()
-            .map_err(|e| {
-                format!(
-                    "Could not automatically determine machine IP address. {}",
-                    e
-                )
-            })?
+        None => get_if_addrs::get_if_addrs()?
             .iter()
             .find(|x| x.ip().is_ipv4() && !x.ip().is_loopback())
-            .ok_or("Could not find a local IPv4 address!")?
+            .ok_or_else(|| Error::InvalidArgument("Could not find a local IPv4 address!".into()))?
             .ip(),
     })
 }
 
-/// Sends a message and returns the received response.
-fn send_and_receive_impl(
-    msg: &[u8],
-    addr: Ipv4Addr,
-    port: Option<u16>,
-) -> Result<UdpSocket, String> {
-    // Set up the socket addresses
-    let unspecified_addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port.unwrap_or(0)));
-    let destination_addr = SocketAddr::from((addr, 80));
-
-    // Set up the communication socket
-    // Note: We need to enable support for broadcast
-    let socket = UdpSocket::bind(unspecified_addr)
-        .map_err(|e| format!("Could not bind to any port. {}", e))?;
-    socket
-        .set_broadcast(true)
-        .map_err(|e| format!("Could not enable broadcast. {}", e))?;
-
-    // Send the message
-    socket
-        .set_read_timeout(Some(Duration::new(10, 0)))
-        .map_err(|e| format!("Could not set read timeout! {}", e))?;
-    socket
-        .send_to(&msg, destination_addr)
-        .map_err(|e| format!("Could not broadcast message! {}", e))?;
+/// Binds a socket to the specified local address, with support for sending broadcasts.
+pub fn bind_broadcast_socket(bind: SocketAddr) -> Result<UdpSocket, Error> {
+    let socket = UdpSocket::bind(bind).map_err(Error::Io)?;
+    socket.set_broadcast(true).map_err(Error::Io)?;
 
     return Ok(socket);
 }
 
-/// Sends a message and returns the as many received responses as possible (within a timeout).
+/// Sends a message and returns as many received responses as possible (within a timeout).
 pub fn send_and_receive_many<I, T>(
     msg: &[u8],
-    addr: Ipv4Addr,
-    port: Option<u16>,
+    addr: SocketAddr,
+    bind: SocketAddr,
+    timeout: Duration,
     cb: T,
-) -> Result<Vec<I>, String>
+) -> Result<Vec<I>, Error>
 where
-    T: Fn(usize, &[u8], SocketAddr) -> Result<I, String>,
+    T: Fn(usize, &[u8], SocketAddr) -> Result<I, Error>,
 {
-    // Get the socket
-    let socket = send_and_receive_impl(msg, addr, port)
-        .map_err(|e| format!("Could not create socket for message sending! {}", e))?;
+    let socket = bind_broadcast_socket(bind)?;
 
-    // Transform the results
+    return send_from_and_receive_many(&socket, msg, &[addr], timeout, cb);
+}
+
+/// Sends a message to every address from an existing socket and returns as many received
+/// responses as possible (within a timeout).
+pub fn send_from_and_receive_many<I, T>(
+    socket: &UdpSocket,
+    msg: &[u8],
+    addrs: &[SocketAddr],
+    timeout: Duration,
+    cb: T,
+) -> Result<Vec<I>, Error>
+where
+    T: Fn(usize, &[u8], SocketAddr) -> Result<I, Error>,
+{
+    // Send the message
+    for addr in addrs {
+        socket.send_to(msg, addr).map_err(Error::Io)?;
+    }
+
+    // Transform the results until the timeout is reached
+    let deadline = Instant::now() + timeout;
     let mut results: Vec<I> = vec![];
     let mut recv_buffer = [0u8; 8092];
-    while let Ok((bytes_received, addr)) = socket.recv_from(&mut recv_buffer) {
-        results.push(cb(bytes_received, &recv_buffer[0..bytes_received], addr)?);
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+
+        socket
+            .set_read_timeout(Some(remaining))
+            .map_err(Error::Io)?;
+        match socket.recv_from(&mut recv_buffer) {
+            Ok((bytes_received, addr)) => {
+                results.push(cb(bytes_received, &recv_buffer[0..bytes_received], addr)?)
+            }
+            Err(_) => break,
+        };
     }
 
     return Ok(results);
@@ -118,33 +165,143 @@ where
 /// Sends a message and returns the first received response.
 pub fn send_and_receive_one<I, T>(
     msg: &[u8],
-    addr: Ipv4Addr,
-    port: Option<u16>,
+    addr: SocketAddr,
+    bind: SocketAddr,
+    timeout: Duration,
+    cb: T,
+) -> Result<I, Error>
+where
+    T: Fn(usize, &[u8], SocketAddr) -> Result<I, Error>,
+{
+    let socket = bind_broadcast_socket(bind)?;
+
+    return send_from_and_receive_one(&socket, msg, addr, timeout, cb);
+}
+
+/// Sends a message from an existing socket and returns the first received response.
+pub fn send_from_and_receive_one<I, T>(
+    socket: &UdpSocket,
+    msg: &[u8],
+    addr: SocketAddr,
+    timeout: Duration,
     cb: T,
-) -> Result<I, String>
+) -> Result<I, Error>
 where
-    T: Fn(usize, &[u8], SocketAddr) -> Result<I, String>,
+    T: Fn(usize, &[u8], SocketAddr) -> Result<I, Error>,
 {
-    // Get the socket
-    let socket = send_and_receive_impl(msg, addr, port)
-        .map_err(|e| format!("Could not create socket for message sending! {}", e))?;
+    // Send the message
+    socket.send_to(msg, addr).map_err(Error::Io)?;
 
     // Transform the result
+    socket.set_read_timeout(Some(timeout)).map_err(Error::Io)?;
     let mut recv_buffer = [0u8; 8092];
-    if let Ok((bytes_received, addr)) = socket.recv_from(&mut recv_buffer) {
-        return Ok(cb(bytes_received, &recv_buffer[0..bytes_received], addr)?);
+    let (bytes_received, addr) = socket.recv_from(&mut recv_buffer)?;
+
+    return cb(bytes_received, &recv_buffer[0..bytes_received], addr);
+}
+
+/// Async version of [bind_broadcast_socket].
+#[cfg(feature = "tokio")]
+pub async fn bind_broadcast_socket_async(bind: SocketAddr) -> Result<tokio::net::UdpSocket, Error> {
+    let socket = tokio::net::UdpSocket::bind(bind).await.map_err(Error::Io)?;
+    socket.set_broadcast(true).map_err(Error::Io)?;
+
+    return Ok(socket);
+}
+
+/// Async version of [send_and_receive_many].
+#[cfg(feature = "tokio")]
+pub async fn send_and_receive_many_async<I, T>(
+    msg: &[u8],
+    addr: SocketAddr,
+    bind: SocketAddr,
+    timeout: Duration,
+    cb: T,
+) -> Result<Vec<I>, Error>
+where
+    T: Fn(usize, &[u8], SocketAddr) -> Result<I, Error>,
+{
+    let socket = bind_broadcast_socket_async(bind).await?;
+
+    return send_from_and_receive_many_async(&socket, msg, &[addr], timeout, cb).await;
+}
+
+/// Async version of [send_from_and_receive_many].
+#[cfg(feature = "tokio")]
+pub async fn send_from_and_receive_many_async<I, T>(
+    socket: &tokio::net::UdpSocket,
+    msg: &[u8],
+    addrs: &[SocketAddr],
+    timeout: Duration,
+    cb: T,
+) -> Result<Vec<I>, Error>
+where
+    T: Fn(usize, &[u8], SocketAddr) -> Result<I, Error>,
+{
+    // Send the message
+    for addr in addrs {
+        socket.send_to(msg, addr).await.map_err(Error::Io)?;
+    }
+
+    // Transform the results until the timeout is reached
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut results: Vec<I> = vec![];
+    let mut recv_buffer = [0u8; 8092];
+    while let Ok(Ok((bytes_received, addr))) =
+        tokio::time::timeout_at(deadline, socket.recv_from(&mut recv_buffer)).await
+    {
+        results.push(cb(bytes_received, &recv_buffer[0..bytes_received], addr)?);
     }
 
-    return Err("No response within timeout!".into());
+    return Ok(results);
+}
+
+/// Async version of [send_and_receive_one].
+#[cfg(feature = "tokio")]
+pub async fn send_and_receive_one_async<I, T>(
+    msg: &[u8],
+    addr: SocketAddr,
+    bind: SocketAddr,
+    timeout: Duration,
+    cb: T,
+) -> Result<I, Error>
+where
+    T: Fn(usize, &[u8], SocketAddr) -> Result<I, Error>,
+{
+    let socket = bind_broadcast_socket_async(bind).await?;
+
+    return send_from_and_receive_one_async(&socket, msg, addr, timeout, cb).await;
+}
+
+/// Async version of [send_from_and_receive_one].
+#[cfg(feature = "tokio")]
+pub async fn send_from_and_receive_one_async<I, T>(
+    socket: &tokio::net::UdpSocket,
+    msg: &[u8],
+    addr: SocketAddr,
+    timeout: Duration,
+    cb: T,
+) -> Result<I, Error>
+where
+    T: Fn(usize, &[u8], SocketAddr) -> Result<I, Error>,
+{
+    // Send the message
+    socket.send_to(msg, addr).await.map_err(Error::Io)?;
+
+    // Transform the result
+    let mut recv_buffer = [0u8; 8092];
+    let (bytes_received, addr) = tokio::time::timeout(timeout, socket.recv_from(&mut recv_buffer))
+        .await
+        .map_err(|_| Error::Timeout)??;
+
+    return cb(bytes_received, &recv_buffer[0..bytes_received], addr);
 }
 
 /// Reverses a MAC address. Used to fix the backwards response from the broadlink device.
 pub fn reverse_mac(mac_flipped: [u8; 6]) -> [u8; 6] {
     // Fix the mac address by reversing it.
-    let mut mac = [0u8; 6];
-    for i in 0..6 {
-        mac[i] = mac_flipped[6 - i - 1];
-    }
+    let mut mac = mac_flipped;
+    mac.reverse();
 
     return mac;
 }
@@ -1,6 +1,6 @@
 use packed_struct::prelude::PackedStruct;
 
-use crate::network::util::checksum;
+use crate::{network::util::checksum, Error};
 
 /// WirelessConnection represents the credentials for connecting to a wireless
 /// network.
@@ -58,7 +58,7 @@ pub struct WirelessConnectionMessage {
 
 impl WirelessConnection<'_> {
     /// Pack a WirelessCOnnection into its network transport format.
-    pub fn to_message(&self) -> Result<WirelessConnectionMessage, String> {
+    pub fn to_message(&self) -> Result<WirelessConnectionMessage, Error> {
         let empty_pass = "";
         let (ssid, pass, security_mode) = match self {
             WirelessConnection::None(ssid) => (ssid, &empty_pass, 0),
@@ -70,7 +70,9 @@ impl WirelessConnection<'_> {
 
         // Ensure that the fields aren't too long
         if ssid.len() > 32 {
-            return Err("Could not use provided SSID! SSID longer than 32 characters.".into());
+            return Err(Error::InvalidArgument(
+                "Could not use provided SSID! SSID longer than 32 characters.".into(),
+            ));
         }
 
         // Copy over the strings into their fixed buffers
@@ -95,26 +97,23 @@ impl WirelessConnection<'_> {
             ssid: ssid_fixed,
             password: pass_fixed,
             ssid_length: u8::try_from(ssid.len()).map_err(|e| {
-                format!(
+                Error::InvalidArgument(format!(
                     "Could not use provided SSID! SSID is too long (max 32 characters). {}",
                     e
-                )
+                ))
             })?,
             password_length: u8::try_from(pass.len()).map_err(|e| {
-                format!(
+                Error::InvalidArgument(format!(
                     "Could not use provided password! Password is too long (max 32 characters). {}",
                     e
-                )
+                ))
             })?,
 
-            security_mode: security_mode,
+            security_mode,
         };
 
         // Add the checksum into the msg
-        msg.checksum = checksum(
-            &msg.pack()
-                .map_err(|e| format!("Could not pack WirelessConnectionMessage! {}", e))?,
-        );
+        msg.checksum = checksum(&msg.pack()?);
 
         // Return the newly created message
         return Ok(msg);
@@ -0,0 +1,440 @@
+use std::{net::Ipv4Addr, sync::Arc};
+
+use packed_struct::PackedStruct;
+use phf::phf_map;
+use serde_json::{json, Value};
+
+use crate::{
+    constants, lookup_model,
+    network::{
+        util::reverse_mac, DiscoveryResponse, JsonDataCommand, JsonDataMessage, PlugDataCommand,
+        PlugDataMessage, PlugEnergyMessage, PlugPowerMessage, UdpTransport,
+    },
+    traits::Transport,
+    Device, DeviceFamily, DeviceInfo, DeviceModel, Error, RetryPolicy,
+};
+
+/// The generation of a smart plug, which decides the messages that it understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlugType {
+    /// The original SP1, which can only be switched on and off.
+    Sp1,
+
+    /// SP2 and SP mini plugs.
+    Sp2,
+
+    /// SP2 plugs which also report their power usage.
+    Sp2s,
+
+    /// SP3 plugs, which have a nightlight.
+    Sp3,
+
+    /// SP3 plugs which also report their power usage.
+    Sp3s,
+
+    /// SP4 plugs, which exchange their state as JSON.
+    Sp4,
+
+    /// Newer SP4 plugs, which may also report their power usage.
+    Sp4b,
+}
+
+impl PlugType {
+    /// Returns true if plugs of this type have a controllable nightlight.
+    pub fn has_nightlight(&self) -> bool {
+        return matches!(self, PlugType::Sp3 | PlugType::Sp4 | PlugType::Sp4b);
+    }
+
+    /// Returns true if plugs of this type can report their power usage.
+    ///
+    /// Note: Not every SP4B plug has a power meter.
+    pub fn has_energy_meter(&self) -> bool {
+        return matches!(self, PlugType::Sp2s | PlugType::Sp3s | PlugType::Sp4b);
+    }
+}
+
+/// A mapping of plug device codes to their type and friendly model equivalent.
+pub const PLUG_CODES: phf::Map<u16, (PlugType, &'static str)> = phf_map! {
+    0x0000u16 => (PlugType::Sp1, "SP1"),
+    0x2717u16 => (PlugType::Sp2, "NEO"),
+    0x2719u16 => (PlugType::Sp2, "SP2-compatible"),
+    0x271Au16 => (PlugType::Sp2, "SP2-compatible"),
+    0x2720u16 => (PlugType::Sp2, "SP mini"),
+    0x2728u16 => (PlugType::Sp2, "SP2-compatible"),
+    0x273Eu16 => (PlugType::Sp2, "SP mini"),
+    0x7530u16 => (PlugType::Sp2, "SP2"),
+    0x7539u16 => (PlugType::Sp2, "SP2-IL"),
+    0x753Eu16 => (PlugType::Sp2, "SP mini 3"),
+    0x7540u16 => (PlugType::Sp2, "MP2"),
+    0x7544u16 => (PlugType::Sp2, "SP2-CL"),
+    0x7546u16 => (PlugType::Sp2, "SP2-UK/BR/IN"),
+    0x7547u16 => (PlugType::Sp2, "SC1"),
+    0x7918u16 => (PlugType::Sp2, "SP2"),
+    0x7919u16 => (PlugType::Sp2, "SP2-compatible"),
+    0x791Au16 => (PlugType::Sp2, "SP2-compatible"),
+    0x7D0Du16 => (PlugType::Sp2, "SP mini 3"),
+    0x2711u16 => (PlugType::Sp2s, "SP2"),
+    0x2716u16 => (PlugType::Sp2s, "NEO PRO"),
+    0x271Du16 => (PlugType::Sp2s, "Ego"),
+    0x2736u16 => (PlugType::Sp2s, "SP mini+"),
+    0x2733u16 => (PlugType::Sp3, "SP3"),
+    0x7D00u16 => (PlugType::Sp3, "SP3-EU"),
+    0x9479u16 => (PlugType::Sp3s, "SP3S-US"),
+    0x947Au16 => (PlugType::Sp3s, "SP3S-EU"),
+    0x7568u16 => (PlugType::Sp4, "SP4L-CN"),
+    0x756Cu16 => (PlugType::Sp4, "SP4M"),
+    0x756Fu16 => (PlugType::Sp4, "MCB1"),
+    0x7579u16 => (PlugType::Sp4, "SP4L-EU"),
+    0x757Bu16 => (PlugType::Sp4, "SP4L-AU"),
+    0x7583u16 => (PlugType::Sp4, "SP mini 3"),
+    0x7587u16 => (PlugType::Sp4, "SP4L-UK"),
+    0x7D11u16 => (PlugType::Sp4, "SP mini 3"),
+    0xA56Au16 => (PlugType::Sp4, "MCB1"),
+    0xA56Bu16 => (PlugType::Sp4, "SCB1E"),
+    0xA56Cu16 => (PlugType::Sp4, "SP4L-EU"),
+    0xA589u16 => (PlugType::Sp4, "SP4L-UK"),
+    0xA5D3u16 => (PlugType::Sp4, "SP4L-EU"),
+    0x5115u16 => (PlugType::Sp4b, "SCB1E"),
+    0x51E2u16 => (PlugType::Sp4b, "AHC/U-01"),
+    0x6111u16 => (PlugType::Sp4b, "MCB1"),
+    0x6113u16 => (PlugType::Sp4b, "SCB1E"),
+    0x618Bu16 => (PlugType::Sp4b, "SP4L-EU"),
+    0x6489u16 => (PlugType::Sp4b, "SP4L-AU"),
+    0x648Bu16 => (PlugType::Sp4b, "SP4M-US"),
+    0x6494u16 => (PlugType::Sp4b, "SCB2"),
+};
+
+/// The state of a smart plug.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlugState {
+    /// Whether the plug is switched on.
+    pub power: bool,
+
+    /// Whether the nightlight is switched on. Always false for plugs without a nightlight.
+    pub nightlight: bool,
+}
+
+/// A broadlink smart plug.
+#[derive(Debug, Clone)]
+pub struct PlugDevice {
+    /// Base information about the plug.
+    pub info: DeviceInfo,
+
+    /// The generation of the plug.
+    pub plug_type: PlugType,
+
+    /// The transport used to communicate with the device.
+    pub transport: Arc<dyn Transport>,
+
+    /// The policy used to retry failed commands sent to the device.
+    pub retry_policy: RetryPolicy,
+}
+
+impl PlugDevice {
+    /// Create a new PlugDevice.
+    ///
+    /// Note: This should not be called directly. Please use [Device::from_ip] or
+    /// [Device::list] instead.
+    pub fn new(name: &str, addr: Ipv4Addr, response: DiscoveryResponse) -> PlugDevice {
+        // Get the type of plug
+        let (plug_type, friendly_model) = match lookup_model(response.model_code) {
+            Some(DeviceModel {
+                family: DeviceFamily::Plug(plug_type),
+                name,
+            }) => (plug_type, name),
+            _ => (PlugType::Sp2, "Unknown".into()),
+        };
+
+        return Self {
+            info: DeviceInfo {
+                address: addr,
+                mac: reverse_mac(response.mac),
+                model_code: response.model_code,
+                friendly_type: "Plug".into(),
+                friendly_model: friendly_model.into(),
+                name: name.into(),
+                auth_id: 0, // This will be populated when authenticated.
+                key: constants::INITIAL_KEY,
+                is_locked: response.is_locked,
+                interface: None,
+            },
+            plug_type,
+            transport: Arc::new(UdpTransport::default()),
+            retry_policy: RetryPolicy::default(),
+        };
+    }
+
+    /// Switch the plug on or off.
+    pub fn set_power(&mut self, power: bool) -> Result<(), Error> {
+        return self.update_state(Some(power), None);
+    }
+
+    /// Switch the nightlight on or off.
+    pub fn set_nightlight(&mut self, nightlight: bool) -> Result<(), Error> {
+        return self.update_state(None, Some(nightlight));
+    }
+
+    /// Get the power and nightlight state of the plug.
+    pub fn get_state(&mut self) -> Result<PlugState, Error> {
+        let payload = self.state_payload()?;
+        let response = self.send_command(&payload)?;
+
+        return self.parse_state(&response);
+    }
+
+    /// Get the current power usage of the plug, in W.
+    pub fn get_energy(&mut self) -> Result<f32, Error> {
+        let payload = self.energy_payload()?;
+        let response = self.send_command(&payload)?;
+
+        return self.parse_energy(&response);
+    }
+
+    /// Sends a raw payload to the plug, using the packet type expected by its generation.
+    /// Note: Try to avoid using this method in favor of [PlugDevice::set_power], [PlugDevice::get_state], etc.
+    pub fn send_command(&mut self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        // We cast this object to a generic device in order to make use of the shared
+        // helper utilities.
+        let generic_device = Device::Plug { plug: self.clone() };
+
+        return match self.plug_type {
+            PlugType::Sp1 => {
+                generic_device.send_command_for::<PlugPowerMessage>(&mut self.info, payload)
+            }
+            _ => generic_device.send_command_for::<PlugDataMessage>(&mut self.info, payload),
+        };
+    }
+
+    /// Changes the specified parts of the state, keeping the rest as-is.
+    fn update_state(&mut self, power: Option<bool>, nightlight: Option<bool>) -> Result<(), Error> {
+        let current = match self.needs_current_state(power, nightlight)? {
+            true => Some(self.get_state()?),
+            false => None,
+        };
+
+        let payload = self.update_payload(power, nightlight, current)?;
+        self.send_command(&payload)?;
+
+        return Ok(());
+    }
+
+    /// Returns true if the current state is needed to change the specified parts of it.
+    fn needs_current_state(
+        &self,
+        power: Option<bool>,
+        nightlight: Option<bool>,
+    ) -> Result<bool, Error> {
+        if nightlight.is_some() && !self.plug_type.has_nightlight() {
+            return Err(Error::Unsupported(format!(
+                "{:?} plugs do not have a nightlight",
+                self.plug_type
+            )));
+        }
+
+        // SP2 / SP3 plugs always expect both flags to be set at once.
+        return Ok(
+            matches!(self.plug_type, PlugType::Sp3) && (power.is_none() || nightlight.is_none())
+        );
+    }
+
+    /// Creates the payload which changes the specified parts of the state.
+    fn update_payload(
+        &self,
+        power: Option<bool>,
+        nightlight: Option<bool>,
+        current: Option<PlugState>,
+    ) -> Result<Vec<u8>, Error> {
+        if let PlugType::Sp4 | PlugType::Sp4b = self.plug_type {
+            // Only send the values which should change.
+            let mut state = serde_json::Map::new();
+            if let Some(power) = power {
+                state.insert("pwr".into(), json!(u8::from(power)));
+            }
+            if let Some(nightlight) = nightlight {
+                state.insert("ntlight".into(), json!(u8::from(nightlight)));
+            }
+
+            return self.json_payload(JsonDataCommand::SetState, &Value::Object(state));
+        }
+
+        let current = current.unwrap_or_default();
+        let power = power.unwrap_or(current.power);
+        let nightlight = nightlight.unwrap_or(current.nightlight);
+
+        return match self.plug_type {
+            PlugType::Sp1 => Ok(PlugPowerMessage::new(power).pack()?.to_vec()),
+            _ => {
+                let flags = u32::from(nightlight) << 1 | u32::from(power);
+
+                Ok(PlugDataMessage::new(PlugDataCommand::SetState, flags)
+                    .pack()?
+                    .to_vec())
+            }
+        };
+    }
+
+    /// Creates the payload which asks the plug for its state.
+    fn state_payload(&self) -> Result<Vec<u8>, Error> {
+        return match self.plug_type {
+            PlugType::Sp1 => Err(Error::Unsupported(
+                "SP1 plugs cannot report their state".into(),
+            )),
+            PlugType::Sp4 | PlugType::Sp4b => {
+                self.json_payload(JsonDataCommand::GetState, &json!({}))
+            }
+            _ => Ok(PlugDataMessage::new(PlugDataCommand::GetState, 0)
+                .pack()?
+                .to_vec()),
+        };
+    }
+
+    /// Decodes the state of the plug from its response to the state payload.
+    fn parse_state(&self, response: &[u8]) -> Result<PlugState, Error> {
+        return match self.plug_type {
+            PlugType::Sp4 | PlugType::Sp4b => {
+                let state = self.parse_json(response)?;
+
+                Ok(PlugState {
+                    power: json_flag(&state, "pwr")?,
+                    nightlight: self.plug_type.has_nightlight() && json_flag(&state, "ntlight")?,
+                })
+            }
+            _ => {
+                let flags = PlugDataMessage::unpack_response(response)?.value();
+
+                Ok(PlugState {
+                    power: flags & 0x01 != 0,
+                    nightlight: self.plug_type.has_nightlight() && flags & 0x02 != 0,
+                })
+            }
+        };
+    }
+
+    /// Creates the payload which asks the plug for its power usage.
+    fn energy_payload(&self) -> Result<Vec<u8>, Error> {
+        return match self.plug_type {
+            PlugType::Sp2s => Ok(PlugDataMessage::new(PlugDataCommand::GetEnergy, 0)
+                .pack()?
+                .to_vec()),
+            PlugType::Sp3s => Ok(PlugEnergyMessage::new().pack()?.to_vec()),
+            PlugType::Sp4b => self.json_payload(JsonDataCommand::GetState, &json!({})),
+            _ => Err(Error::Unsupported(format!(
+                "{:?} plugs cannot report their power usage",
+                self.plug_type
+            ))),
+        };
+    }
+
+    /// Decodes the power usage of the plug, in W, from its response to the energy payload.
+    fn parse_energy(&self, response: &[u8]) -> Result<f32, Error> {
+        return match self.plug_type {
+            PlugType::Sp3s => PlugEnergyMessage::unpack_energy(response),
+            PlugType::Sp4b => {
+                // Plugs without a power meter either leave out the reading or report -1.
+                let state = self.parse_json(response)?;
+                match state.get("power").and_then(Value::as_f64) {
+                    Some(power) if power >= 0.0 => Ok(power as f32 / 1000.0),
+                    _ => Err(Error::Unsupported(
+                        "This plug does not have a power meter".into(),
+                    )),
+                }
+            }
+            _ => {
+                let energy = PlugDataMessage::unpack_response(response)?.value();
+
+                Ok(energy as f32 / 1000.0)
+            }
+        };
+    }
+
+    /// Packs a JSON message for SP4 plugs.
+    fn json_payload(&self, command: JsonDataCommand, state: &Value) -> Result<Vec<u8>, Error> {
+        return JsonDataMessage::new(command)
+            .pack_with_payload(&state.to_string(), self.plug_type == PlugType::Sp4b);
+    }
+
+    /// Decodes the JSON state sent back by SP4 plugs.
+    fn parse_json(&self, response: &[u8]) -> Result<Value, Error> {
+        let json =
+            JsonDataMessage::unpack_with_payload(response, self.plug_type == PlugType::Sp4b)?;
+
+        return serde_json::from_str(&json)
+            .map_err(|e| Error::InvalidResponse(format!("Could not parse plug state! {}", e)));
+    }
+}
+
+/// Async versions of the plug operations, for use within a tokio runtime.
+#[cfg(feature = "tokio")]
+impl PlugDevice {
+    /// Async version of [PlugDevice::set_power].
+    pub async fn set_power_async(&mut self, power: bool) -> Result<(), Error> {
+        return self.update_state_async(Some(power), None).await;
+    }
+
+    /// Async version of [PlugDevice::set_nightlight].
+    pub async fn set_nightlight_async(&mut self, nightlight: bool) -> Result<(), Error> {
+        return self.update_state_async(None, Some(nightlight)).await;
+    }
+
+    /// Async version of [PlugDevice::get_state].
+    pub async fn get_state_async(&mut self) -> Result<PlugState, Error> {
+        let payload = self.state_payload()?;
+        let response = self.send_command_async(&payload).await?;
+
+        return self.parse_state(&response);
+    }
+
+    /// Async version of [PlugDevice::get_energy].
+    pub async fn get_energy_async(&mut self) -> Result<f32, Error> {
+        let payload = self.energy_payload()?;
+        let response = self.send_command_async(&payload).await?;
+
+        return self.parse_energy(&response);
+    }
+
+    /// Async version of [PlugDevice::send_command].
+    pub async fn send_command_async(&mut self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        let generic_device = Device::Plug { plug: self.clone() };
+
+        return match self.plug_type {
+            PlugType::Sp1 => {
+                generic_device
+                    .send_command_for_async::<PlugPowerMessage>(&mut self.info, payload)
+                    .await
+            }
+            _ => {
+                generic_device
+                    .send_command_for_async::<PlugDataMessage>(&mut self.info, payload)
+                    .await
+            }
+        };
+    }
+
+    /// Async version of [PlugDevice::update_state].
+    async fn update_state_async(
+        &mut self,
+        power: Option<bool>,
+        nightlight: Option<bool>,
+    ) -> Result<(), Error> {
+        let current = match self.needs_current_state(power, nightlight)? {
+            true => Some(self.get_state_async().await?),
+            false => None,
+        };
+
+        let payload = self.update_payload(power, nightlight, current)?;
+        self.send_command_async(&payload).await?;
+
+        return Ok(());
+    }
+}
+
+/// Reads a 0 / 1 flag from the JSON state of an SP4 plug.
+fn json_flag(state: &Value, key: &str) -> Result<bool, Error> {
+    return match state.get(key).and_then(Value::as_u64) {
+        Some(value) => Ok(value != 0),
+        None => Err(Error::InvalidResponse(format!(
+            "Plug state is missing {:?}",
+            key
+        ))),
+    };
+}
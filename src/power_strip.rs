@@ -0,0 +1,153 @@
+use std::{net::Ipv4Addr, sync::Arc};
+
+use phf::phf_map;
+
+use crate::{
+    constants, lookup_model,
+    network::{util::reverse_mac, DiscoveryResponse, PowerStripDataMessage, UdpTransport},
+    traits::Transport,
+    Device, DeviceInfo, Error, RetryPolicy,
+};
+
+/// A mapping of power strip device codes to their friendly model equivalent.
+pub const POWER_STRIP_CODES: phf::Map<u16, &'static str> = phf_map! {
+    0x4EB5u16 => "MP1-1K4S",
+    0x4EF7u16 => "MP1-1K4S",
+    0x4F1Bu16 => "MP1-1K3S2U",
+    0x4F65u16 => "MP1-1K3S2U",
+};
+
+/// The number of sockets on a power strip.
+pub const POWER_STRIP_SOCKETS: usize = 4;
+
+/// A broadlink MP1 power strip, with individually switchable sockets.
+#[derive(Debug, Clone)]
+pub struct PowerStripDevice {
+    /// Base information about the power strip.
+    pub info: DeviceInfo,
+
+    /// The transport used to communicate with the device.
+    pub transport: Arc<dyn Transport>,
+
+    /// The policy used to retry failed commands sent to the device.
+    pub retry_policy: RetryPolicy,
+}
+
+impl PowerStripDevice {
+    /// Create a new PowerStripDevice.
+    ///
+    /// Note: This should not be called directly. Please use [Device::from_ip] or
+    /// [Device::list] instead.
+    pub fn new(name: &str, addr: Ipv4Addr, response: DiscoveryResponse) -> PowerStripDevice {
+        // Get the type of power strip
+        let friendly_model: String = lookup_model(response.model_code)
+            .map_or("Unknown".into(), |model| model.name)
+            .to_string();
+
+        return Self {
+            info: DeviceInfo {
+                address: addr,
+                mac: reverse_mac(response.mac),
+                model_code: response.model_code,
+                friendly_type: "Power strip".into(),
+                friendly_model,
+                name: name.into(),
+                auth_id: 0, // This will be populated when authenticated.
+                key: constants::INITIAL_KEY,
+                is_locked: response.is_locked,
+                interface: None,
+            },
+            transport: Arc::new(UdpTransport::default()),
+            retry_policy: RetryPolicy::default(),
+        };
+    }
+
+    /// Get whether each of the sockets is switched on, starting with the first socket.
+    pub fn get_state(&mut self) -> Result<[bool; POWER_STRIP_SOCKETS], Error> {
+        let response = self.send_command(PowerStripDataMessage::get_state())?;
+
+        return Ok(sockets_from_mask(PowerStripDataMessage::unpack_state(
+            &response,
+        )?));
+    }
+
+    /// Switch a single socket on or off. Sockets are numbered starting from 1.
+    pub fn set_power(&mut self, socket: u8, power: bool) -> Result<(), Error> {
+        let mask = socket_mask(socket)?;
+        self.send_command(PowerStripDataMessage::set_state(mask, power))?;
+
+        return Ok(());
+    }
+
+    /// Sends a raw message to the power strip.
+    /// Note: Try to avoid using this method in favor of [PowerStripDevice::get_state] and [PowerStripDevice::set_power].
+    pub fn send_command(&mut self, msg: PowerStripDataMessage) -> Result<Vec<u8>, Error> {
+        let packed = msg.pack_with_checksum()?;
+
+        // We cast this object to a generic device in order to make use of the shared
+        // helper utilities.
+        return Device::PowerStrip {
+            strip: self.clone(),
+        }
+        .send_command_for::<PowerStripDataMessage>(&mut self.info, &packed);
+    }
+}
+
+/// Async versions of the power strip operations, for use within a tokio runtime.
+#[cfg(feature = "tokio")]
+impl PowerStripDevice {
+    /// Async version of [PowerStripDevice::get_state].
+    pub async fn get_state_async(&mut self) -> Result<[bool; POWER_STRIP_SOCKETS], Error> {
+        let response = self
+            .send_command_async(PowerStripDataMessage::get_state())
+            .await?;
+
+        return Ok(sockets_from_mask(PowerStripDataMessage::unpack_state(
+            &response,
+        )?));
+    }
+
+    /// Async version of [PowerStripDevice::set_power].
+    pub async fn set_power_async(&mut self, socket: u8, power: bool) -> Result<(), Error> {
+        let mask = socket_mask(socket)?;
+        self.send_command_async(PowerStripDataMessage::set_state(mask, power))
+            .await?;
+
+        return Ok(());
+    }
+
+    /// Async version of [PowerStripDevice::send_command].
+    pub async fn send_command_async(
+        &mut self,
+        msg: PowerStripDataMessage,
+    ) -> Result<Vec<u8>, Error> {
+        let packed = msg.pack_with_checksum()?;
+        return Device::PowerStrip {
+            strip: self.clone(),
+        }
+        .send_command_for_async::<PowerStripDataMessage>(&mut self.info, &packed)
+        .await;
+    }
+}
+
+/// Get the bit mask selecting the specified (1-based) socket.
+fn socket_mask(socket: u8) -> Result<u8, Error> {
+    if socket < 1 || usize::from(socket) > POWER_STRIP_SOCKETS {
+        return Err(Error::InvalidArgument(format!(
+            "Socket must be between 1 and {}, got {}",
+            POWER_STRIP_SOCKETS, socket
+        )));
+    }
+
+    return Ok(1 << (socket - 1));
+}
+
+/// Splits the state mask reported by the power strip into the state of each socket.
+fn sockets_from_mask(mask: u8) -> [bool; POWER_STRIP_SOCKETS] {
+    let mut sockets = [false; POWER_STRIP_SOCKETS];
+    for (i, socket) in sockets.iter_mut().enumerate() {
+        *socket = mask & (1 << i) != 0;
+    }
+
+    return sockets;
+}
@@ -1,30 +1,112 @@
-use std::{net::Ipv4Addr, time::Duration};
+use std::{net::Ipv4Addr, sync::Arc, time::Duration};
 
 use phf::phf_map;
 
 use crate::{
-    constants,
-    network::{util::reverse_mac, DiscoveryResponse, RemoteDataCommand, RemoteDataMessage},
-    Device, DeviceInfo,
+    constants, lookup_model,
+    network::{
+        util::reverse_mac, DiscoveryResponse, RemoteDataCommand, RemoteDataMessage,
+        RemoteSensorReadings, UdpTransport,
+    },
+    traits::Transport,
+    Device, DeviceFamily, DeviceInfo, Error, RetryPolicy,
 };
 
-/// A mapping of remote device codes to their friendly model equivalent.
-pub const REMOTE_CODES: phf::Map<u16, &'static str> = phf_map! {
-    0x520Bu16 => "RM4 Pro",
-    0x5213u16 => "RM4 Pro",
-    0x5218u16 => "RM4C Pro",
-    0x6026u16 => "RM4 Pro",
-    0x6184u16 => "RMC4 Pro",
-    0x61A2u16 => "RM4 Pro",
-    0x649Bu16 => "RM4 Pro",
-    0x653Cu16 => "RM4 Pro",
+/// The generation of a remote, which decides how its commands are framed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteGeneration {
+    /// RM2 generation remotes (e.g. RM mini 3, RM pro+), which expect a 4 byte command.
+    Legacy,
+
+    /// RM4 generation remotes, which expect the command to be prefixed with its length.
+    Rm4,
+}
+
+/// Describes a model of remote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteModel {
+    /// The friendly name of the model.
+    pub name: &'static str,
+
+    /// The generation of the model.
+    pub generation: RemoteGeneration,
+
+    /// Whether the model can report temperature and humidity (e.g. using an HTS2 cable).
+    pub has_sensors: bool,
+}
+
+/// A mapping of remote device codes to their model.
+pub const REMOTE_CODES: phf::Map<u16, RemoteModel> = phf_map! {
+    0x2712u16 => RemoteModel { name: "RM pro/pro+", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x272Au16 => RemoteModel { name: "RM pro", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x2737u16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x273Du16 => RemoteModel { name: "RM pro", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x277Cu16 => RemoteModel { name: "RM home", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x2783u16 => RemoteModel { name: "RM home", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x2787u16 => RemoteModel { name: "RM pro", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x278Bu16 => RemoteModel { name: "RM plus", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x278Fu16 => RemoteModel { name: "RM mini", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x2797u16 => RemoteModel { name: "RM pro+", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x279Du16 => RemoteModel { name: "RM pro+", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27A1u16 => RemoteModel { name: "RM plus", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27A6u16 => RemoteModel { name: "RM plus", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27A9u16 => RemoteModel { name: "RM pro+", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27C2u16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27C3u16 => RemoteModel { name: "RM pro+", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27C7u16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27CCu16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27CDu16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27D0u16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27D1u16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27D3u16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27DCu16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x27DEu16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Legacy, has_sensors: false },
+    0x51DAu16 => RemoteModel { name: "RM4 mini", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x5209u16 => RemoteModel { name: "RM4 TV mate", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x520Bu16 => RemoteModel { name: "RM4 Pro", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x520Cu16 => RemoteModel { name: "RM4 mini", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x520Du16 => RemoteModel { name: "RM4C mini", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x5211u16 => RemoteModel { name: "RM4C mate", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x5212u16 => RemoteModel { name: "RM4 TV mate", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x5213u16 => RemoteModel { name: "RM4 Pro", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x5216u16 => RemoteModel { name: "RM4 mini", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x5218u16 => RemoteModel { name: "RM4C Pro", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x521Cu16 => RemoteModel { name: "RM4 mini", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x5F36u16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x6026u16 => RemoteModel { name: "RM4 Pro", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x6070u16 => RemoteModel { name: "RM4C mini", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x610Eu16 => RemoteModel { name: "RM4 mini", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x610Fu16 => RemoteModel { name: "RM4C mini", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x6184u16 => RemoteModel { name: "RMC4 Pro", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x61A2u16 => RemoteModel { name: "RM4 Pro", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x62BCu16 => RemoteModel { name: "RM4 mini", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x62BEu16 => RemoteModel { name: "RM4C mini", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x6364u16 => RemoteModel { name: "RM4S", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x648Du16 => RemoteModel { name: "RM4 mini", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x649Bu16 => RemoteModel { name: "RM4 Pro", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x6508u16 => RemoteModel { name: "RM mini 3", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x6539u16 => RemoteModel { name: "RM4C mini", generation: RemoteGeneration::Rm4, has_sensors: true },
+    0x653Au16 => RemoteModel { name: "RM4 mini", generation: RemoteGeneration::Rm4, has_sensors: false },
+    0x653Cu16 => RemoteModel { name: "RM4 Pro", generation: RemoteGeneration::Rm4, has_sensors: true },
 };
 
+/// How many times to poll the device while learning a code.
+const LEARNING_ATTEMPTS: usize = 10;
+
+/// How long to wait between polls while learning a code.
+const LEARNING_INTERVAL: Duration = Duration::from_secs(3);
+
 /// A broadlink device capable of transmitting IR / RF codes.
 #[derive(Debug, Clone)]
 pub struct RemoteDevice {
     /// Base information about the remote.
     pub info: DeviceInfo,
+
+    /// The transport used to communicate with the device.
+    pub transport: Arc<dyn Transport>,
+
+    /// The policy used to retry failed commands sent to the device.
+    pub retry_policy: RetryPolicy,
 }
 
 impl RemoteDevice {
@@ -34,9 +116,8 @@ impl RemoteDevice {
     /// [Device::list] instead.
     pub fn new(name: &str, addr: Ipv4Addr, response: DiscoveryResponse) -> RemoteDevice {
         // Get the type of remote
-        let friendly_model: String = REMOTE_CODES
-            .get(&response.model_code)
-            .unwrap_or(&"Unknown")
+        let friendly_model: String = lookup_model(response.model_code)
+            .map_or("Unknown".into(), |model| model.name)
             .to_string();
 
         return Self {
@@ -45,12 +126,15 @@ impl RemoteDevice {
                 mac: reverse_mac(response.mac),
                 model_code: response.model_code,
                 friendly_type: "Remote".into(),
-                friendly_model: friendly_model,
+                friendly_model,
                 name: name.into(),
                 auth_id: 0, // This will be populated when authenticated.
                 key: constants::INITIAL_KEY,
                 is_locked: response.is_locked,
+                interface: None,
             },
+            transport: Arc::new(UdpTransport::default()),
+            retry_policy: RetryPolicy::default(),
         };
     }
 
@@ -59,28 +143,23 @@ impl RemoteDevice {
     /// When learning, the remote's LED will light up orange. Simply long press
     /// (and release) the IR button while pointing the control at the device until the light
     /// turns off.
-    pub fn learn_ir(&self) -> Result<Vec<u8>, String> {
+    pub fn learn_ir(&mut self) -> Result<Vec<u8>, Error> {
         // First enter learning...
-        self.send_command(&[], RemoteDataCommand::StartLearningIR)
-            .map_err(|e| format!("Could not enter learning mode! {}", e))?;
+        self.send_command(&[], RemoteDataCommand::StartLearningIR)?;
 
         // Block until we learn the code or timeout
-        let attempts = 10;
-        let interval = Duration::from_secs(3);
-        for _ in 0..attempts {
+        for _ in 0..LEARNING_ATTEMPTS {
             // Sleep before trying again
-            std::thread::sleep(interval);
+            std::thread::sleep(LEARNING_INTERVAL);
 
-            let code: Vec<u8> = self
-                .send_command(&[], RemoteDataCommand::GetCode)
-                .map_err(|e| format!("Could not check code status of device! {}", e))?;
-            if code.len() != 0 {
+            let code = code_or_empty(self.send_command(&[], RemoteDataCommand::GetCode))?;
+            if !code.is_empty() {
                 return Ok(code);
             }
         }
 
         // If we haven't gotten anything up until now, then we failed
-        return Err("Could not learn IR code! Operation timed out.".into());
+        return Err(Error::Timeout);
     }
 
     /// Attempts to learn an RF code.
@@ -94,23 +173,18 @@ impl RemoteDevice {
     ///   2) Long press (and release) the RF button until the orange LED turns off
     ///      and then back on.
     ///   3) Press the RF button once more normally until the orange LED turns off.
-    pub fn learn_rf(&self) -> Result<Vec<u8>, String> {
+    pub fn learn_rf(&mut self) -> Result<Vec<u8>, Error> {
         // Start sweeping for the type of frequency in use
-        self.send_command(&[], RemoteDataCommand::SweepRfFrequencies)
-            .map_err(|e| format!("Could not start sweeping frequencies! {}", e))?;
+        self.send_command(&[], RemoteDataCommand::SweepRfFrequencies)?;
 
         // Wait for the frequency to be identified
-        let attempts = 10;
-        let interval = Duration::from_secs(3);
         let mut frequency_found = false;
-        for _ in 0..attempts {
+        for _ in 0..LEARNING_ATTEMPTS {
             // Sleep before trying again
-            std::thread::sleep(interval);
+            std::thread::sleep(LEARNING_INTERVAL);
 
-            let frequency: Vec<u8> = self
-                .send_command(&[], RemoteDataCommand::CheckFrequency)
-                .map_err(|e| format!("Could not check code status of device! {}", e))?;
-            if frequency[0] == 1 {
+            let frequency: Vec<u8> = self.send_command(&[], RemoteDataCommand::CheckFrequency)?;
+            if frequency.first() == Some(&1) {
                 frequency_found = true;
                 break;
             }
@@ -118,65 +192,236 @@ impl RemoteDevice {
 
         // Error out if no frequency is found
         if !frequency_found {
-            self.send_command(&[], RemoteDataCommand::StopRfSweep)
-                .map_err(|e| format!("Could not cancel RF sweep! {}", e))?;
-            return Err("Could not determine frequency!".into());
+            self.send_command(&[], RemoteDataCommand::StopRfSweep)?;
+            return Err(Error::Timeout);
         }
 
         // Enter RF learning mode
-        self.send_command(&[], RemoteDataCommand::StartLearningRF)
-            .map_err(|e| format!("Could not enter learning mode! {}", e))?;
+        self.send_command(&[], RemoteDataCommand::StartLearningRF)?;
 
         // Block until we learn the code or timeout
-        for _ in 0..attempts {
+        for _ in 0..LEARNING_ATTEMPTS {
             // Sleep before trying again
-            std::thread::sleep(interval);
+            std::thread::sleep(LEARNING_INTERVAL);
 
-            let code: Vec<u8> = self
-                .send_command(&[], RemoteDataCommand::GetCode)
-                .map_err(|e| format!("Could not check code status of device! {}", e))?;
-            if code.len() != 0 {
+            let code = code_or_empty(self.send_command(&[], RemoteDataCommand::GetCode))?;
+            if !code.is_empty() {
                 return Ok(code);
             }
         }
 
         // If we haven't gotten anything up until now, then we failed
-        self.send_command(&[], RemoteDataCommand::StopRfSweep)
-            .map_err(|e| format!("Could not cancel RF sweep! {}", e))?;
-        return Err("Could not learn RF code! Operation timed out.".into());
+        self.send_command(&[], RemoteDataCommand::StopRfSweep)?;
+        return Err(Error::Timeout);
     }
 
     /// Sends an IR/RF code to the world.
-    pub fn send_code(&self, code: &[u8]) -> Result<(), String> {
-        self.send_command(code, RemoteDataCommand::SendCode)
-            .map_err(|e| format!("Could not send IR code to device! {}", e))?;
+    pub fn send_code(&mut self, code: &[u8]) -> Result<(), Error> {
+        self.send_command(code, RemoteDataCommand::SendCode)?;
 
         return Ok(());
     }
 
+    /// Get the temperature and humidity readings of the remote.
+    ///
+    /// Only models with a sensor (e.g. using an HTS2 cable) support this, as listed in [REMOTE_CODES].
+    pub fn check_sensors(&mut self) -> Result<RemoteSensorReadings, Error> {
+        self.ensure_sensors()?;
+        let data = self.send_command(&[], RemoteDataCommand::CheckSensors)?;
+
+        return RemoteSensorReadings::unpack_response(&data);
+    }
+
+    /// Returns an error if the model of this remote has no temperature / humidity sensor.
+    fn ensure_sensors(&self) -> Result<(), Error> {
+        return match lookup_model(self.info.model_code).map(|model| model.family) {
+            Some(DeviceFamily::Remote {
+                has_sensors: true, ..
+            }) => Ok(()),
+            _ => Err(Error::Unsupported(format!(
+                "{} ({:#06X}) has no temperature / humidity sensor",
+                self.info.friendly_model, self.info.model_code
+            ))),
+        };
+    }
+
+    /// Get the generation of this remote. Unknown models are assumed to be RM4 remotes.
+    pub fn generation(&self) -> RemoteGeneration {
+        return match lookup_model(self.info.model_code).map(|model| model.family) {
+            Some(DeviceFamily::Remote { generation, .. }) => generation,
+            _ => RemoteGeneration::Rm4,
+        };
+    }
+
     /// Sends a raw command to the remote.
     /// Note: Try to avoid using this method in favor of [RemoteDevice::send_code], [RemoteDevice::learn_ir], etc.
     pub fn send_command(
-        &self,
+        &mut self,
         payload: &[u8],
         command: RemoteDataCommand,
-    ) -> Result<Vec<u8>, String> {
+    ) -> Result<Vec<u8>, Error> {
+        // Construct the data message
+        let generation = self.generation();
+        let packed = pack_command(generation, command, payload)?;
+
         // We cast this object to a generic device in order to make use of the shared
         // helper utilities.
-        let generic_device = Device::Remote {
+        let response = Device::Remote {
             remote: self.clone(),
-        };
+        }
+        .send_command_for::<RemoteDataMessage>(&mut self.info, &packed)?;
+
+        return unpack_response(generation, &response);
+    }
+}
+
+/// Async versions of the remote operations, for use within a tokio runtime.
+#[cfg(feature = "tokio")]
+impl RemoteDevice {
+    /// Async version of [RemoteDevice::learn_ir].
+    pub async fn learn_ir_async(&mut self) -> Result<Vec<u8>, Error> {
+        // First enter learning...
+        self.send_command_async(&[], RemoteDataCommand::StartLearningIR)
+            .await?;
+
+        // Wait until we learn the code or timeout
+        for _ in 0..LEARNING_ATTEMPTS {
+            // Sleep before trying again
+            tokio::time::sleep(LEARNING_INTERVAL).await;
+
+            let code = code_or_empty(
+                self.send_command_async(&[], RemoteDataCommand::GetCode)
+                    .await,
+            )?;
+            if !code.is_empty() {
+                return Ok(code);
+            }
+        }
+
+        // If we haven't gotten anything up until now, then we failed
+        return Err(Error::Timeout);
+    }
+
+    /// Async version of [RemoteDevice::learn_rf].
+    pub async fn learn_rf_async(&mut self) -> Result<Vec<u8>, Error> {
+        // Start sweeping for the type of frequency in use
+        self.send_command_async(&[], RemoteDataCommand::SweepRfFrequencies)
+            .await?;
 
+        // Wait for the frequency to be identified
+        let mut frequency_found = false;
+        for _ in 0..LEARNING_ATTEMPTS {
+            // Sleep before trying again
+            tokio::time::sleep(LEARNING_INTERVAL).await;
+
+            let frequency: Vec<u8> = self
+                .send_command_async(&[], RemoteDataCommand::CheckFrequency)
+                .await?;
+            if frequency.first() == Some(&1) {
+                frequency_found = true;
+                break;
+            }
+        }
+
+        // Error out if no frequency is found
+        if !frequency_found {
+            self.send_command_async(&[], RemoteDataCommand::StopRfSweep)
+                .await?;
+            return Err(Error::Timeout);
+        }
+
+        // Enter RF learning mode
+        self.send_command_async(&[], RemoteDataCommand::StartLearningRF)
+            .await?;
+
+        // Wait until we learn the code or timeout
+        for _ in 0..LEARNING_ATTEMPTS {
+            // Sleep before trying again
+            tokio::time::sleep(LEARNING_INTERVAL).await;
+
+            let code = code_or_empty(
+                self.send_command_async(&[], RemoteDataCommand::GetCode)
+                    .await,
+            )?;
+            if !code.is_empty() {
+                return Ok(code);
+            }
+        }
+
+        // If we haven't gotten anything up until now, then we failed
+        self.send_command_async(&[], RemoteDataCommand::StopRfSweep)
+            .await?;
+        return Err(Error::Timeout);
+    }
+
+    /// Async version of [RemoteDevice::send_code].
+    pub async fn send_code_async(&mut self, code: &[u8]) -> Result<(), Error> {
+        self.send_command_async(code, RemoteDataCommand::SendCode)
+            .await?;
+
+        return Ok(());
+    }
+
+    /// Async version of [RemoteDevice::check_sensors].
+    pub async fn check_sensors_async(&mut self) -> Result<RemoteSensorReadings, Error> {
+        self.ensure_sensors()?;
+        let data = self
+            .send_command_async(&[], RemoteDataCommand::CheckSensors)
+            .await?;
+
+        return RemoteSensorReadings::unpack_response(&data);
+    }
+
+    /// Async version of [RemoteDevice::send_command].
+    pub async fn send_command_async(
+        &mut self,
+        payload: &[u8],
+        command: RemoteDataCommand,
+    ) -> Result<Vec<u8>, Error> {
         // Construct the data message
-        let msg = RemoteDataMessage::new(command);
-        let packed = msg
-            .pack_with_payload(&payload)
-            .map_err(|e| format!("Could not pack remote data message! {}", e))?;
+        let generation = self.generation();
+        let packed = pack_command(generation, command, payload)?;
 
-        let response = generic_device
-            .send_command::<RemoteDataMessage>(&packed)
-            .map_err(|e| format!("Could not send code inside of the command! {}", e))?;
+        let response = Device::Remote {
+            remote: self.clone(),
+        }
+        .send_command_for_async::<RemoteDataMessage>(&mut self.info, &packed)
+        .await?;
 
-        return RemoteDataMessage::unpack_with_payload(&response);
+        return unpack_response(generation, &response);
     }
 }
+
+/// Packs a command using the framing expected by the given generation of remote.
+pub(crate) fn pack_command(
+    generation: RemoteGeneration,
+    command: RemoteDataCommand,
+    payload: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let msg = RemoteDataMessage::new(command);
+
+    return match generation {
+        RemoteGeneration::Legacy => msg.pack_legacy_with_payload(payload),
+        RemoteGeneration::Rm4 => msg.pack_with_payload(payload),
+    };
+}
+
+/// Unpacks the payload of a response using the framing of the given generation of remote.
+pub(crate) fn unpack_response(
+    generation: RemoteGeneration,
+    bytes: &[u8],
+) -> Result<Vec<u8>, Error> {
+    return match generation {
+        RemoteGeneration::Legacy => RemoteDataMessage::unpack_legacy_with_payload(bytes),
+        RemoteGeneration::Rm4 => RemoteDataMessage::unpack_with_payload(bytes),
+    };
+}
+
+/// Treats the errors reported by the device while no code has been learned as an empty code.
+fn code_or_empty(response: Result<Vec<u8>, Error>) -> Result<Vec<u8>, Error> {
+    return match response {
+        // The device reports a storage / read error until a code has been learned.
+        Err(Error::Device { code: -5 }) | Err(Error::Device { code: -10 }) => Ok(vec![]),
+        result => result,
+    };
+}
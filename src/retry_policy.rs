@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use crate::Error;
+
+/// Decides how commands sent to a device are retried when they fail.
+///
+/// Only transient failures, such as timeouts and socket errors, are retried. If enabled, a device
+/// which no longer accepts the session (e.g. after a reboot) is re-authenticated once before
+/// sending the command again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// How many times to send a command before giving up. Values below 1 are treated as 1.
+    pub attempts: u32,
+
+    /// How long to wait before the first retry. The delay doubles with every subsequent retry.
+    pub backoff: Duration,
+
+    /// Whether to re-authenticate with the device when it no longer accepts the session.
+    pub reauthenticate: bool,
+}
+
+impl RetryPolicy {
+    /// A policy which sends every command exactly once.
+    pub fn never() -> RetryPolicy {
+        return RetryPolicy {
+            attempts: 1,
+            backoff: Duration::ZERO,
+            reauthenticate: false,
+        };
+    }
+
+    /// Returns true if a command which failed with the specified error should be sent again.
+    pub fn should_retry(&self, error: &Error) -> bool {
+        return matches!(error, Error::Timeout | Error::Io(_));
+    }
+
+    /// Get how long to wait after the specified (1-based) failed attempt.
+    pub fn backoff_for(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+
+        return self.backoff.saturating_mul(factor);
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        return RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_millis(250),
+            reauthenticate: true,
+        };
+    }
+}
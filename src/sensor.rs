@@ -0,0 +1,108 @@
+use std::{net::Ipv4Addr, sync::Arc};
+
+use packed_struct::PackedStruct;
+use phf::phf_map;
+
+use crate::{
+    constants, lookup_model,
+    network::{
+        util::reverse_mac, DiscoveryResponse, SensorDataCommand, SensorDataMessage, SensorReadings,
+        UdpTransport,
+    },
+    traits::Transport,
+    Device, DeviceInfo, Error, RetryPolicy,
+};
+
+/// A mapping of environmental sensor device codes to their friendly model equivalent.
+pub const SENSOR_CODES: phf::Map<u16, &'static str> = phf_map! {
+    0x2714u16 => "A1 e-Sensor",
+};
+
+/// A broadlink environmental sensor, such as the A1.
+#[derive(Debug, Clone)]
+pub struct SensorDevice {
+    /// Base information about the sensor.
+    pub info: DeviceInfo,
+
+    /// The transport used to communicate with the device.
+    pub transport: Arc<dyn Transport>,
+
+    /// The policy used to retry failed commands sent to the device.
+    pub retry_policy: RetryPolicy,
+}
+
+impl SensorDevice {
+    /// Create a new SensorDevice.
+    ///
+    /// Note: This should not be called directly. Please use [Device::from_ip] or
+    /// [Device::list] instead.
+    pub fn new(name: &str, addr: Ipv4Addr, response: DiscoveryResponse) -> SensorDevice {
+        // Get the type of sensor
+        let friendly_model: String = lookup_model(response.model_code)
+            .map_or("Unknown".into(), |model| model.name)
+            .to_string();
+
+        return Self {
+            info: DeviceInfo {
+                address: addr,
+                mac: reverse_mac(response.mac),
+                model_code: response.model_code,
+                friendly_type: "Sensor".into(),
+                friendly_model,
+                name: name.into(),
+                auth_id: 0, // This will be populated when authenticated.
+                key: constants::INITIAL_KEY,
+                is_locked: response.is_locked,
+                interface: None,
+            },
+            transport: Arc::new(UdpTransport::default()),
+            retry_policy: RetryPolicy::default(),
+        };
+    }
+
+    /// Get the current temperature, humidity, light, air quality and noise readings.
+    pub fn get_readings(&mut self) -> Result<SensorReadings, Error> {
+        let response = self.send_command(SensorDataCommand::GetReadings)?;
+
+        return SensorReadings::unpack_response(&response);
+    }
+
+    /// Sends a raw command to the sensor.
+    /// Note: Try to avoid using this method in favor of [SensorDevice::get_readings].
+    pub fn send_command(&mut self, command: SensorDataCommand) -> Result<Vec<u8>, Error> {
+        let packed = SensorDataMessage::new(command).pack()?;
+
+        // We cast this object to a generic device in order to make use of the shared
+        // helper utilities.
+        return Device::Sensor {
+            sensor: self.clone(),
+        }
+        .send_command_for::<SensorDataMessage>(&mut self.info, &packed);
+    }
+}
+
+/// Async versions of the sensor operations, for use within a tokio runtime.
+#[cfg(feature = "tokio")]
+impl SensorDevice {
+    /// Async version of [SensorDevice::get_readings].
+    pub async fn get_readings_async(&mut self) -> Result<SensorReadings, Error> {
+        let response = self
+            .send_command_async(SensorDataCommand::GetReadings)
+            .await?;
+
+        return SensorReadings::unpack_response(&response);
+    }
+
+    /// Async version of [SensorDevice::send_command].
+    pub async fn send_command_async(
+        &mut self,
+        command: SensorDataCommand,
+    ) -> Result<Vec<u8>, Error> {
+        let packed = SensorDataMessage::new(command).pack()?;
+        return Device::Sensor {
+            sensor: self.clone(),
+        }
+        .send_command_for_async::<SensorDataMessage>(&mut self.info, &packed)
+        .await;
+    }
+}
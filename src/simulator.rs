@@ -0,0 +1,691 @@
+//! An in-process emulator of a broadlink device, useful for testing without real hardware.
+//!
+//! The simulator binds a local UDP socket and answers discovery, authentication and data
+//! messages on a background thread, based on a configurable [SimulatorState].
+
+use std::{
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use packed_struct::prelude::{PackedStruct, PackedStructSlice, PrimitiveEnum};
+
+use crate::{
+    constants, lookup_model,
+    network::{
+        util::{reverse_mac, unpack_zero_padded},
+        AlarmDataCommand, AlarmDataMessage, AlarmSensor, AuthenticationMessage,
+        AuthenticationResponse, BulbState, CommandMessage, CurtainCommand, CurtainDataMessage,
+        DiscoveryResponse, HvacDataCommand, HvacDataMessage, JsonDataCommand, JsonDataMessage,
+        NetworkConfig, PlugDataCommand, PlugDataMessage, PlugPowerMessage, PowerStripCommand,
+        PowerStripDataMessage, RemoteDataCommand, RemoteDataMessage, SensorDataCommand,
+        SensorDataMessage, ThermostatDataCommand, ThermostatDataMessage, UdpTransport,
+    },
+    remote::{pack_command, unpack_response},
+    traits::CommandTrait,
+    BulbType, DeviceFamily, Error, PlugState, PlugType, RemoteGeneration, POWER_STRIP_SOCKETS,
+};
+
+/// The magic header present on every command message.
+const COMMAND_MAGIC: [u8; 8] = [0x5A, 0xA5, 0xAA, 0x55, 0x5A, 0xA5, 0xAA, 0x55];
+
+/// The error code reported when a command is sent with the wrong session key.
+const ERROR_KEY_EXPIRED: i16 = -7;
+
+/// The error code reported when a command is not supported by the simulated device.
+const ERROR_NOT_SUPPORTED: i16 = -4;
+
+/// The error code reported when no code has been learned yet.
+const ERROR_NO_DATA: i16 = -5;
+
+/// How far the simulated curtain moves, in percent, every time its position is polled.
+const CURTAIN_STEP: u8 = 10;
+
+/// The Modbus exception reported when accessing registers which do not exist.
+const MODBUS_ILLEGAL_ADDRESS: u8 = 0x02;
+
+/// The identity of a simulated device.
+#[derive(Debug, Clone)]
+pub struct SimulatorConfig {
+    /// The address to bind the simulator to. Use port 0 to pick any free port.
+    pub bind_addr: SocketAddr,
+
+    /// The name reported during discovery.
+    pub name: String,
+
+    /// The model code reported during discovery. This decides which data messages are understood.
+    pub model_code: u16,
+
+    /// The MAC address reported during discovery.
+    pub mac: [u8; 6],
+
+    /// The lock status reported during discovery.
+    pub is_locked: bool,
+
+    /// The authentication ID issued to clients.
+    pub auth_id: u32,
+
+    /// The session key issued to clients.
+    pub key: [u8; 16],
+}
+
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        return SimulatorConfig {
+            bind_addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+            name: "Simulator".into(),
+            model_code: 0x649B,
+            mac: [0x02, 0x00, 0x00, 0x00, 0x00, 0x01],
+            is_locked: false,
+            auth_id: 1,
+            key: rand::random(),
+        };
+    }
+}
+
+/// The mutable state of a simulated device.
+#[derive(Debug, Clone)]
+pub struct SimulatorState {
+    /// The authentication ID issued to clients.
+    pub auth_id: u32,
+
+    /// The session key issued to clients. Change this to simulate a device reboot.
+    pub key: [u8; 16],
+
+    /// The code returned when the client asks for a learned code, if any.
+    pub learned_code: Option<Vec<u8>>,
+
+    /// Whether an RF frequency is found when sweeping.
+    pub rf_frequency_found: bool,
+
+    /// Every code blasted through the simulated remote, in order.
+    pub sent_codes: Vec<Vec<u8>>,
+
+    /// The packed [crate::network::RemoteSensorReadings] of the simulated remote.
+    pub remote_sensors: Vec<u8>,
+
+    /// The packed [crate::network::AirCondState] of the simulated HVAC unit.
+    pub hvac_state: Vec<u8>,
+
+    /// The packed [crate::network::AirCondInfo] of the simulated HVAC unit.
+    pub hvac_info: Vec<u8>,
+
+    /// The state of the simulated plug.
+    pub plug_state: PlugState,
+
+    /// The power usage reported by the simulated plug, in W.
+    pub plug_energy: f32,
+
+    /// Whether each socket of the simulated power strip is switched on.
+    pub strip_sockets: [bool; POWER_STRIP_SOCKETS],
+
+    /// The packed [crate::network::SensorReadings] of the simulated environmental sensor.
+    pub sensor_readings: Vec<u8>,
+
+    /// The state of the simulated bulb.
+    pub bulb_state: BulbState,
+
+    /// The holding registers of the simulated thermostat, as read into a
+    /// [crate::network::ThermostatStatus] after its 3 byte Modbus header.
+    pub thermostat_registers: Vec<u8>,
+
+    /// The position of the simulated curtain, in percent, where 0 is closed and 100 is open.
+    pub curtain_position: u8,
+
+    /// The direction the simulated curtain is moving in, if any.
+    pub curtain_moving: Option<CurtainCommand>,
+
+    /// The sensors paired with the simulated alarm hub.
+    pub alarm_sensors: Vec<AlarmSensor>,
+}
+
+/// A simulated broadlink device, answering requests on a background thread.
+///
+/// The simulator stops when dropped.
+pub struct Simulator {
+    /// The identity of this device.
+    config: SimulatorConfig,
+
+    /// The address that the simulator is listening on.
+    address: SocketAddr,
+
+    /// The state shared with the background thread.
+    state: Arc<Mutex<SimulatorState>>,
+
+    /// Set to false to stop the background thread.
+    running: Arc<AtomicBool>,
+
+    /// The background thread answering requests.
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Simulator {
+    /// Start a new simulated device.
+    pub fn start(config: SimulatorConfig) -> Result<Simulator, Error> {
+        let socket = UdpSocket::bind(config.bind_addr).map_err(Error::Io)?;
+        socket
+            .set_read_timeout(Some(Duration::from_millis(50)))
+            .map_err(Error::Io)?;
+        let address = socket.local_addr().map_err(Error::Io)?;
+
+        let state = Arc::new(Mutex::new(SimulatorState {
+            auth_id: config.auth_id,
+            key: config.key,
+            learned_code: None,
+            rf_frequency_found: true,
+            sent_codes: vec![],
+            remote_sensors: vec![0; 4],
+            hvac_state: vec![0; 13],
+            hvac_info: vec![0; 22],
+            plug_state: PlugState::default(),
+            plug_energy: 0.0,
+            strip_sockets: [false; POWER_STRIP_SOCKETS],
+            sensor_readings: vec![0; 16],
+            bulb_state: BulbState::default(),
+            thermostat_registers: vec![0; 44],
+            curtain_position: 0,
+            curtain_moving: None,
+            alarm_sensors: vec![],
+        }));
+        let running = Arc::new(AtomicBool::new(true));
+
+        // Answer requests until stopped
+        let thread_config = config.clone();
+        let thread_state = state.clone();
+        let thread_running = running.clone();
+        let handle = std::thread::spawn(move || {
+            let mut recv_buffer = [0u8; 8092];
+            while thread_running.load(Ordering::SeqCst) {
+                let (bytes_received, sender) = match socket.recv_from(&mut recv_buffer) {
+                    Ok(r) => r,
+                    Err(_) => continue,
+                };
+
+                let mut state = lock(&thread_state);
+                let response =
+                    handle_packet(&thread_config, &mut state, &recv_buffer[0..bytes_received]);
+                if let Ok(Some(bytes)) = response {
+                    let _ = socket.send_to(&bytes, sender);
+                }
+            }
+        });
+
+        return Ok(Simulator {
+            config,
+            address,
+            state,
+            running,
+            handle: Some(handle),
+        });
+    }
+
+    /// Get the address that the simulator is listening on.
+    pub fn address(&self) -> SocketAddr {
+        return self.address;
+    }
+
+    /// Get the identity of the simulated device.
+    pub fn config(&self) -> &SimulatorConfig {
+        return &self.config;
+    }
+
+    /// Access the state of the simulated device.
+    pub fn state(&self) -> MutexGuard<'_, SimulatorState> {
+        return lock(&self.state);
+    }
+
+    /// Get a [UdpTransport] which talks to this simulator.
+    ///
+    /// Discovery responses are received on any free port, so that several simulators can be
+    /// used concurrently.
+    pub fn transport(&self) -> UdpTransport {
+        return UdpTransport::with_config(NetworkConfig {
+            bind_addr: Ipv4Addr::LOCALHOST,
+            discovery_port: 0,
+            device_port: self.address.port(),
+            ..Default::default()
+        });
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Locks the shared state, ignoring poisoning from a panicked test.
+fn lock(state: &Mutex<SimulatorState>) -> MutexGuard<'_, SimulatorState> {
+    return state.lock().unwrap_or_else(|e| e.into_inner());
+}
+
+/// Handles a single received packet, returning the response to send back, if any.
+fn handle_packet(
+    config: &SimulatorConfig,
+    state: &mut SimulatorState,
+    bytes: &[u8],
+) -> Result<Option<Vec<u8>>, Error> {
+    // Discovery messages are the only ones not wrapped in a command
+    if !bytes.starts_with(&COMMAND_MAGIC) {
+        if bytes.len() != 48 {
+            return Ok(None);
+        }
+
+        let mut name = [0u8; 62];
+        let name_len = config.name.len().min(name.len());
+        name[..name_len].copy_from_slice(&config.name.as_bytes()[..name_len]);
+
+        let response = DiscoveryResponse {
+            model_code: config.model_code,
+            mac: reverse_mac(config.mac),
+            name,
+            is_locked: config.is_locked,
+        };
+
+        return Ok(Some(response.pack()?.to_vec()));
+    }
+
+    if bytes.len() < 0x38 {
+        return Ok(None);
+    }
+    let header = CommandMessage::unpack_from_slice(&bytes[0..0x38])?;
+
+    // Authentication requests are encrypted using the initial key
+    if header.packet_type == AuthenticationMessage::packet_type() {
+        CommandMessage::unpack_with_payload(bytes.to_vec(), &constants::INITIAL_KEY)?;
+
+        let auth = AuthenticationResponse {
+            id: state.auth_id,
+            key: state.key,
+        };
+        let response = create_response::<AuthenticationMessage>(
+            config,
+            0,
+            &auth.pack()?,
+            &constants::INITIAL_KEY,
+        )?;
+
+        return Ok(Some(response));
+    }
+
+    // Everything else must use the issued session key
+    let payload = match CommandMessage::unpack_with_payload(bytes.to_vec(), &state.key) {
+        Ok(p) => p,
+        Err(_) => {
+            let response =
+                create_response::<RemoteDataMessage>(config, ERROR_KEY_EXPIRED, &[], &state.key)?;
+            return Ok(Some(response));
+        }
+    };
+
+    let family = lookup_model(config.model_code).map(|model| model.family);
+    let result = match family {
+        // SP1 plugs are switched using their own packet type
+        Some(DeviceFamily::Plug(PlugType::Sp1))
+            if header.packet_type == PlugPowerMessage::packet_type() =>
+        {
+            handle_plug_power(state, &payload)?
+        }
+        _ if header.packet_type != RemoteDataMessage::packet_type() => Err(ERROR_NOT_SUPPORTED),
+        Some(DeviceFamily::Remote { generation, .. }) => {
+            handle_remote_data(generation, state, &payload)?
+        }
+        Some(DeviceFamily::Hvac) => handle_hvac_data(state, &payload)?,
+        Some(DeviceFamily::Plug(plug_type)) => handle_plug_data(plug_type, state, &payload)?,
+        Some(DeviceFamily::PowerStrip) => handle_power_strip_data(state, &payload)?,
+        Some(DeviceFamily::Sensor) => handle_sensor_data(state, &payload)?,
+        Some(DeviceFamily::Bulb(bulb_type)) => handle_bulb_data(bulb_type, state, &payload)?,
+        Some(DeviceFamily::Thermostat) => handle_thermostat_data(state, &payload)?,
+        Some(DeviceFamily::Curtain) => handle_curtain_data(state, &payload)?,
+        Some(DeviceFamily::Alarm) => handle_alarm_data(state, &payload)?,
+        Some(DeviceFamily::Generic) | None => Err(ERROR_NOT_SUPPORTED),
+    };
+
+    let response = match result {
+        Ok(data) => create_response::<RemoteDataMessage>(config, 0, &data, &state.key)?,
+        Err(code) => create_response::<RemoteDataMessage>(config, code, &[], &state.key)?,
+    };
+
+    return Ok(Some(response));
+}
+
+/// Handles a [RemoteDataMessage], returning the packed response or a device error code.
+fn handle_remote_data(
+    generation: RemoteGeneration,
+    state: &mut SimulatorState,
+    payload: &[u8],
+) -> Result<Result<Vec<u8>, i16>, Error> {
+    // Trailing zeros of the header are stripped when decrypting, so restore them here.
+    let header_size = match generation {
+        RemoteGeneration::Legacy => 0x04,
+        RemoteGeneration::Rm4 => 0x06,
+    };
+    let mut payload = payload.to_vec();
+    if payload.len() < header_size {
+        payload.resize(header_size, 0);
+    }
+
+    let command = match generation {
+        RemoteGeneration::Legacy => RemoteDataCommand::from_primitive(payload[0]),
+        RemoteGeneration::Rm4 => {
+            Some(RemoteDataMessage::unpack_from_slice(&payload[0..0x06])?.command)
+        }
+    };
+    let command = match command {
+        Some(c) => c,
+        None => return Ok(Err(ERROR_NOT_SUPPORTED)),
+    };
+    let data = unpack_response(generation, &payload)?;
+
+    let response: Vec<u8> = match command {
+        RemoteDataCommand::SendCode => {
+            state.sent_codes.push(data);
+            vec![]
+        }
+        RemoteDataCommand::GetCode => match &state.learned_code {
+            Some(code) => code.clone(),
+            None => return Ok(Err(ERROR_NO_DATA)),
+        },
+        RemoteDataCommand::CheckFrequency => vec![state.rf_frequency_found as u8],
+        RemoteDataCommand::CheckSensors => state.remote_sensors.clone(),
+        RemoteDataCommand::StartLearningIR
+        | RemoteDataCommand::StartLearningRF
+        | RemoteDataCommand::SweepRfFrequencies
+        | RemoteDataCommand::StopRfSweep => vec![],
+    };
+
+    return Ok(Ok(pack_command(generation, command, &response)?));
+}
+
+/// Handles a [HvacDataMessage], returning the packed response or a device error code.
+fn handle_hvac_data(
+    state: &mut SimulatorState,
+    payload: &[u8],
+) -> Result<Result<Vec<u8>, i16>, Error> {
+    if payload.len() < 12 {
+        return Ok(Err(ERROR_NOT_SUPPORTED));
+    }
+
+    let header = HvacDataMessage::unpack_from_slice(&payload[0..12])?;
+    let data = HvacDataMessage::unpack_with_payload(payload)?;
+
+    // The command is encoded in the upper nibble of the low byte
+    let command = match HvacDataCommand::from_primitive(((header.command & 0xFF) >> 4) as u8) {
+        Some(c) => c,
+        None => return Ok(Err(ERROR_NOT_SUPPORTED)),
+    };
+
+    let response = match command {
+        HvacDataCommand::GetState => state.hvac_state.clone(),
+        HvacDataCommand::GetAcInfo => state.hvac_info.clone(),
+        HvacDataCommand::SetState => {
+            state.hvac_state = data;
+            vec![]
+        }
+    };
+
+    return Ok(Ok(
+        HvacDataMessage::new(command).pack_with_payload(&response)?
+    ));
+}
+
+/// Handles a message sent to a plug, returning the packed response or a device error code.
+fn handle_plug_data(
+    plug_type: PlugType,
+    state: &mut SimulatorState,
+    payload: &[u8],
+) -> Result<Result<Vec<u8>, i16>, Error> {
+    if let PlugType::Sp4 | PlugType::Sp4b = plug_type {
+        return handle_plug_json(plug_type, state, payload);
+    }
+
+    let request = PlugDataMessage::unpack_response(payload)?;
+    let value = match (request.command, plug_type) {
+        (PlugDataCommand::SetState, PlugType::Sp2 | PlugType::Sp2s | PlugType::Sp3) => {
+            state.plug_state = PlugState {
+                power: request.value() & 0x01 != 0,
+                nightlight: request.value() & 0x02 != 0,
+            };
+            request.value()
+        }
+        (PlugDataCommand::GetState, PlugType::Sp2 | PlugType::Sp2s | PlugType::Sp3) => {
+            u32::from(state.plug_state.nightlight) << 1 | u32::from(state.plug_state.power)
+        }
+        (PlugDataCommand::GetEnergy, PlugType::Sp2s) => (state.plug_energy * 1000.0) as u32,
+        _ => return Ok(Err(ERROR_NOT_SUPPORTED)),
+    };
+
+    return Ok(Ok(PlugDataMessage::new(request.command, value)
+        .pack()?
+        .to_vec()));
+}
+
+/// Handles a [PlugPowerMessage] sent to an SP1 plug, returning the packed response or a device error code.
+fn handle_plug_power(
+    state: &mut SimulatorState,
+    payload: &[u8],
+) -> Result<Result<Vec<u8>, i16>, Error> {
+    let request: PlugPowerMessage = unpack_zero_padded(payload)?;
+    state.plug_state.power = request.power != 0;
+
+    return Ok(Ok(request.pack()?.to_vec()));
+}
+
+/// Handles a [JsonDataMessage] sent to an SP4 plug, returning the packed response or a device error code.
+fn handle_plug_json(
+    plug_type: PlugType,
+    state: &mut SimulatorState,
+    payload: &[u8],
+) -> Result<Result<Vec<u8>, i16>, Error> {
+    let length_prefix = plug_type == PlugType::Sp4b;
+    let json = JsonDataMessage::unpack_with_payload(payload, length_prefix)?;
+    let request: serde_json::Value = serde_json::from_str(&json)
+        .map_err(|e| Error::InvalidArgument(format!("Could not parse plug state! {}", e)))?;
+
+    if let Some(power) = request.get("pwr").and_then(serde_json::Value::as_u64) {
+        state.plug_state.power = power != 0;
+    }
+    if let Some(nightlight) = request.get("ntlight").and_then(serde_json::Value::as_u64) {
+        state.plug_state.nightlight = nightlight != 0;
+    }
+
+    let mut response = serde_json::json!({
+        "pwr": u8::from(state.plug_state.power),
+        "ntlight": u8::from(state.plug_state.nightlight),
+    });
+    if length_prefix {
+        response["power"] = serde_json::json!((state.plug_energy * 1000.0) as u64);
+    }
+
+    return Ok(Ok(JsonDataMessage::new(JsonDataCommand::GetState)
+        .pack_with_payload(&response.to_string(), length_prefix)?));
+}
+
+/// Handles a [PowerStripDataMessage], returning the packed response or a device error code.
+fn handle_power_strip_data(
+    state: &mut SimulatorState,
+    payload: &[u8],
+) -> Result<Result<Vec<u8>, i16>, Error> {
+    let request: PowerStripDataMessage = unpack_zero_padded(payload)?;
+    if request.command == PowerStripCommand::SetState {
+        for (i, socket) in state.strip_sockets.iter_mut().enumerate() {
+            if request.socket_mask & (1 << i) != 0 {
+                *socket = request.state_mask & (1 << i) != 0;
+            }
+        }
+    }
+
+    let mut response = PowerStripDataMessage::get_state();
+    response.state_mask = state
+        .strip_sockets
+        .iter()
+        .enumerate()
+        .map(|(i, &socket)| u8::from(socket) << i)
+        .sum();
+
+    return Ok(Ok(response.pack_with_checksum()?));
+}
+
+/// Handles a [SensorDataMessage], returning the packed response or a device error code.
+fn handle_sensor_data(
+    state: &mut SimulatorState,
+    payload: &[u8],
+) -> Result<Result<Vec<u8>, i16>, Error> {
+    return Ok(match unpack_zero_padded::<SensorDataMessage>(payload) {
+        Ok(SensorDataMessage {
+            command: SensorDataCommand::GetReadings,
+        }) => Ok(state.sensor_readings.clone()),
+        Err(_) => Err(ERROR_NOT_SUPPORTED),
+    });
+}
+
+/// Handles a [JsonDataMessage] sent to a bulb, returning the packed response or a device error code.
+fn handle_bulb_data(
+    bulb_type: BulbType,
+    state: &mut SimulatorState,
+    payload: &[u8],
+) -> Result<Result<Vec<u8>, i16>, Error> {
+    let length_prefix = bulb_type == BulbType::Lb1;
+    let request = BulbState::unpack_message(payload, length_prefix)?;
+
+    // Only the fields which are set in the request are changed
+    let encode = |state: &BulbState| {
+        serde_json::to_value(state)
+            .map_err(|e| Error::InvalidArgument(format!("Could not encode bulb state! {}", e)))
+    };
+    if let (serde_json::Value::Object(mut current), serde_json::Value::Object(changes)) =
+        (encode(&state.bulb_state)?, encode(&request)?)
+    {
+        current.extend(changes);
+        state.bulb_state = serde_json::from_value(serde_json::Value::Object(current))
+            .map_err(|e| Error::InvalidArgument(format!("Could not decode bulb state! {}", e)))?;
+    }
+
+    return Ok(Ok(state
+        .bulb_state
+        .pack_message(JsonDataCommand::GetState, length_prefix)?));
+}
+
+/// Handles a [ThermostatDataMessage], returning the packed response or a device error code.
+fn handle_thermostat_data(
+    state: &mut SimulatorState,
+    payload: &[u8],
+) -> Result<Result<Vec<u8>, i16>, Error> {
+    let frame = ThermostatDataMessage::unpack_with_payload(payload)?;
+    if frame.len() < 0x06 {
+        return Ok(Err(ERROR_NOT_SUPPORTED));
+    }
+
+    let request = ThermostatDataMessage::unpack_from_slice(&frame[0x00..0x04])?;
+    let start = usize::from(request.register) * 2;
+    let registers = &mut state.thermostat_registers;
+
+    // Writes are acknowledged by echoing the request, up to the register count
+    let response = match request.command {
+        ThermostatDataCommand::ReadRegisters => {
+            let count = usize::from(u16::from_be_bytes([frame[4], frame[5]]));
+            match registers.get(start..start + count * 2) {
+                Some(values) => [&[frame[0], frame[1], (count * 2) as u8], values].concat(),
+                None => vec![frame[0], frame[1] | 0x80, MODBUS_ILLEGAL_ADDRESS],
+            }
+        }
+        ThermostatDataCommand::WriteRegister => {
+            // The room temperature shares its register with the target temperature, but is read-only
+            let values = if request.register == 0x01 {
+                &frame[5..6]
+            } else {
+                &frame[4..6]
+            };
+            let offset = start + 2 - values.len();
+            match registers.get_mut(offset..offset + values.len()) {
+                Some(target) => {
+                    target.copy_from_slice(values);
+                    frame.clone()
+                }
+                None => vec![frame[0], frame[1] | 0x80, MODBUS_ILLEGAL_ADDRESS],
+            }
+        }
+        ThermostatDataCommand::WriteRegisters => {
+            let values = frame.get(7..).unwrap_or_default();
+            match registers.get_mut(start..start + values.len()) {
+                Some(target) => {
+                    target.copy_from_slice(values);
+                    frame[0x00..0x06].to_vec()
+                }
+                None => vec![frame[0], frame[1] | 0x80, MODBUS_ILLEGAL_ADDRESS],
+            }
+        }
+    };
+
+    return Ok(Ok(ThermostatDataMessage::pack_frame(&response)?));
+}
+
+/// Handles a [CurtainDataMessage], returning the packed response or a device error code.
+fn handle_curtain_data(
+    state: &mut SimulatorState,
+    payload: &[u8],
+) -> Result<Result<Vec<u8>, i16>, Error> {
+    let request = match CurtainDataMessage::unpack_response(payload) {
+        Ok(r) => r,
+        Err(_) => return Ok(Err(ERROR_NOT_SUPPORTED)),
+    };
+
+    match request.command {
+        CurtainCommand::Open | CurtainCommand::Close => {
+            state.curtain_moving = Some(request.command);
+        }
+        CurtainCommand::Stop => state.curtain_moving = None,
+        // The curtain moves a step closer to its end every time it is polled
+        CurtainCommand::GetPosition => {
+            state.curtain_position = match state.curtain_moving {
+                Some(CurtainCommand::Open) => {
+                    state.curtain_position.saturating_add(CURTAIN_STEP).min(100)
+                }
+                Some(CurtainCommand::Close) => state.curtain_position.saturating_sub(CURTAIN_STEP),
+                _ => state.curtain_position,
+            };
+        }
+    }
+
+    let mut response = CurtainDataMessage::new(request.command);
+    response.attribute = state.curtain_position;
+
+    return Ok(Ok(response.pack()?.to_vec()));
+}
+
+/// Handles an [AlarmDataMessage], returning the packed response or a device error code.
+fn handle_alarm_data(
+    state: &mut SimulatorState,
+    payload: &[u8],
+) -> Result<Result<Vec<u8>, i16>, Error> {
+    return Ok(match unpack_zero_padded::<AlarmDataMessage>(payload) {
+        Ok(AlarmDataMessage {
+            command: AlarmDataCommand::GetSensors,
+        }) => Ok(AlarmDataMessage::pack_sensors(&state.alarm_sensors)?),
+        Err(_) => Err(ERROR_NOT_SUPPORTED),
+    });
+}
+
+/// Wraps a payload in a command, as sent back by a device.
+fn create_response<T>(
+    config: &SimulatorConfig,
+    error_code: i16,
+    payload: &[u8],
+    key: &[u8; 16],
+) -> Result<Vec<u8>, Error>
+where
+    T: CommandTrait,
+{
+    let mut response = CommandMessage::new::<T>(config.model_code, config.mac, 0);
+    response.error_code = error_code as u16;
+
+    return response.pack_with_payload(payload, key);
+}
@@ -1,20 +1,251 @@
 #[cfg(test)]
 mod tests {
-    use std::net::{IpAddr, Ipv4Addr};
+    use std::{
+        net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    };
 
     use chrono::naive::NaiveDate;
-    use chrono::offset::{FixedOffset, TimeZone};
+    use chrono::offset::FixedOffset;
     use chrono::prelude::DateTime;
     use packed_struct::prelude::PackedStruct;
 
+    #[cfg(feature = "tokio")]
+    use crate::traits::TransportFuture;
+
     use crate::{
         constants,
+        ir::{
+            DecodedCode, IrCode, IrProtocol, LircButton, LircRemote, ProntoCode, ProtocolCode,
+            RfBand, RfCode,
+        },
         network::{
-            AuthenticationMessage, CommandMessage, DiscoveryMessage, RemoteDataCommand,
-            RemoteDataMessage, WirelessConnection,
+            AirQuality, AlarmSensor, AlarmSensorType, AuthenticationMessage,
+            AuthenticationResponse, BulbColorMode, BulbState, CommandMessage, CurtainCommand,
+            CurtainDataMessage, DiscoveryMessage, DiscoveryResponse, DiscoveryTarget,
+            JsonDataCommand, JsonDataMessage, LightLevel, NetworkConfig, NoiseLevel,
+            PowerStripDataMessage, RemoteDataCommand, RemoteDataMessage, RemoteSensorReadings,
+            SensorReadings, ThermostatDataCommand, ThermostatDataMessage, ThermostatLoopMode,
+            ThermostatMode, ThermostatPeriod, ThermostatSchedule, ThermostatSensor, UdpTransport,
+            WirelessConnection,
         },
+        register_model,
+        simulator::{Simulator, SimulatorConfig},
+        traits::{DeviceTrait, Transport},
+        Device, DeviceFamily, DeviceSession, Error, PlugState, PlugType, RemoteGeneration,
+        RetryPolicy,
     };
 
+    /// Creates an authenticated device talking to the given simulator.
+    fn connect_to_simulator(sim: &Simulator) -> Device {
+        return Device::from_ip_with_transport(
+            Ipv4Addr::LOCALHOST,
+            Some(Ipv4Addr::LOCALHOST),
+            Arc::new(sim.transport()),
+        )
+        .expect("Could not connect to simulator!");
+    }
+
+    /// Async version of [connect_to_simulator].
+    #[cfg(feature = "tokio")]
+    async fn connect_to_simulator_async(sim: &Simulator) -> Device {
+        return Device::from_ip_with_config_async(
+            Ipv4Addr::LOCALHOST,
+            Some(Ipv4Addr::LOCALHOST),
+            sim.transport().config(),
+        )
+        .await
+        .expect("Could not connect to simulator!");
+    }
+
+    /// A fake transport which answers discovery and authentication requests in memory.
+    #[derive(Debug)]
+    struct LoopbackTransport;
+
+    impl Transport for LoopbackTransport {
+        fn send_and_receive_one(
+            &self,
+            msg: &[u8],
+            addr: Ipv4Addr,
+            _local_port: Option<u16>,
+        ) -> Result<(Vec<u8>, SocketAddr), Error> {
+            let sender = SocketAddr::from((addr, 80));
+
+            // Discovery messages are not wrapped in a command
+            if msg.len() == 48 {
+                let mut name = [0u8; 62];
+                name[..8].copy_from_slice(b"Loopback");
+
+                let response = DiscoveryResponse {
+                    model_code: 0x649B,
+                    mac: [0x6, 0x5, 0x4, 0x3, 0x2, 0x1],
+                    name,
+                    is_locked: false,
+                };
+
+                return Ok((response.pack()?.to_vec(), sender));
+            }
+
+            // Everything else should be an authentication request
+            CommandMessage::unpack_with_payload(msg.to_vec(), &constants::INITIAL_KEY)?;
+            let auth = AuthenticationResponse {
+                id: 0x1234,
+                key: [0xAB; 16],
+            };
+            let response = CommandMessage::new::<AuthenticationMessage>(
+                0x649B,
+                [0x1, 0x2, 0x3, 0x4, 0x5, 0x6],
+                0,
+            )
+            .pack_with_payload(&auth.pack()?, &constants::INITIAL_KEY)?;
+
+            return Ok((response, sender));
+        }
+
+        fn send_and_receive_many(
+            &self,
+            msg: &[u8],
+            addr: Ipv4Addr,
+            local_port: Option<u16>,
+        ) -> Result<Vec<(Vec<u8>, SocketAddr)>, Error> {
+            // Answer twice, as devices sometimes do, along with a device of an unknown model
+            // and a device with a garbled name
+            let answer = self.send_and_receive_one(msg, addr, local_port)?;
+            let unknown = DiscoveryResponse {
+                model_code: 0x0001,
+                mac: [0x1; 6],
+                name: [0; 62],
+                is_locked: false,
+            };
+            let garbled = DiscoveryResponse {
+                model_code: 0x649B,
+                mac: [0x2; 6],
+                name: [0xFF; 62],
+                is_locked: false,
+            };
+
+            return Ok(vec![
+                answer.clone(),
+                answer,
+                (
+                    unknown.pack()?.to_vec(),
+                    SocketAddr::from(([10, 0, 0, 3], 80)),
+                ),
+                (
+                    garbled.pack()?.to_vec(),
+                    SocketAddr::from(([10, 0, 0, 4], 80)),
+                ),
+            ]);
+        }
+    }
+
+    /// A transport which fails a number of sends with a timeout before passing them on.
+    #[derive(Debug)]
+    struct LossyTransport {
+        inner: UdpTransport,
+        drops: AtomicUsize,
+    }
+
+    impl Transport for LossyTransport {
+        fn send_and_receive_one(
+            &self,
+            msg: &[u8],
+            addr: Ipv4Addr,
+            local_port: Option<u16>,
+        ) -> Result<(Vec<u8>, SocketAddr), Error> {
+            let remaining = self.drops.load(Ordering::SeqCst);
+            if remaining > 0 {
+                self.drops.store(remaining - 1, Ordering::SeqCst);
+                return Err(Error::Timeout);
+            }
+
+            return self.inner.send_and_receive_one(msg, addr, local_port);
+        }
+
+        fn send_and_receive_many(
+            &self,
+            msg: &[u8],
+            addr: Ipv4Addr,
+            local_port: Option<u16>,
+        ) -> Result<Vec<(Vec<u8>, SocketAddr)>, Error> {
+            return self.inner.send_and_receive_many(msg, addr, local_port);
+        }
+    }
+
+    /// A transport which sends broadcasts to the loopback address, so that lists reach simulators.
+    #[derive(Debug)]
+    struct LoopbackBroadcastTransport {
+        inner: UdpTransport,
+    }
+
+    impl LoopbackBroadcastTransport {
+        fn destination(addr: Ipv4Addr) -> Ipv4Addr {
+            return if addr == Ipv4Addr::BROADCAST {
+                Ipv4Addr::LOCALHOST
+            } else {
+                addr
+            };
+        }
+    }
+
+    impl Transport for LoopbackBroadcastTransport {
+        fn send_and_receive_one(
+            &self,
+            msg: &[u8],
+            addr: Ipv4Addr,
+            local_port: Option<u16>,
+        ) -> Result<(Vec<u8>, SocketAddr), Error> {
+            return self
+                .inner
+                .send_and_receive_one(msg, Self::destination(addr), local_port);
+        }
+
+        fn send_and_receive_many(
+            &self,
+            msg: &[u8],
+            addr: Ipv4Addr,
+            local_port: Option<u16>,
+        ) -> Result<Vec<(Vec<u8>, SocketAddr)>, Error> {
+            return self
+                .inner
+                .send_and_receive_many(msg, Self::destination(addr), local_port);
+        }
+
+        #[cfg(feature = "tokio")]
+        fn send_and_receive_one_async<'a>(
+            &'a self,
+            msg: &'a [u8],
+            addr: Ipv4Addr,
+            local_port: Option<u16>,
+        ) -> TransportFuture<'a, (Vec<u8>, SocketAddr)> {
+            return self
+                .inner
+                .send_and_receive_one_async(msg, Self::destination(addr), local_port);
+        }
+
+        #[cfg(feature = "tokio")]
+        fn send_and_receive_many_async<'a>(
+            &'a self,
+            msg: &'a [u8],
+            addr: Ipv4Addr,
+            local_port: Option<u16>,
+        ) -> TransportFuture<'a, Vec<(Vec<u8>, SocketAddr)>> {
+            return self.inner.send_and_receive_many_async(
+                msg,
+                Self::destination(addr),
+                local_port,
+            );
+        }
+
+        fn config(&self) -> NetworkConfig {
+            return self.inner.config();
+        }
+    }
+
     #[test]
     fn authentication_packs_correctly() {
         let auth = AuthenticationMessage::new("Test 1");
@@ -55,15 +286,1059 @@ mod tests {
         assert_eq!(expected, &actual);
     }
 
+    #[test]
+    fn command_rejects_bad_checksum() {
+        let payload: [u8; 10] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let cmd = CommandMessage::with_count::<AuthenticationMessage>(
+            0x1234,
+            0x649B,
+            [0x1u8, 0x2u8, 0x3u8, 0x4u8, 0x5u8, 0x6u8],
+            0xABCDEFAB,
+        );
+
+        let mut packed = cmd
+            .pack_with_payload(&payload, &constants::INITIAL_KEY)
+            .expect("Could not pack test command message!");
+
+        // Corrupt a byte of the encrypted payload
+        let last = packed.len() - 1;
+        packed[last] ^= 0xFF;
+
+        let result = CommandMessage::unpack_with_payload(packed, &constants::INITIAL_KEY);
+        assert!(matches!(result, Err(Error::ChecksumMismatch { .. })));
+    }
+
+    #[test]
+    fn device_authenticates_through_transport() {
+        let device = Device::from_ip_with_transport(
+            Ipv4Addr::new(10, 0, 0, 2),
+            Some(Ipv4Addr::new(10, 0, 0, 1)),
+            Arc::new(LoopbackTransport),
+        )
+        .expect("Could not create device through loopback transport!");
+
+        let info = device.get_info();
+        assert!(matches!(device, Device::Remote { .. }));
+        assert_eq!(info.name.trim_end_matches('\0'), "Loopback");
+        assert_eq!(info.auth_id, 0x1234);
+        assert_eq!(info.key, [0xAB; 16]);
+    }
+
+    #[test]
+    fn discovery_iter_uses_transport() {
+        let found = Device::discover_iter_with_transport(
+            &[DiscoveryTarget::Address(Ipv4Addr::new(10, 0, 0, 2))],
+            Arc::new(LoopbackTransport),
+        )
+        .expect("Could not start discovery through loopback transport!")
+        .collect::<Vec<_>>();
+
+        // Duplicates are merged, while the garbled device is reported as failed
+        assert_eq!(found.len(), 3);
+        assert_eq!(found.iter().filter(|x| x.is_ok()).count(), 2);
+        assert_eq!(found[0].as_ref().unwrap().get_info().key, [0xAB; 16]);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_operations_use_transport() {
+        let device = Device::from_ip_with_transport_async(
+            Ipv4Addr::new(10, 0, 0, 2),
+            Some(Ipv4Addr::new(10, 0, 0, 1)),
+            Arc::new(LoopbackTransport),
+        )
+        .await
+        .expect("Could not create device through loopback transport!");
+        assert_eq!(device.get_info().auth_id, 0x1234);
+        assert_eq!(device.get_info().key, [0xAB; 16]);
+
+        let result = Device::list_with_transport_async(
+            Some(Ipv4Addr::new(10, 0, 0, 1)),
+            Arc::new(LoopbackTransport),
+        )
+        .await
+        .expect("Could not list devices through loopback transport!");
+        assert_eq!(result.devices.len(), 2);
+        assert_eq!(result.failed.len(), 1);
+
+        let mut stream = Device::discover_stream_with_transport(
+            &[DiscoveryTarget::Address(Ipv4Addr::new(10, 0, 0, 2))],
+            Arc::new(LoopbackTransport),
+        )
+        .await
+        .expect("Could not start discovery through loopback transport!");
+        let mut found = vec![];
+        while let Some(item) = stream.next().await {
+            found.push(item);
+        }
+        assert_eq!(found.len(), 3);
+        assert_eq!(found.iter().filter(|x| x.is_ok()).count(), 2);
+    }
+
+    #[test]
+    fn list_merges_duplicates_and_reports_failures() {
+        let result = Device::list_with_transport(
+            Some(Ipv4Addr::new(10, 0, 0, 1)),
+            Arc::new(LoopbackTransport),
+        )
+        .expect("Could not list devices through loopback transport!");
+
+        // Unknown models still authenticate, as generic devices
+        assert_eq!(result.devices.len(), 2);
+        assert_eq!(result.devices[0].get_info().auth_id, 0x1234);
+        assert!(matches!(result.devices[1], Device::Generic { .. }));
+        assert_eq!(result.devices[1].get_info().auth_id, 0x1234);
+
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].mac, Some([0x2; 6]));
+        assert!(matches!(result.failed[0].error, Error::InvalidResponse(_)));
+    }
+
+    #[test]
+    fn simulated_list_finds_devices() {
+        let sim = Simulator::start(SimulatorConfig::default()).expect("Could not start simulator!");
+        let config = NetworkConfig {
+            discovery_timeout: Duration::from_millis(300),
+            ..sim.transport().config()
+        };
+
+        let result = Device::list_with_transport(
+            Some(Ipv4Addr::LOCALHOST),
+            Arc::new(LoopbackBroadcastTransport {
+                inner: UdpTransport::with_config(config),
+            }),
+        )
+        .expect("Could not list simulator!");
+        assert_eq!(result.devices.len(), 1);
+        assert!(result.failed.is_empty());
+        assert_eq!(result.devices[0].get_info().key, sim.config().key);
+    }
+
+    #[test]
+    fn discovery_targets_parse_and_expand() {
+        let range: DiscoveryTarget = "10.0.0.7/30".parse().expect("Could not parse range!");
+        assert_eq!(
+            range,
+            DiscoveryTarget::Range(Ipv4Addr::new(10, 0, 0, 7), 30)
+        );
+        assert_eq!(
+            range.addresses().expect("Could not expand range!"),
+            vec![Ipv4Addr::new(10, 0, 0, 5), Ipv4Addr::new(10, 0, 0, 6)]
+        );
+
+        assert_eq!(
+            "10.0.0.2".parse::<DiscoveryTarget>().unwrap(),
+            DiscoveryTarget::Address(Ipv4Addr::new(10, 0, 0, 2))
+        );
+        assert_eq!(
+            "eth0".parse::<DiscoveryTarget>().unwrap(),
+            DiscoveryTarget::Interface("eth0".into())
+        );
+        assert!(DiscoveryTarget::Range(Ipv4Addr::new(10, 0, 0, 0), 8)
+            .addresses()
+            .is_err());
+    }
+
+    #[test]
+    fn simulated_discovery_tags_interface() {
+        let sim = Simulator::start(SimulatorConfig::default()).expect("Could not start simulator!");
+        let config = NetworkConfig {
+            discovery_timeout: Duration::from_millis(300),
+            ..sim.transport().config()
+        };
+
+        let result = Device::discover(&[DiscoveryTarget::Address(Ipv4Addr::LOCALHOST)], config)
+            .expect("Could not discover simulator!");
+        assert_eq!(result.devices.len(), 1);
+        assert!(result.failed.is_empty());
+
+        let info = result.devices[0].get_info();
+        assert_eq!(info.key, sim.config().key);
+        let interface = info
+            .interface
+            .expect("Device was not tagged with an interface!");
+        assert!(interface.is_loopback());
+        assert!(interface.contains(Ipv4Addr::LOCALHOST));
+    }
+
+    #[test]
+    fn simulated_discovery_streams_devices() {
+        let first =
+            Simulator::start(SimulatorConfig::default()).expect("Could not start simulator!");
+        let _second = Simulator::start(SimulatorConfig {
+            bind_addr: SocketAddr::from(([127, 0, 0, 2], first.address().port())),
+            mac: [0x02, 0x00, 0x00, 0x00, 0x00, 0x02],
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+
+        let targets = [
+            DiscoveryTarget::Address(Ipv4Addr::LOCALHOST),
+            DiscoveryTarget::Address(Ipv4Addr::new(127, 0, 0, 2)),
+        ];
+        let config = NetworkConfig {
+            discovery_timeout: Duration::from_secs(5),
+            ..first.transport().config()
+        };
+
+        // Stopping after the first device does not wait for the timeout
+        let start = Instant::now();
+        let found = Device::discover_iter(&targets, config)
+            .expect("Could not start discovery!")
+            .stop_after(1)
+            .collect::<Vec<_>>();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].is_ok());
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // Cancelling stops the discovery right away
+        let iter = Device::discover_iter(&targets, config).expect("Could not start discovery!");
+        iter.cancel_flag().store(true, Ordering::SeqCst);
+        assert_eq!(iter.count(), 0);
+
+        // Otherwise, every device is yielded
+        let config = NetworkConfig {
+            discovery_timeout: Duration::from_millis(500),
+            ..config
+        };
+        let found = Device::discover_iter(&targets, config)
+            .expect("Could not start discovery!")
+            .filter_map(Result::ok)
+            .count();
+        assert_eq!(found, 2);
+    }
+
+    #[test]
+    fn simulated_remote_learns_and_sends() {
+        let sim = Simulator::start(SimulatorConfig::default()).expect("Could not start simulator!");
+        let code: Vec<u8> = vec![0x26, 0x00, 0x04, 0x00, 0x12, 0x34, 0x0D, 0x05];
+
+        let mut remote = match connect_to_simulator(&sim) {
+            Device::Remote { remote } => remote,
+            _ => panic!("Simulator did not report a remote!"),
+        };
+        assert_eq!(remote.info.key, sim.config().key);
+
+        sim.state().learned_code = Some(code.clone());
+        let learned = remote.learn_ir().expect("Could not learn simulated code!");
+        assert_eq!(learned, code);
+
+        remote
+            .send_code(&learned)
+            .expect("Could not send simulated code!");
+        assert_eq!(sim.state().sent_codes, vec![code]);
+    }
+
+    #[test]
+    fn simulated_legacy_remote_learns_and_sends() {
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x2737,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+        let code: Vec<u8> = vec![0x26, 0x00, 0x04, 0x00, 0x12, 0x34, 0x0D, 0x05];
+
+        let mut remote = match connect_to_simulator(&sim) {
+            Device::Remote { remote } => remote,
+            _ => panic!("Simulator did not report a remote!"),
+        };
+        assert_eq!(remote.generation(), RemoteGeneration::Legacy);
+
+        sim.state().learned_code = Some(code.clone());
+        let learned = remote.learn_ir().expect("Could not learn simulated code!");
+        assert_eq!(learned, code);
+
+        remote
+            .send_code(&learned)
+            .expect("Could not send simulated code!");
+        assert_eq!(sim.state().sent_codes, vec![code]);
+    }
+
+    #[test]
+    fn simulated_remote_checks_sensors() {
+        let sim = Simulator::start(SimulatorConfig::default()).expect("Could not start simulator!");
+
+        let mut readings = RemoteSensorReadings::default();
+        readings
+            .set_temperature(-3.25)
+            .expect("Could not set temperature!");
+        readings
+            .set_humidity(55.5)
+            .expect("Could not set humidity!");
+        sim.state().remote_sensors = readings.pack().expect("Could not pack!").to_vec();
+
+        let mut remote = match connect_to_simulator(&sim) {
+            Device::Remote { remote } => remote,
+            _ => panic!("Simulator did not report a remote!"),
+        };
+
+        let actual = remote.check_sensors().expect("Could not check sensors!");
+        assert_eq!(actual.get_temperature(), -3.25);
+        assert_eq!(actual.get_humidity(), 55.5);
+
+        // Values which round out of range are rejected
+        assert!(readings.set_temperature(127.999).is_err());
+        assert!(readings.set_temperature(f32::NAN).is_err());
+        readings
+            .set_temperature(-128.0)
+            .expect("Could not set temperature!");
+        assert_eq!(readings.get_temperature(), -128.0);
+
+        // Models without a sensor are rejected before anything is sent
+        remote.info.model_code = 0x5209;
+        assert!(matches!(remote.check_sensors(), Err(Error::Unsupported(_))));
+    }
+
+    #[test]
+    fn simulated_hvac_keeps_state() {
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x4E2A,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+
+        let mut hvac = match connect_to_simulator(&sim) {
+            Device::Hvac { hvac } => hvac,
+            _ => panic!("Simulator did not report an HVAC unit!"),
+        };
+
+        let mut state = hvac.get_state().expect("Could not get simulated state!");
+        assert!(!state.power);
+
+        state.power = true;
+        state
+            .set_target_temp(22.0)
+            .expect("Could not set target temperature!");
+        hvac.set_state(&mut state)
+            .expect("Could not set simulated state!");
+
+        let state = hvac.get_state().expect("Could not get simulated state!");
+        assert!(state.power);
+        assert_eq!(state.get_target_temp(), 22.0);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn simulated_async_discovery_finds_devices() {
+        let sim = Simulator::start(SimulatorConfig::default()).expect("Could not start simulator!");
+        let config = NetworkConfig {
+            discovery_timeout: Duration::from_millis(300),
+            ..sim.transport().config()
+        };
+
+        let device = connect_to_simulator_async(&sim).await;
+        assert_eq!(device.get_info().key, sim.config().key);
+
+        let result = Device::list_with_transport_async(
+            Some(Ipv4Addr::LOCALHOST),
+            Arc::new(LoopbackBroadcastTransport {
+                inner: UdpTransport::with_config(config),
+            }),
+        )
+        .await
+        .expect("Could not list simulator!");
+        assert_eq!(result.devices.len(), 1);
+        assert_eq!(result.devices[0].get_info().key, sim.config().key);
+
+        let mut stream =
+            Device::discover_stream(&[DiscoveryTarget::Address(Ipv4Addr::LOCALHOST)], config)
+                .await
+                .expect("Could not start discovery!");
+        let device = stream
+            .next()
+            .await
+            .expect("Simulator was not discovered!")
+            .expect("Could not authenticate with simulator!");
+        assert_eq!(device.get_info().key, sim.config().key);
+        assert!(stream.next().await.is_none());
+
+        // Cancelling stops a stream which is still waiting for responses
+        let config = NetworkConfig {
+            discovery_timeout: Duration::from_secs(5),
+            ..config
+        };
+        let mut stream =
+            Device::discover_stream(&[DiscoveryTarget::Address(Ipv4Addr::LOCALHOST)], config)
+                .await
+                .expect("Could not start discovery!");
+        let cancel = stream.cancel_flag();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            cancel.store(true, Ordering::SeqCst);
+        });
+
+        let start = Instant::now();
+        assert!(stream.next().await.is_some_and(|x| x.is_ok()));
+        assert!(stream.next().await.is_none());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn simulated_async_remote_learns_and_sends() {
+        let sim = Simulator::start(SimulatorConfig::default()).expect("Could not start simulator!");
+        let code: Vec<u8> = vec![0x26, 0x00, 0x04, 0x00, 0x12, 0x34, 0x0D, 0x05];
+        let rf_code: Vec<u8> = vec![0xB2, 0x00, 0x04, 0x00, 0x12, 0x34, 0x0D, 0x05];
+
+        let mut remote = match connect_to_simulator_async(&sim).await {
+            Device::Remote { remote } => remote,
+            _ => panic!("Simulator did not report a remote!"),
+        };
+
+        sim.state().learned_code = Some(code.clone());
+        let learned = remote
+            .learn_ir_async()
+            .await
+            .expect("Could not learn simulated code!");
+        assert_eq!(learned, code);
+
+        sim.state().learned_code = Some(rf_code.clone());
+        let learned_rf = remote
+            .learn_rf_async()
+            .await
+            .expect("Could not learn simulated RF code!");
+        assert_eq!(learned_rf, rf_code);
+
+        remote
+            .send_code_async(&learned)
+            .await
+            .expect("Could not send simulated code!");
+        remote
+            .send_command_async(&learned_rf, RemoteDataCommand::SendCode)
+            .await
+            .expect("Could not send simulated command!");
+        assert_eq!(sim.state().sent_codes, vec![code, rf_code]);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn simulated_async_hvac_keeps_state() {
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x4E2A,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+
+        let mut hvac = match connect_to_simulator_async(&sim).await {
+            Device::Hvac { hvac } => hvac,
+            _ => panic!("Simulator did not report an HVAC unit!"),
+        };
+
+        let mut state = hvac
+            .get_state_async()
+            .await
+            .expect("Could not get simulated state!");
+        assert!(!state.power);
+
+        state.power = true;
+        state
+            .set_target_temp(22.0)
+            .expect("Could not set target temperature!");
+        hvac.set_state_async(&mut state)
+            .await
+            .expect("Could not set simulated state!");
+
+        let state = hvac
+            .get_state_async()
+            .await
+            .expect("Could not get simulated state!");
+        assert!(state.power);
+        assert_eq!(state.get_target_temp(), 22.0);
+        hvac.get_info_async()
+            .await
+            .expect("Could not get simulated info!");
+    }
+
+    #[test]
+    fn simulated_unknown_models_fall_back_and_register() {
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x7FFE,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+
+        // Unknown models only support raw commands, which the simulator does not understand
+        let mut generic = match connect_to_simulator(&sim) {
+            Device::Generic { generic } => generic,
+            _ => panic!("Simulator did not report a generic device!"),
+        };
+        assert_eq!(generic.info.friendly_model, "Unknown");
+        assert!(matches!(
+            generic.send_command::<RemoteDataMessage>(&[0x01]),
+            Err(Error::Device { code: -4 })
+        ));
+
+        // Registered models are handled as their family, with names known only at runtime
+        let name = format!("SP3 {}", "clone");
+        register_model(0x7FFE, name, DeviceFamily::Plug(PlugType::Sp3));
+        let mut plug = match connect_to_simulator(&sim) {
+            Device::Plug { plug } => plug,
+            _ => panic!("Simulator did not report a plug!"),
+        };
+        assert_eq!(plug.info.friendly_model, "SP3 clone");
+        assert_eq!(plug.plug_type, PlugType::Sp3);
+
+        plug.set_power(true).expect("Could not set power!");
+        assert!(sim.state().plug_state.power);
+    }
+
+    #[test]
+    fn simulated_plugs_switch_and_report_energy() {
+        // An SP1 can only be switched, using its own packet type
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x0000,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+
+        let mut plug = match connect_to_simulator(&sim) {
+            Device::Plug { plug } => plug,
+            _ => panic!("Simulator did not report a plug!"),
+        };
+        assert_eq!(plug.plug_type, PlugType::Sp1);
+
+        plug.set_power(true)
+            .expect("Could not set simulated power!");
+        assert!(sim.state().plug_state.power);
+        plug.set_power(false)
+            .expect("Could not set simulated power!");
+        assert!(!sim.state().plug_state.power);
+        assert!(matches!(plug.get_state(), Err(Error::Unsupported(_))));
+
+        // An SP3 keeps the nightlight when switching the power
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x2733,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+
+        let mut plug = match connect_to_simulator(&sim) {
+            Device::Plug { plug } => plug,
+            _ => panic!("Simulator did not report a plug!"),
+        };
+
+        plug.set_nightlight(true)
+            .expect("Could not set simulated nightlight!");
+        plug.set_power(true)
+            .expect("Could not set simulated power!");
+        assert_eq!(
+            plug.get_state().expect("Could not get simulated state!"),
+            PlugState {
+                power: true,
+                nightlight: true
+            }
+        );
+        assert!(matches!(plug.get_energy(), Err(Error::Unsupported(_))));
+
+        // An SP4B exchanges its state and power usage as JSON
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x6111,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+        sim.state().plug_energy = 12.5;
+
+        let mut plug = match connect_to_simulator(&sim) {
+            Device::Plug { plug } => plug,
+            _ => panic!("Simulator did not report a plug!"),
+        };
+
+        plug.set_power(true)
+            .expect("Could not set simulated power!");
+        assert!(sim.state().plug_state.power);
+        assert!(
+            !plug
+                .get_state()
+                .expect("Could not get simulated state!")
+                .nightlight
+        );
+        assert_eq!(plug.get_energy().expect("Could not get energy!"), 12.5);
+    }
+
+    #[test]
+    fn simulated_alarm_lists_sensors() {
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x2722,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+
+        let sensor = |serial, name, sensor_type| {
+            AlarmSensor::new(serial, name, sensor_type).expect("Could not create sensor!")
+        };
+        let mut door = sensor(
+            [0x12, 0x34, 0x56, 0x78],
+            "Front door",
+            AlarmSensorType::DoorWindow,
+        );
+        door.status = 0x10;
+        let motion = sensor([0x9A, 0xBC, 0xDE, 0xF0], "Hallway", AlarmSensorType::Motion);
+        let empty = sensor([0; 4], "", AlarmSensorType::Unknown(0));
+        let fob = sensor([0x01, 0x02, 0x03, 0x04], "Keys", AlarmSensorType::KeyFob);
+        sim.state().alarm_sensors = vec![door, motion, empty, fob];
+
+        let mut alarm = match connect_to_simulator(&sim) {
+            Device::Alarm { alarm } => alarm,
+            _ => panic!("Simulator did not report an alarm hub!"),
+        };
+
+        let sensors = alarm.get_sensors().expect("Could not get sensors!");
+        assert_eq!(sensors, vec![door, motion, fob]);
+        assert_eq!(sensors[0].get_serial(), "12345678");
+        assert_eq!(
+            sensors[0].get_name().expect("Could not get name!"),
+            "Front door"
+        );
+        assert_eq!(sensors[0].get_sensor_type(), AlarmSensorType::DoorWindow);
+        assert_eq!(sensors[0].status, 0x10);
+        assert_eq!(sensors[1].status, 0x00);
+        assert_eq!(sensors[2].get_sensor_type(), AlarmSensorType::KeyFob);
+    }
+
+    #[test]
+    fn simulated_bulb_updates_partially() {
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x60C7,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+        sim.state().bulb_state = BulbState {
+            power: Some(false),
+            brightness: Some(100),
+            red: Some(255),
+            green: Some(128),
+            blue: Some(0),
+            color_mode: Some(BulbColorMode::Rgb),
+            ..Default::default()
+        };
+
+        let mut bulb = match connect_to_simulator(&sim) {
+            Device::Bulb { bulb } => bulb,
+            _ => panic!("Simulator did not report a bulb!"),
+        };
+
+        let state = bulb
+            .set_state(&BulbState {
+                power: Some(true),
+                brightness: Some(40),
+                ..Default::default()
+            })
+            .expect("Could not set simulated state!");
+        assert_eq!(state.power, Some(true));
+        assert_eq!(state.brightness, Some(40));
+        assert_eq!(state.green, Some(128));
+
+        let state = bulb.get_state().expect("Could not get simulated state!");
+        assert_eq!(state, sim.state().bulb_state);
+        assert_eq!(state.color_mode, Some(BulbColorMode::Rgb));
+
+        // Color modes not known to this library are kept as-is
+        sim.state().bulb_state.color_mode = Some(BulbColorMode::Unknown(7));
+        let state = bulb.get_state().expect("Could not get simulated state!");
+        assert_eq!(state.color_mode, Some(BulbColorMode::Unknown(7)));
+    }
+
+    #[test]
+    fn simulated_thermostat_reports_and_changes_status() {
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x4EAD,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+
+        // Locked and powered on, with a room temperature of 21.5 and an unknown mode and sensor
+        {
+            let mut state = sim.state();
+            state.thermostat_registers[0] = 0x01;
+            state.thermostat_registers[1] = 0x01;
+            state.thermostat_registers[2] = 43;
+            state.thermostat_registers[4] = 0x07;
+            state.thermostat_registers[5] = 0x09;
+        }
+
+        let mut thermostat = match connect_to_simulator(&sim) {
+            Device::Thermostat { thermostat } => thermostat,
+            _ => panic!("Simulator did not report a thermostat!"),
+        };
+
+        let status = thermostat.get_status().expect("Could not get status!");
+        assert!(status.power);
+        assert!(status.remote_lock);
+        assert_eq!(status.get_room_temp(), 21.5);
+        assert_eq!(status.get_mode(), ThermostatMode::Unknown(7));
+        assert_eq!(status.get_loop_mode(), ThermostatLoopMode::Unknown(0));
+        assert_eq!(status.get_sensor(), ThermostatSensor::Unknown(9));
+
+        thermostat
+            .set_target_temp(22.5)
+            .expect("Could not set target temperature!");
+        thermostat.set_power(false).expect("Could not set power!");
+        thermostat
+            .set_mode(
+                ThermostatMode::Auto,
+                ThermostatLoopMode::Weekdays1234567,
+                ThermostatSensor::External,
+            )
+            .expect("Could not set mode!");
+
+        let period = |start_hour, temp| ThermostatPeriod {
+            start_hour,
+            start_minute: 30,
+            temp,
+        };
+        let schedule = ThermostatSchedule {
+            weekday: [
+                period(6, 21.0),
+                period(8, 17.0),
+                period(11, 17.5),
+                period(12, 17.0),
+                period(17, 22.0),
+                period(22, 16.0),
+            ],
+            weekend: [period(8, 21.5), period(23, 16.0)],
+        };
+        thermostat
+            .set_schedule(&schedule)
+            .expect("Could not set schedule!");
+
+        let status = thermostat.get_status().expect("Could not get status!");
+        assert!(!status.power);
+        assert!(status.remote_lock);
+        assert_eq!(status.get_room_temp(), 21.5);
+        assert_eq!(status.get_target_temp(), 22.5);
+        assert_eq!(status.get_mode(), ThermostatMode::Auto);
+        assert_eq!(status.get_loop_mode(), ThermostatLoopMode::Weekdays1234567);
+        assert_eq!(status.get_sensor(), ThermostatSensor::External);
+        assert_eq!(status.get_schedule(), schedule);
+    }
+
+    #[test]
+    fn simulated_curtain_moves_to_position() {
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x4E4D,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+
+        let mut curtain = match connect_to_simulator(&sim) {
+            Device::Curtain { curtain } => curtain,
+            _ => panic!("Simulator did not report a curtain!"),
+        };
+
+        let mut progress = vec![];
+        let position = curtain
+            .set_position(30, |p| progress.push(p))
+            .expect("Could not set position!");
+        assert_eq!(position, 30);
+        assert_eq!(progress, vec![0, 10, 20, 30]);
+        assert_eq!(sim.state().curtain_moving, None);
+
+        curtain.close().expect("Could not close curtain!");
+        assert_eq!(curtain.get_position().expect("Could not get position!"), 20);
+        curtain.stop().expect("Could not stop curtain!");
+        assert_eq!(curtain.get_position().expect("Could not get position!"), 20);
+
+        assert!(matches!(
+            curtain.set_position(101, |_| {}),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn simulated_power_strip_switches_sockets() {
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x4EB5,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+
+        let mut strip = match connect_to_simulator(&sim) {
+            Device::PowerStrip { strip } => strip,
+            _ => panic!("Simulator did not report a power strip!"),
+        };
+
+        strip
+            .set_power(2, true)
+            .expect("Could not switch socket 2!");
+        strip
+            .set_power(4, true)
+            .expect("Could not switch socket 4!");
+        strip
+            .set_power(2, false)
+            .expect("Could not switch socket 2!");
+        assert_eq!(
+            strip.get_state().expect("Could not get simulated state!"),
+            [false, false, false, true]
+        );
+        assert!(matches!(
+            strip.set_power(5, true),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn simulated_sensor_reports_readings() {
+        let sim = Simulator::start(SimulatorConfig {
+            model_code: 0x2714,
+            ..Default::default()
+        })
+        .expect("Could not start simulator!");
+
+        let mut readings = SensorReadings::default();
+        readings.set_light(LightLevel::Bright);
+        readings.set_air_quality(AirQuality::Good);
+        readings.set_noise(NoiseLevel::Noisy);
+        readings
+            .set_temperature(-3.6)
+            .expect("Could not set temperature!");
+        readings
+            .set_humidity(48.0)
+            .expect("Could not set humidity!");
+        sim.state().sensor_readings = readings.pack().expect("Could not pack!").to_vec();
+
+        let mut sensor = match connect_to_simulator(&sim) {
+            Device::Sensor { sensor } => sensor,
+            _ => panic!("Simulator did not report a sensor!"),
+        };
+
+        let actual = sensor.get_readings().expect("Could not get readings!");
+        assert_eq!(actual, readings);
+        assert_eq!(actual.get_temperature(), -3.6);
+        assert_eq!(actual.get_humidity(), 48.0);
+        assert_eq!(actual.get_air_quality(), AirQuality::Good);
+
+        // Levels not known to this library are kept as-is
+        sim.state().sensor_readings = vec![0, 0, 0, 0, 21, 4, 48, 0, 3, 0, 7, 0, 5];
+        let actual = sensor.get_readings().expect("Could not get readings!");
+        assert_eq!(actual.get_temperature(), 21.4);
+        assert_eq!(actual.get_light(), LightLevel::Bright);
+        assert_eq!(actual.get_air_quality(), AirQuality::Unknown(7));
+        assert_eq!(actual.get_noise(), NoiseLevel::Unknown(5));
+        assert!(readings.set_temperature(128.0).is_err());
+        assert!(readings.set_humidity(-0.1).is_err());
+    }
+
+    #[test]
+    fn session_round_trips() {
+        let session = DeviceSession {
+            address: Ipv4Addr::new(10, 0, 0, 2),
+            mac: [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF],
+            model_code: 0x649B,
+            name: "Living Room".into(),
+            auth_id: 0x1234,
+            key: [0xAB; 16],
+        };
+
+        let parsed: DeviceSession = session
+            .to_string()
+            .parse()
+            .expect("Could not parse session!");
+        assert_eq!(parsed, session);
+        assert!("10.0.0.2 AA:BB".parse::<DeviceSession>().is_err());
+
+        let json = serde_json::to_string(&session).expect("Could not serialize session!");
+        let parsed: DeviceSession =
+            serde_json::from_str(&json).expect("Could not deserialize session!");
+        assert_eq!(parsed, session);
+    }
+
+    #[test]
+    fn simulated_session_reauthenticates_after_reboot() {
+        let sim = Simulator::start(SimulatorConfig::default()).expect("Could not start simulator!");
+        let session = connect_to_simulator(&sim).get_session();
+        assert_eq!(session.key, sim.config().key);
+
+        // Restoring the session must not require authenticating again
+        let mut device = Device::from_session_with_transport(&session, Arc::new(sim.transport()))
+            .expect("Could not restore session!");
+        assert_eq!(device.get_session(), session);
+
+        // Simulate a reboot, which invalidates the saved key
+        let new_key = [0x42; 16];
+        sim.state().key = new_key;
+
+        let remote = match &mut device {
+            Device::Remote { remote } => remote,
+            _ => panic!("Simulator did not report a remote!"),
+        };
+        remote
+            .send_code(&[0x26, 0x00])
+            .expect("Could not send code after reboot!");
+        assert_eq!(remote.info.key, new_key);
+        assert_eq!(sim.state().sent_codes.len(), 1);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn simulated_async_session_reauthenticates_after_reboot() {
+        let sim = Simulator::start(SimulatorConfig::default()).expect("Could not start simulator!");
+        let mut device = connect_to_simulator_async(&sim).await;
+        assert_eq!(device.get_info().key, sim.config().key);
+
+        // Simulate a reboot, which invalidates the saved key
+        let new_key = [0x42; 16];
+        sim.state().key = new_key;
+
+        let remote = match &mut device {
+            Device::Remote { remote } => remote,
+            _ => panic!("Simulator did not report a remote!"),
+        };
+        remote
+            .send_code_async(&[0x26, 0x00])
+            .await
+            .expect("Could not send code after reboot!");
+        assert_eq!(remote.info.key, new_key);
+        assert_eq!(sim.state().sent_codes.len(), 1);
+    }
+
+    #[test]
+    fn simulated_failed_authentication_keeps_session() {
+        let sim = Simulator::start(SimulatorConfig::default()).expect("Could not start simulator!");
+        let transport = Arc::new(LossyTransport {
+            inner: sim.transport(),
+            drops: AtomicUsize::new(0),
+        });
+
+        let mut device = connect_to_simulator(&sim);
+        let session = device.get_session();
+        device.set_transport(transport.clone());
+        device.set_retry_policy(RetryPolicy::never());
+
+        // A lost authentication response leaves the previous session usable
+        transport.drops.store(1, Ordering::SeqCst);
+        assert!(matches!(device.authenticate(), Err(Error::Timeout)));
+        assert_eq!(device.get_session(), session);
+
+        let remote = match &mut device {
+            Device::Remote { remote } => remote,
+            _ => panic!("Simulator did not report a remote!"),
+        };
+        remote
+            .send_code(&[0x26, 0x00])
+            .expect("Could not send code with the previous session!");
+        assert_eq!(sim.state().sent_codes.len(), 1);
+    }
+
+    #[test]
+    fn simulated_commands_retry_lost_packets() {
+        let sim = Simulator::start(SimulatorConfig::default()).expect("Could not start simulator!");
+        let transport = Arc::new(LossyTransport {
+            inner: sim.transport(),
+            drops: AtomicUsize::new(0),
+        });
+
+        let mut device = connect_to_simulator(&sim);
+        device.set_transport(transport.clone());
+        device.set_retry_policy(RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_millis(10),
+            reauthenticate: true,
+        });
+
+        let mut remote = match device {
+            Device::Remote { remote } => remote,
+            _ => panic!("Simulator did not report a remote!"),
+        };
+
+        // Two lost packets are recovered from
+        transport.drops.store(2, Ordering::SeqCst);
+        remote
+            .send_code(&[0x26, 0x00])
+            .expect("Could not send code over a lossy transport!");
+        assert_eq!(sim.state().sent_codes.len(), 1);
+
+        // Unless retrying is disabled
+        remote.retry_policy = RetryPolicy::never();
+        transport.drops.store(1, Ordering::SeqCst);
+        assert!(matches!(
+            remote.send_code(&[0x26, 0x00]),
+            Err(Error::Timeout)
+        ));
+        assert_eq!(sim.state().sent_codes.len(), 1);
+    }
+
+    #[test]
+    fn network_config_limits_waiting_time() {
+        // A socket which never answers
+        let silent = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).expect("Could not bind socket!");
+        let config = NetworkConfig {
+            bind_addr: Ipv4Addr::LOCALHOST,
+            discovery_port: 0,
+            device_port: silent.local_addr().unwrap().port(),
+            discovery_timeout: Duration::from_millis(100),
+            command_timeout: Duration::from_millis(100),
+        };
+
+        let start = Instant::now();
+        let result =
+            Device::from_ip_with_config(Ipv4Addr::LOCALHOST, Some(Ipv4Addr::LOCALHOST), config);
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn udp_transport_announces_bound_discovery_port() {
+        // Both a configured port and any free port must be announced as the one sent from
+        for discovery_port in [42425, 0] {
+            let device = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).expect("Could not bind!");
+            let transport = UdpTransport::with_config(NetworkConfig {
+                bind_addr: Ipv4Addr::LOCALHOST,
+                discovery_port,
+                device_port: device.local_addr().expect("Could not get address!").port(),
+                ..Default::default()
+            });
+
+            // Answer with the port announced in the message and the one it was sent from
+            let responder = std::thread::spawn(move || {
+                let mut buffer = [0u8; 2];
+                let (_, addr) = device.recv_from(&mut buffer).expect("Could not receive!");
+                let announced = u16::from_le_bytes(buffer);
+                device
+                    .send_to(
+                        &[announced.to_le_bytes(), addr.port().to_le_bytes()].concat(),
+                        addr,
+                    )
+                    .expect("Could not answer!");
+            });
+
+            let (response, _) = transport
+                .discover_one(&|port| Ok(port.to_le_bytes().to_vec()), Ipv4Addr::LOCALHOST)
+                .expect("Could not discover!");
+            responder.join().expect("Responder panicked!");
+
+            assert_ne!(response[0..2], [0, 0]);
+            assert_eq!(response[0..2], response[2..4]);
+            if discovery_port != 0 {
+                assert_eq!(response[0..2], discovery_port.to_le_bytes());
+            }
+        }
+    }
+
+    #[test]
+    fn curtain_data_packs_correctly() {
+        // Calculated using the python-broadlink library
+        let expected: &[u8] = &[
+            0x09, 0x00, 0xBB, 0x06, 0x5D, 0x00, 0x00, 0x00, 0x00, 0xFA, 0x44, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        let actual = CurtainDataMessage::new(CurtainCommand::GetPosition)
+            .pack()
+            .expect("Could not pack test curtain message!");
+
+        assert_eq!(expected, &actual);
+    }
+
     #[test]
     fn discovery_packs_correctly() {
         // Note: No idea why we must +1 on the minute, but this test will fail otherwise
         let discover = DiscoveryMessage::new(
             IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)),
             42424,
-            Some(DateTime::from_utc(
-                NaiveDate::from_ymd(2000, 2, 14).and_hms(10, 30 + 1, 0),
-                TimeZone::from_offset(&FixedOffset::west(5)),
+            Some(DateTime::from_naive_utc_and_offset(
+                NaiveDate::from_ymd_opt(2000, 2, 14)
+                    .and_then(|d| d.and_hms_opt(10, 30 + 1, 0))
+                    .expect("Invalid test date!"),
+                FixedOffset::west_opt(5).expect("Invalid test offset!"),
             )),
         )
         .expect("Could not construct DiscoveryMessage!");
@@ -92,6 +1367,353 @@ mod tests {
             .expect("Could not pack test remote data message!");
 
         assert_eq!(expected, &actual);
+
+        // Older remotes do not expect the length
+        let legacy = RemoteDataMessage::new(RemoteDataCommand::SendCode)
+            .pack_legacy_with_payload(&payload)
+            .expect("Could not pack test legacy remote data message!");
+        assert_eq!(&expected[2..], &legacy);
+    }
+
+    #[test]
+    fn ir_code_round_trips() {
+        // A learned code, padded to 16 bytes, ending with the terminator
+        let learned: &[u8] = &[
+            0x26, 0x00, 0x08, 0x00, 0x10, 0x20, 0x00, 0x01, 0x00, 0x00, 0x0D, 0x05, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        let code = IrCode::from_bytes(learned).expect("Could not parse IR code!");
+        assert_eq!(code.repeat, 0);
+        assert_eq!(code.durations, vec![525, 1051, 8407, 109456]);
+        assert_eq!(
+            code.to_bytes().expect("Could not encode IR code!"),
+            &learned[0..12]
+        );
+
+        // Codes ending with a pulse get the terminator appended
+        let mut code = IrCode::new(vec![9000, 4500, 560]);
+        code.repeat = 2;
+        assert_eq!(
+            code.to_bytes().expect("Could not encode IR code!"),
+            vec![0x26, 0x02, 0x08, 0x00, 0x00, 0x01, 0x12, 0x89, 0x11, 0x00, 0x0D, 0x05]
+        );
+
+        // RF codes are told apart by their type
+        let rf = RfCode::new(RfBand::Rf315, vec![328, 985]);
+        let bytes = rf.to_bytes().expect("Could not encode RF code!");
+        assert_eq!(bytes, vec![0xD7, 0x00, 0x02, 0x00, 0x0A, 0x1E]);
+        assert_eq!(
+            RfCode::from_bytes(&bytes).expect("Could not parse RF code!"),
+            rf
+        );
+        assert!(matches!(
+            IrCode::from_bytes(&bytes),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn pronto_code_round_trips() {
+        let hex = "0000 006D 0002 0001 0156 00AB 0015 0040 0156 0055";
+        let pronto: ProntoCode = hex.parse().expect("Could not parse Pronto code!");
+        assert_eq!(pronto.frequency, 38029);
+        assert_eq!(pronto.once, vec![8993, 4497, 552, 1683]);
+        assert_eq!(pronto.repeat, vec![8993, 2235]);
+        assert_eq!(pronto.to_string(), hex);
+        assert_eq!(
+            pronto
+                .to_pronto_hex()
+                .expect("Could not encode Pronto code!"),
+            hex
+        );
+
+        // The repeat sequence follows the one sent once
+        let code = pronto.to_ir_code(1);
+        assert_eq!(code.repeat, 0);
+        assert_eq!(
+            code.durations,
+            vec![8993, 4497, 552, 1683, 8993, 2235, 8993, 2235]
+        );
+
+        // Codes ending with a pulse get the terminator appended
+        let exported = ProntoCode::from_ir_code(&IrCode::new(vec![8993, 4497, 552]), 38029);
+        assert_eq!(
+            exported.to_string(),
+            "0000 006D 0002 0000 0156 00AB 0015 1042"
+        );
+
+        // Codes which cannot be encoded are reported as errors, without failing to format
+        let invalid = [
+            ProntoCode::from_ir_code(&IrCode::new(vec![8993, 4497]), 0),
+            ProntoCode {
+                frequency: 38029,
+                once: vec![8993],
+                repeat: vec![],
+            },
+            ProntoCode::from_ir_code(&IrCode::new(vec![u32::MAX, 4497]), 38029),
+        ];
+        for code in invalid {
+            assert!(matches!(
+                code.to_pronto_hex(),
+                Err(Error::InvalidArgument(_))
+            ));
+            assert!(code.to_string().starts_with("<invalid Pronto code"));
+        }
+
+        assert!(matches!(
+            "0100 006D 0000 0000".parse::<ProntoCode>(),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            "0000 006D 0002 0000 0156".parse::<ProntoCode>(),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn protocol_codes_encode_correctly() {
+        // NEC frames are followed by short repeat frames
+        let mut nec = ProtocolCode::new(IrProtocol::Nec, 0x04, 0x08);
+        nec.repeats = 1;
+        let code = nec.to_ir_code().expect("Could not encode NEC code!");
+        assert_eq!(code.repeat, 0);
+        assert_eq!(code.durations.len(), 2 + 64 + 2 + 4);
+        assert_eq!(&code.durations[0..6], &[9000, 4500, 560, 560, 560, 560]);
+        assert_eq!(&code.durations[68..], &[9000, 2250, 560, 96190]);
+        assert_eq!(code.durations.iter().sum::<u32>(), 2 * 108_000);
+
+        // RC5 ones are a space followed by a pulse, with the leading space dropped
+        let mut rc5 = ProtocolCode::new(IrProtocol::Rc5, 0x05, 0x35);
+        rc5.toggle = true;
+        rc5.repeats = 2;
+        let code = rc5.to_ir_code().expect("Could not encode RC5 code!");
+        assert_eq!(code.repeat, 2);
+        assert_eq!(
+            &code.durations[0..18],
+            &[
+                889, 889, 889, 889, 1778, 889, 889, 1778, 1778, 1778, 889, 889, 889, 889, 1778,
+                1778, 1778, 1778
+            ]
+        );
+
+        // Sony codes send the command first, then the address
+        let sony = ProtocolCode::new(IrProtocol::Sony, 0x01, 0x15);
+        let code = sony.to_ir_code().expect("Could not encode Sony code!");
+        assert_eq!(
+            code.durations,
+            vec![
+                2400, 600, 1200, 600, 600, 600, 1200, 600, 600, 600, 1200, 600, 600, 600, 600, 600,
+                1200, 600, 600, 600, 600, 600, 600, 600, 600, 25800
+            ]
+        );
+
+        assert_eq!(code.durations.iter().sum::<u32>(), 45_000);
+
+        let mut invalid = ProtocolCode::new(IrProtocol::Sony, 0x20, 0x15);
+        assert!(matches!(invalid.to_bytes(), Err(Error::InvalidArgument(_))));
+        invalid.bits = 15;
+        assert!(invalid.to_bytes().is_ok());
+        invalid.bits = 16;
+        assert!(matches!(invalid.to_bytes(), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn learned_codes_decode_correctly() {
+        let protocols = [
+            IrProtocol::Nec,
+            IrProtocol::NecExtended,
+            IrProtocol::Rc5,
+            IrProtocol::Rc6,
+            IrProtocol::Sony,
+            IrProtocol::Samsung,
+            IrProtocol::Panasonic,
+        ];
+        for protocol in protocols {
+            let mut code = ProtocolCode::new(protocol, 0x12, 0x41);
+            code.repeats = 2;
+            code.toggle = matches!(protocol, IrProtocol::Rc5 | IrProtocol::Rc6);
+
+            // Learned pulses tend to be longer, and spaces shorter
+            let mut learned = code.to_ir_code().expect("Could not encode code!");
+            for (i, duration) in learned.durations.iter_mut().enumerate() {
+                if i % 2 == 0 {
+                    *duration += 60;
+                } else {
+                    *duration -= 60;
+                }
+            }
+
+            let bytes = learned.to_bytes().expect("Could not encode IR code!");
+            match DecodedCode::from_bytes(&bytes).expect("Could not decode IR code!") {
+                DecodedCode::Protocol {
+                    code: decoded,
+                    confidence,
+                } => {
+                    assert_eq!(decoded, code);
+                    assert!(confidence > 0.8 && confidence <= 1.0);
+                }
+                unknown => panic!("Could not decode {:?} code! Got {}", protocol, unknown),
+            };
+        }
+
+        let nec = ProtocolCode::new(IrProtocol::Nec, 0x04, 0x08)
+            .to_ir_code()
+            .expect("Could not encode NEC code!");
+        assert_eq!(
+            DecodedCode::from_ir_code(&nec).to_string(),
+            "NEC addr 0x04 cmd 0x08"
+        );
+
+        // Codes of other protocols keep their durations
+        let unknown = IrCode::new(vec![3000, 3000, 500, 500, 500]);
+        assert_eq!(
+            DecodedCode::from_ir_code(&unknown),
+            DecodedCode::Unknown {
+                durations: unknown.durations.clone()
+            }
+        );
+    }
+
+    #[test]
+    fn lirc_config_round_trips() {
+        let config = "
+# A NEC remote, using protocol parameters
+begin remote
+  name  tv
+  bits           16
+  flags SPACE_ENC|CONST_LENGTH
+  header       9000  4500
+  one           560  1680
+  zero          560   560
+  ptrail        560
+  pre_data_bits  16
+  pre_data   0x20DF
+  gap        108000
+
+      begin codes
+          KEY_POWER    0x10EF # Address 0x04, command 0x08
+      end codes
+end remote
+
+begin remote
+  name  vcr
+  bits           13
+  flags RC5|CONST_LENGTH
+  one           889   889
+  zero          889   889
+  plead         889
+  gap        113778
+
+      begin codes
+          KEY_PLAY     0x1975
+      end codes
+end remote
+";
+        let remotes = LircRemote::from_config(config).expect("Could not parse LIRC config!");
+        assert_eq!(remotes.len(), 2);
+        assert_eq!(remotes[0].name, "tv");
+        assert_eq!(
+            remotes[0].get_code("KEY_POWER"),
+            Some(
+                &ProtocolCode::new(IrProtocol::Nec, 0x04, 0x08)
+                    .to_ir_code()
+                    .expect("Could not encode NEC code!")
+            )
+        );
+
+        let mut rc5 = ProtocolCode::new(IrProtocol::Rc5, 0x05, 0x35);
+        rc5.toggle = true;
+        assert_eq!(
+            remotes[1].get_code("KEY_PLAY"),
+            Some(&rc5.to_ir_code().expect("Could not encode RC5 code!"))
+        );
+
+        // Learned codes are exported as raw codes, sharing the gap of the remote
+        let mut remote = LircRemote::new("learned");
+        for (name, durations) in [
+            ("KEY_1", vec![9000, 4500, 560, 1680, 560, 40000]),
+            ("KEY_2", vec![3456, 1728, 432, 432, 432, 1296, 432, 40000]),
+        ] {
+            remote.buttons.push(LircButton {
+                name: name.into(),
+                code: IrCode::new(durations),
+            });
+        }
+
+        let exported = remote.to_config().expect("Could not export LIRC config!");
+        assert!(exported.contains("flags RAW_CODES"));
+        assert!(exported.contains("gap          40000"));
+        assert_eq!(
+            LircRemote::from_config(&exported).expect("Could not parse exported config!"),
+            vec![remote.clone()]
+        );
+
+        assert!(matches!(
+            LircRemote::from_config("begin remote\n  name broken\n"),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        // Codes wider than 64 bits cannot be encoded
+        for key in ["bits", "pre_data_bits", "post_data_bits"] {
+            let config = format!("begin remote\n  name wide\n  {} 65\nend remote\n", key);
+            assert!(matches!(
+                LircRemote::from_config(&config),
+                Err(Error::InvalidArgument(_))
+            ));
+        }
+
+        // Names which could not be read back are not exported
+        remote.buttons[0].name = "KEY 1".into();
+        assert!(matches!(remote.to_config(), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn json_data_packs_correctly() {
+        let message = JsonDataMessage::new(JsonDataCommand::GetState);
+
+        // Calculated using the python-broadlink library
+        let expected: &[u8] = &[
+            0xA5, 0xA5, 0x5A, 0x5A, 0xB3, 0xC1, 0x01, 0x0B, 0x02, 0x00, 0x00, 0x00, 0x7B, 0x7D,
+        ];
+        let actual = message
+            .pack_with_payload("{}", false)
+            .expect("Could not pack test JSON message!");
+
+        assert_eq!(expected, &actual);
+        assert_eq!(
+            JsonDataMessage::unpack_with_payload(&actual, false).expect("Could not unpack!"),
+            "{}"
+        );
+    }
+
+    #[test]
+    fn power_strip_packs_correctly() {
+        // Calculated using the python-broadlink library
+        let expected: &[u8] = &[
+            0x0D, 0x00, 0xA5, 0xA5, 0x5A, 0x5A, 0xB6, 0xC0, 0x02, 0x00, 0x03, 0x00, 0x00, 0x02,
+            0x02, 0x00,
+        ];
+        let actual = PowerStripDataMessage::set_state(0x02, true)
+            .pack_with_checksum()
+            .expect("Could not pack test power strip message!");
+
+        assert_eq!(expected, &actual);
+    }
+
+    #[test]
+    fn thermostat_data_packs_correctly() {
+        // Calculated using the python-broadlink library
+        let expected: &[u8] = &[0x08, 0x00, 0x01, 0x03, 0x00, 0x00, 0x00, 0x16, 0xC4, 0x04];
+        let actual = ThermostatDataMessage::new(ThermostatDataCommand::ReadRegisters, 0x00)
+            .pack_with_payload(&[0x00, 0x16])
+            .expect("Could not pack test thermostat message!");
+
+        assert_eq!(expected, &actual);
+        assert_eq!(
+            ThermostatDataMessage::unpack_with_payload(&actual)
+                .expect("Could not unpack test thermostat message!"),
+            &expected[0x02..0x08]
+        );
     }
 
     #[test]
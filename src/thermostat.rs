@@ -0,0 +1,353 @@
+use std::{net::Ipv4Addr, sync::Arc};
+
+use phf::phf_map;
+
+use crate::{
+    constants, lookup_model,
+    network::{
+        util::reverse_mac, DiscoveryResponse, ThermostatDataCommand, ThermostatDataMessage,
+        ThermostatLoopMode, ThermostatMode, ThermostatSchedule, ThermostatSensor, ThermostatStatus,
+        UdpTransport,
+    },
+    traits::Transport,
+    Device, DeviceInfo, Error, RetryPolicy,
+};
+
+/// A mapping of thermostat device codes to their friendly model equivalent.
+pub const THERMOSTAT_CODES: phf::Map<u16, &'static str> = phf_map! {
+    0x4EADu16 => "HY02/HY03",
+};
+
+/// The register holding the power and lock state.
+const REGISTER_POWER: u16 = 0x00;
+
+/// The register holding the target temperature.
+const REGISTER_TARGET_TEMP: u16 = 0x01;
+
+/// The register holding the mode and sensor.
+const REGISTER_MODE: u16 = 0x02;
+
+/// The first register of the weekly schedule.
+const REGISTER_SCHEDULE: u16 = 0x0A;
+
+/// The number of registers making up the full status.
+const STATUS_REGISTERS: u16 = 0x16;
+
+/// A Hysen / Beok thermostat, controlled through Modbus frames.
+#[derive(Debug, Clone)]
+pub struct ThermostatDevice {
+    /// Base information about the thermostat.
+    pub info: DeviceInfo,
+
+    /// The transport used to communicate with the device.
+    pub transport: Arc<dyn Transport>,
+
+    /// The policy used to retry failed commands sent to the device.
+    pub retry_policy: RetryPolicy,
+}
+
+impl ThermostatDevice {
+    /// Create a new ThermostatDevice.
+    ///
+    /// Note: This should not be called directly. Please use [Device::from_ip] or
+    /// [Device::list] instead.
+    pub fn new(name: &str, addr: Ipv4Addr, response: DiscoveryResponse) -> ThermostatDevice {
+        // Get the type of thermostat
+        let friendly_model: String = lookup_model(response.model_code)
+            .map_or("Unknown".into(), |model| model.name)
+            .to_string();
+
+        return Self {
+            info: DeviceInfo {
+                address: addr,
+                mac: reverse_mac(response.mac),
+                model_code: response.model_code,
+                friendly_type: "Thermostat".into(),
+                friendly_model,
+                name: name.into(),
+                auth_id: 0, // This will be populated when authenticated.
+                key: constants::INITIAL_KEY,
+                is_locked: response.is_locked,
+                interface: None,
+            },
+            transport: Arc::new(UdpTransport::default()),
+            retry_policy: RetryPolicy::default(),
+        };
+    }
+
+    /// Get the full status of the thermostat.
+    pub fn get_status(&mut self) -> Result<ThermostatStatus, Error> {
+        let response = self.send_command(
+            ThermostatDataCommand::ReadRegisters,
+            0x00,
+            &STATUS_REGISTERS.to_be_bytes(),
+        )?;
+
+        return ThermostatStatus::unpack_response(&response);
+    }
+
+    /// Set the target temperature, in degrees Celsius, rounded to half degrees.
+    ///
+    /// This switches a thermostat following its schedule to a manually set temperature.
+    pub fn set_target_temp(&mut self, temp: f32) -> Result<(), Error> {
+        let payload = target_temp_payload(temp)?;
+        self.send_command(
+            ThermostatDataCommand::WriteRegister,
+            REGISTER_TARGET_TEMP,
+            &payload,
+        )?;
+
+        return Ok(());
+    }
+
+    /// Switch the thermostat on or off, keeping the lock as-is. Its wifi stays connected.
+    pub fn set_power(&mut self, power: bool) -> Result<(), Error> {
+        let status = self.get_status()?;
+        let payload = power_payload(&status, power, status.remote_lock);
+        self.send_command(
+            ThermostatDataCommand::WriteRegister,
+            REGISTER_POWER,
+            &payload,
+        )?;
+
+        return Ok(());
+    }
+
+    /// Lock or unlock the buttons on the thermostat, keeping the power as-is.
+    pub fn set_remote_lock(&mut self, remote_lock: bool) -> Result<(), Error> {
+        let status = self.get_status()?;
+        let payload = power_payload(&status, status.power, remote_lock);
+        self.send_command(
+            ThermostatDataCommand::WriteRegister,
+            REGISTER_POWER,
+            &payload,
+        )?;
+
+        return Ok(());
+    }
+
+    /// Set the operating mode, which days follow the weekday schedule and the sensor used.
+    pub fn set_mode(
+        &mut self,
+        mode: ThermostatMode,
+        loop_mode: ThermostatLoopMode,
+        sensor: ThermostatSensor,
+    ) -> Result<(), Error> {
+        let payload = mode_payload(mode, loop_mode, sensor);
+        self.send_command(
+            ThermostatDataCommand::WriteRegister,
+            REGISTER_MODE,
+            &payload,
+        )?;
+
+        return Ok(());
+    }
+
+    /// Set the weekly schedule followed in [ThermostatMode::Auto].
+    pub fn set_schedule(&mut self, schedule: &ThermostatSchedule) -> Result<(), Error> {
+        let payload = schedule_payload(schedule)?;
+        self.send_command(
+            ThermostatDataCommand::WriteRegisters,
+            REGISTER_SCHEDULE,
+            &payload,
+        )?;
+
+        return Ok(());
+    }
+
+    /// Sends a raw Modbus command to the thermostat, returning the response frame.
+    /// Note: Try to avoid using this method in favor of [ThermostatDevice::get_status],
+    /// [ThermostatDevice::set_target_temp], etc.
+    pub fn send_command(
+        &mut self,
+        command: ThermostatDataCommand,
+        register: u16,
+        payload: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        // Construct the data message
+        let msg = ThermostatDataMessage::new(command, register);
+        let packed = msg.pack_with_payload(payload)?;
+
+        // We cast this object to a generic device in order to make use of the shared
+        // helper utilities.
+        let response = Device::Thermostat {
+            thermostat: self.clone(),
+        }
+        .send_command_for::<ThermostatDataMessage>(&mut self.info, &packed)?;
+
+        return ThermostatDataMessage::unpack_with_payload(&response);
+    }
+}
+
+/// Async versions of the thermostat operations, for use within a tokio runtime.
+#[cfg(feature = "tokio")]
+impl ThermostatDevice {
+    /// Async version of [ThermostatDevice::get_status].
+    pub async fn get_status_async(&mut self) -> Result<ThermostatStatus, Error> {
+        let response = self
+            .send_command_async(
+                ThermostatDataCommand::ReadRegisters,
+                0x00,
+                &STATUS_REGISTERS.to_be_bytes(),
+            )
+            .await?;
+
+        return ThermostatStatus::unpack_response(&response);
+    }
+
+    /// Async version of [ThermostatDevice::set_target_temp].
+    pub async fn set_target_temp_async(&mut self, temp: f32) -> Result<(), Error> {
+        let payload = target_temp_payload(temp)?;
+        self.send_command_async(
+            ThermostatDataCommand::WriteRegister,
+            REGISTER_TARGET_TEMP,
+            &payload,
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    /// Async version of [ThermostatDevice::set_power].
+    pub async fn set_power_async(&mut self, power: bool) -> Result<(), Error> {
+        let status = self.get_status_async().await?;
+        let payload = power_payload(&status, power, status.remote_lock);
+        self.send_command_async(
+            ThermostatDataCommand::WriteRegister,
+            REGISTER_POWER,
+            &payload,
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    /// Async version of [ThermostatDevice::set_remote_lock].
+    pub async fn set_remote_lock_async(&mut self, remote_lock: bool) -> Result<(), Error> {
+        let status = self.get_status_async().await?;
+        let payload = power_payload(&status, status.power, remote_lock);
+        self.send_command_async(
+            ThermostatDataCommand::WriteRegister,
+            REGISTER_POWER,
+            &payload,
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    /// Async version of [ThermostatDevice::set_mode].
+    pub async fn set_mode_async(
+        &mut self,
+        mode: ThermostatMode,
+        loop_mode: ThermostatLoopMode,
+        sensor: ThermostatSensor,
+    ) -> Result<(), Error> {
+        let payload = mode_payload(mode, loop_mode, sensor);
+        self.send_command_async(
+            ThermostatDataCommand::WriteRegister,
+            REGISTER_MODE,
+            &payload,
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    /// Async version of [ThermostatDevice::set_schedule].
+    pub async fn set_schedule_async(&mut self, schedule: &ThermostatSchedule) -> Result<(), Error> {
+        let payload = schedule_payload(schedule)?;
+        self.send_command_async(
+            ThermostatDataCommand::WriteRegisters,
+            REGISTER_SCHEDULE,
+            &payload,
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    /// Async version of [ThermostatDevice::send_command].
+    pub async fn send_command_async(
+        &mut self,
+        command: ThermostatDataCommand,
+        register: u16,
+        payload: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        // Construct the data message
+        let msg = ThermostatDataMessage::new(command, register);
+        let packed = msg.pack_with_payload(payload)?;
+
+        let response = Device::Thermostat {
+            thermostat: self.clone(),
+        }
+        .send_command_for_async::<ThermostatDataMessage>(&mut self.info, &packed)
+        .await?;
+
+        return ThermostatDataMessage::unpack_with_payload(&response);
+    }
+}
+
+/// Encodes a temperature in half degrees, as stored by the thermostat.
+fn encode_temp(temp: f32) -> Result<u8, Error> {
+    if !(5.0..=99.0).contains(&temp) {
+        return Err(Error::InvalidArgument(format!(
+            "Temperature is out of range (5-99), got {}",
+            temp
+        )));
+    }
+
+    return Ok((temp * 2.0).round() as u8);
+}
+
+/// Builds the value of the target temperature register.
+fn target_temp_payload(temp: f32) -> Result<[u8; 2], Error> {
+    return Ok([0x00, encode_temp(temp)?]);
+}
+
+/// Builds the value of the power register, keeping the heating / cooling flag as-is.
+fn power_payload(status: &ThermostatStatus, power: bool, remote_lock: bool) -> [u8; 2] {
+    return [
+        u8::from(remote_lock),
+        u8::from(status.cooling) << 7 | u8::from(power),
+    ];
+}
+
+/// Builds the value of the mode register.
+fn mode_payload(
+    mode: ThermostatMode,
+    loop_mode: ThermostatLoopMode,
+    sensor: ThermostatSensor,
+) -> [u8; 2] {
+    return [
+        (u8::from(loop_mode) & 0x0F) << 4 | u8::from(mode) & 0x0F,
+        u8::from(sensor),
+    ];
+}
+
+/// Builds the values of the schedule registers: the start of every period, then their temperatures.
+fn schedule_payload(schedule: &ThermostatSchedule) -> Result<Vec<u8>, Error> {
+    let periods = schedule.weekday.iter().chain(schedule.weekend.iter());
+
+    let mut times = vec![];
+    let mut temps = vec![];
+    for period in periods {
+        if period.start_hour > 23 || period.start_minute > 59 {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid start of a period: {:02}:{:02}",
+                period.start_hour, period.start_minute
+            )));
+        }
+
+        times.extend([period.start_hour, period.start_minute]);
+        temps.push(encode_temp(period.temp)?);
+    }
+
+    // Register count, then the byte count of the values
+    let values = [times, temps].concat();
+    let mut payload = ((values.len() / 2) as u16).to_be_bytes().to_vec();
+    payload.push(values.len() as u8);
+    payload.extend(values);
+
+    return Ok(payload);
+}
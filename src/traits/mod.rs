@@ -2,6 +2,8 @@
 
 mod command_trait;
 mod device_trait;
+mod transport_trait;
 
 pub use command_trait::*;
 pub use device_trait::*;
+pub use transport_trait::*;
@@ -0,0 +1,142 @@
+use std::{
+    fmt,
+    net::{Ipv4Addr, SocketAddr},
+};
+
+#[cfg(feature = "tokio")]
+use std::{future::Future, pin::Pin};
+
+use crate::{network::NetworkConfig, Error};
+
+/// The future returned by the async methods of a [Transport].
+#[cfg(feature = "tokio")]
+pub type TransportFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'a>>;
+
+/// Builds a discovery message announcing the specified local port, which devices answer to.
+pub type DiscoveryBuilder<'a> = &'a (dyn Fn(u16) -> Result<Vec<u8>, Error> + Send + Sync);
+
+/// A datagram channel used to exchange messages with broadlink devices.
+///
+/// The default implementation is [crate::network::UdpTransport], but any channel capable of
+/// delivering the packed messages (e.g. a relay host or an in-memory fake) can be used instead.
+pub trait Transport: fmt::Debug + Send + Sync {
+    /// Sends a message to the specified address and returns the first response, along with
+    /// the address of its sender.
+    ///
+    /// The local port is used when the device is expected to answer on a known port, such
+    /// as during discovery.
+    fn send_and_receive_one(
+        &self,
+        msg: &[u8],
+        addr: Ipv4Addr,
+        local_port: Option<u16>,
+    ) -> Result<(Vec<u8>, SocketAddr), Error>;
+
+    /// Sends a message to the specified address and returns as many responses as possible,
+    /// along with the address of their senders.
+    fn send_and_receive_many(
+        &self,
+        msg: &[u8],
+        addr: Ipv4Addr,
+        local_port: Option<u16>,
+    ) -> Result<Vec<(Vec<u8>, SocketAddr)>, Error>;
+
+    /// Async version of [Transport::send_and_receive_one], used by every async operation.
+    ///
+    /// Note: The default implementation calls the blocking method, which blocks the runtime
+    /// until it returns. Override it for transports which can wait asynchronously.
+    #[cfg(feature = "tokio")]
+    fn send_and_receive_one_async<'a>(
+        &'a self,
+        msg: &'a [u8],
+        addr: Ipv4Addr,
+        local_port: Option<u16>,
+    ) -> TransportFuture<'a, (Vec<u8>, SocketAddr)> {
+        return Box::pin(async move {
+            return self.send_and_receive_one(msg, addr, local_port);
+        });
+    }
+
+    /// Async version of [Transport::send_and_receive_many], used by every async operation.
+    ///
+    /// Note: The default implementation calls the blocking method, which blocks the runtime
+    /// until it returns. Override it for transports which can wait asynchronously.
+    #[cfg(feature = "tokio")]
+    fn send_and_receive_many_async<'a>(
+        &'a self,
+        msg: &'a [u8],
+        addr: Ipv4Addr,
+        local_port: Option<u16>,
+    ) -> TransportFuture<'a, Vec<(Vec<u8>, SocketAddr)>> {
+        return Box::pin(async move {
+            return self.send_and_receive_many(msg, addr, local_port);
+        });
+    }
+
+    /// Get the network settings of this transport.
+    fn config(&self) -> NetworkConfig {
+        return NetworkConfig::default();
+    }
+
+    /// Sends a discovery message to the specified address and returns the first response,
+    /// along with the address of its sender.
+    ///
+    /// The message is built for the local port that responses are received on. The default
+    /// implementation announces the configured discovery port. Transports which can pick any
+    /// free port must announce the port of the socket that the responses are received on.
+    fn discover_one(
+        &self,
+        build: DiscoveryBuilder,
+        addr: Ipv4Addr,
+    ) -> Result<(Vec<u8>, SocketAddr), Error> {
+        let port = self.config().discovery_port;
+
+        return self.send_and_receive_one(&build(port)?, addr, Some(port));
+    }
+
+    /// Sends a discovery message to the specified address and returns as many responses as
+    /// possible, along with the address of their senders. Refer to [Transport::discover_one].
+    fn discover_many(
+        &self,
+        build: DiscoveryBuilder,
+        addr: Ipv4Addr,
+    ) -> Result<Vec<(Vec<u8>, SocketAddr)>, Error> {
+        let port = self.config().discovery_port;
+
+        return self.send_and_receive_many(&build(port)?, addr, Some(port));
+    }
+
+    /// Async version of [Transport::discover_one].
+    #[cfg(feature = "tokio")]
+    fn discover_one_async<'a>(
+        &'a self,
+        build: DiscoveryBuilder<'a>,
+        addr: Ipv4Addr,
+    ) -> TransportFuture<'a, (Vec<u8>, SocketAddr)> {
+        return Box::pin(async move {
+            let port = self.config().discovery_port;
+            let msg = build(port)?;
+
+            return self
+                .send_and_receive_one_async(&msg, addr, Some(port))
+                .await;
+        });
+    }
+
+    /// Async version of [Transport::discover_many].
+    #[cfg(feature = "tokio")]
+    fn discover_many_async<'a>(
+        &'a self,
+        build: DiscoveryBuilder<'a>,
+        addr: Ipv4Addr,
+    ) -> TransportFuture<'a, Vec<(Vec<u8>, SocketAddr)>> {
+        return Box::pin(async move {
+            let port = self.config().discovery_port;
+            let msg = build(port)?;
+
+            return self
+                .send_and_receive_many_async(&msg, addr, Some(port))
+                .await;
+        });
+    }
+}